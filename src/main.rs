@@ -6,25 +6,54 @@
 )]
 pub mod staking_parachain {}
 
+use color_eyre::eyre::{bail, eyre};
 use rand::prelude::*;
+use std::{path::PathBuf, str::FromStr};
 use structopt::StructOpt;
 
 use subxt::{
-    utils::{AccountId32, MultiAddress, MultiAddress::Id},
+    blocks::ExtrinsicEvents,
+    config::{DefaultExtrinsicParams, DefaultExtrinsicParamsBuilder, ExtrinsicParams},
+    ext::codec::{DecodeAll, Encode},
+    storage::Storage,
+    tx::TxProgress,
+    utils::{AccountId32, MultiAddress, MultiAddress::Id, H256},
     OnlineClient, SubstrateConfig,
 };
-use subxt_signer::sr25519::dev;
+use subxt_signer::{
+    sr25519::{dev, Keypair},
+    SecretUri,
+};
 
 use crate::staking_parachain::{
+    nomination_pools::events::{PaidOut, PoolCommissionClaimed},
     runtime_types::{
-        pallet_balances::pallet::Call as BalancesCall, pallet_staking::RewardDestination,
-        sp_arithmetic::per_things::Perbill, staking_rococo_runtime::RuntimeCall,
+        pallet_bags_list::pallet::Call as VoterListCall,
+        pallet_balances::pallet::Call as BalancesCall,
+        pallet_election_provider_multi_block::types::Phase,
+        pallet_nomination_pools::pallet::Call as PoolsCall,
+        pallet_nomination_pools::{
+            BondExtra, CommissionChangeRate, ConfigOp, PoolMember, PoolState,
+        },
+        pallet_staking::pallet::pallet::Call as StakingCall,
+        pallet_staking::RewardDestination,
+        pallet_sudo::pallet::Call as SudoCall,
+        pallet_utility::pallet::Call as UtilityCall,
+        sp_arithmetic::per_things::Perbill,
+        sp_npos_elections::ElectionScore,
+        sp_staking::PagedExposureMetadata,
+        sp_weights::weight_v2::Weight,
+        staking_rococo_runtime::RuntimeCall,
     },
     staking::calls::types::nominate::Targets,
+    sudo::events::Sudid,
+    transaction_payment::events::TransactionFeePaid,
+    utility::events::{BatchInterrupted, ItemCompleted, ItemFailed},
 };
 
 type Balance = u128; // fetch from Metadata
 type Target = MultiAddress<AccountId32, ()>;
+type Voter = (AccountId32, u64, Vec<AccountId32>);
 
 /// CLI for easy interaction with the staking-parachain.
 #[derive(Debug, StructOpt)]
@@ -41,12 +70,16 @@ enum Command {
         /// The id of the destination parachain.
         #[structopt(long, default_value = "2000")]
         parachain_id: u32,
-        /// The number  of new validators.
+        /// The number of CLI-created validators to have, including the existing ones.
         #[structopt(long, default_value = "10")]
         number: usize,
         /// Balance to bond with
         #[structopt(long, default_value = "1000000000000")]
         bond_amount: Balance,
+        /// Reward destination: `staked`, `stash`, `none`, `account:<ADDRESS>` or `random` for a
+        /// random mix of them (paying to the signer account).
+        #[structopt(long, default_value = "staked")]
+        payee: Payee,
         #[structopt(long)]
         alice: bool,
         /// RPC and signer configs.
@@ -58,7 +91,7 @@ enum Command {
         /// The id of the destination parachain.
         #[structopt(long, default_value = "2000")]
         parachain_id: u32,
-        /// The number  of new validators.
+        /// The number of CLI-created nominators to have, including the existing ones.
         #[structopt(long, default_value = "10")]
         number: usize,
         /// Balance to bond with
@@ -67,6 +100,10 @@ enum Command {
         /// The approx number of nominations per voter.
         #[structopt(long, default_value = "6")]
         nominations: usize,
+        /// Reward destination: `staked`, `stash`, `none`, `account:<ADDRESS>` or `random` for a
+        /// random mix of them (paying to the signer account).
+        #[structopt(long, default_value = "staked")]
+        payee: Payee,
         #[structopt(long)]
         alice: bool,
         /// RPC and signer configs.
@@ -78,6 +115,645 @@ enum Command {
         #[structopt(flatten)]
         configs: Configs,
     },
+    /// Validator queries.
+    #[structopt(name = "validators")]
+    Validators(ValidatorsCommand),
+    /// Election queries.
+    #[structopt(name = "election")]
+    Election(ElectionCommand),
+    /// Long running monitors.
+    #[structopt(name = "monitor")]
+    Monitor(MonitorCommand),
+    /// Shows the reward points earned by each validator in an era.
+    #[structopt(name = "era-points")]
+    EraPoints {
+        /// Era to query. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        /// Only show the validators created by the CLI.
+        #[structopt(long)]
+        managed: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Moves all the voter list nodes which are in the wrong bag to the bag of their current
+    /// score.
+    #[structopt(name = "rebag-all")]
+    RebagAll {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Moves a CLI-created nominator in front of the first lighter node ahead of it in its bag.
+    #[structopt(name = "put-in-front-of")]
+    PutInFrontOf {
+        /// Stash of the CLI-created nominator to move.
+        #[structopt(long)]
+        nominator: AccountId32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Re-randomizes the targets of a fraction of the CLI-created nominators, once or every
+    /// `--every` eras.
+    #[structopt(name = "rotate-nominations")]
+    RotateNominations {
+        /// Fraction (0, 1] of the CLI-created nominators to rotate.
+        #[structopt(long, default_value = "0.1")]
+        fraction: f64,
+        /// The approx number of nominations per voter.
+        #[structopt(long, default_value = "6")]
+        nominations: usize,
+        /// Keeps running, rotating every `every` eras.
+        #[structopt(long)]
+        every: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Keeps running for `--eras` eras, bonding `--step` more (or unbonding it) for every bonded
+    /// CLI-created staker once per era, to drift the election inputs gradually.
+    #[structopt(name = "ramp")]
+    Ramp {
+        /// Balance bonded extra, or unbonded, per staker and era.
+        #[structopt(long)]
+        step: Balance,
+        /// Unbonds the step instead, never below the minimum bond of the staker's role.
+        #[structopt(long)]
+        decrease: bool,
+        /// Number of eras to ramp the bonds for.
+        #[structopt(long, default_value = "5")]
+        eras: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Raises the minimum validator commission with sudo and reports the validators below it.
+    #[structopt(name = "set-min-commission")]
+    SetMinCommission {
+        /// New minimum commission, in percent.
+        #[structopt(long)]
+        percent: f64,
+        /// Raises the commission of the validators below the new minimum with
+        /// `force_apply_min_commission`.
+        #[structopt(long)]
+        force_apply: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Advances the chain by a number of eras by forcing a new era every session, with sudo.
+    #[structopt(name = "fast-forward")]
+    FastForward {
+        /// Number of eras to advance.
+        #[structopt(long, default_value = "1")]
+        eras: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Chills and unbonds all the CLI-created stakers and, with `--daemon`, waits for the
+    /// bonding duration to withdraw and reap them.
+    #[structopt(name = "full-exit")]
+    FullExit {
+        /// Keeps running until the funds can be withdrawn, then withdraws and reaps the accounts.
+        #[structopt(long)]
+        daemon: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Transfers away the balances of the CLI-created accounts so that they are reaped, and
+    /// removes them from the state file.
+    #[structopt(name = "reap")]
+    Reap {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Adds or removes tags of the CLI-created accounts of the chain, those with all the `--tag`
+    /// tags, so that later commands can target them with `--tag`.
+    #[structopt(name = "tag")]
+    Tag {
+        /// Tags added to the accounts.
+        #[structopt(long)]
+        add: Vec<String>,
+        /// Tags removed from the accounts.
+        #[structopt(long)]
+        remove: Vec<String>,
+        /// Only tags the accounts with this role: `validator` or `nominator`.
+        #[structopt(long)]
+        role: Option<state::Role>,
+        /// Only tags the first this many accounts.
+        #[structopt(long)]
+        first: Option<usize>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Reports how many blocks each session validator authored over a block range versus its
+    /// expected share, flagging the CLI-created validators that never authored.
+    #[structopt(name = "authorship")]
+    Authorship {
+        /// First block of the range.
+        #[structopt(long)]
+        from: u32,
+        /// Last block of the range. Defaults to the latest finalized block.
+        #[structopt(long)]
+        to: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Writes the raw storage of the staking, voter list and election pallets to a file.
+    #[structopt(name = "dump")]
+    Dump {
+        /// File the dump is written to.
+        #[structopt(long)]
+        out: PathBuf,
+        /// Block to dump. Defaults to the latest finalized block.
+        #[structopt(long)]
+        at: Option<H256>,
+        /// Pallets to dump. Defaults to the staking, voter list and election pallets.
+        #[structopt(long, use_delimiter = true)]
+        pallets: Vec<String>,
+        /// SCALE encodes the dump instead of writing it as JSON.
+        #[structopt(long)]
+        scale: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Writes the entries of a storage dump onto a chain with sudo `set_storage`, or into a raw
+    /// genesis patch.
+    #[structopt(name = "restore")]
+    Restore {
+        /// Dump written by `dump`.
+        #[structopt(long)]
+        dump: PathBuf,
+        /// Removes the storage of the dumped pallets before writing the dump.
+        #[structopt(long)]
+        clear: bool,
+        /// Number of storage items written per `set_storage` call.
+        #[structopt(long, default_value = "512")]
+        chunk_size: usize,
+        /// Writes a raw genesis patch with the dump entries to this file instead of writing
+        /// them onto the chain.
+        #[structopt(long)]
+        genesis_out: Option<PathBuf>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Compares a storage dump with the current storage of the chain, key by key.
+    #[structopt(name = "verify-dump")]
+    VerifyDump {
+        /// Dump written by `dump`.
+        #[structopt(long)]
+        dump: PathBuf,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Staking statistics.
+    #[structopt(name = "stats")]
+    Stats(StatsCommand),
+    /// Lists the offences reported to staking, and the resulting slashes, over a block range.
+    #[structopt(name = "offences")]
+    Offences {
+        /// First block of the range.
+        #[structopt(long)]
+        from: u32,
+        /// Last block of the range. Defaults to the latest finalized block.
+        #[structopt(long)]
+        to: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Shows the state of the staking messages exchanged with the relay chain: the outgoing
+    /// validator set, the incoming session reports and offences, and the depth of the upward
+    /// and downward message queues.
+    #[structopt(name = "staking-messages")]
+    StakingMessages {
+        /// Name of the pallet receiving the session reports and sending the validator sets.
+        #[structopt(long, default_value = "StakingRcClient")]
+        rc_client_pallet: String,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Session queries.
+    #[structopt(name = "session")]
+    Session(SessionCommand),
+    /// Transaction pool queries.
+    #[structopt(name = "pool")]
+    Pool(PoolCommand),
+    /// Nomination pools operations.
+    #[structopt(name = "pools")]
+    Pools(PoolsCommand),
+    /// Delegated staking queries.
+    #[structopt(name = "delegated")]
+    Delegated(DelegatedCommand),
+    /// Node keystore operations.
+    #[structopt(name = "keys")]
+    Keys(KeysCommand),
+    /// Era exposures queries.
+    #[structopt(name = "exposures")]
+    Exposures(ExposuresCommand),
+    /// Nominator queries.
+    #[structopt(name = "nominators")]
+    Nominators(NominatorsCommand),
+    /// Voter list bags queries.
+    #[structopt(name = "bags")]
+    Bags(BagsCommand),
+    /// Consistency checks of the staking state.
+    #[structopt(name = "check")]
+    Check(CheckCommand),
+    /// Staking rewards of the CLI-created stakers.
+    #[structopt(name = "rewards")]
+    Rewards(RewardsCommand),
+    /// Data exports for offline analysis.
+    #[structopt(name = "export")]
+    Export(ExportCommand),
+    /// Blocks until the validators created by the CLI are elected into the active set.
+    #[structopt(name = "wait-until-active")]
+    WaitUntilActive {
+        /// Fraction (0, 1] of the CLI-created validators that must be active.
+        #[structopt(long, default_value = "1.0")]
+        fraction: f64,
+        /// Number of eras to wait for before giving up.
+        #[structopt(long, default_value = "5")]
+        max_eras: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Reproduces the voter and target trimming of the election snapshot off-chain and lists
+    /// the voters and targets that would be left out under the current bounds.
+    #[structopt(name = "simulate-trimming")]
+    SimulateTrimming {
+        /// Only lists the CLI-created stakers left out.
+        #[structopt(long)]
+        managed: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Reports, per bond amount or per account, the nominations allowed by the staking runtime
+    /// API and the other staking limits applying to it, to plan population runs.
+    #[structopt(name = "quota")]
+    Quota {
+        /// Bond amounts to report the limits of.
+        #[structopt(long, use_delimiter = true, required_unless = "accounts")]
+        bonds: Vec<Balance>,
+        /// Bonded accounts to report the limits of, at their active bond.
+        #[structopt(long, use_delimiter = true)]
+        accounts: Vec<AccountId32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Shows the runtime and metadata versions of the chain.
+    #[structopt(name = "runtime")]
+    Runtime {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Checks that the chain is fit for a run: connectivity, peers, finality progress, pallets,
+    /// sudo key and funder balance, and prints the pass/fail checklist.
+    #[structopt(name = "doctor")]
+    Doctor {
+        /// Seconds to wait for a new finalized block.
+        #[structopt(long, default_value = "60")]
+        finality_timeout: u64,
+        /// Free balance the signer, which funds the stakers, must have at least.
+        #[structopt(long, default_value = "0")]
+        min_balance: Balance,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Runs the staking invariants, the config drift check, the election verification and the
+    /// assertions in one pass, for CI pipelines after an end-to-end test. Fails if any check
+    /// does; use `--output json` for machine-readable results.
+    #[structopt(name = "ci-check")]
+    CiCheck {
+        /// TOML file with the expected staking configs, as for `check config`. The drift check
+        /// is skipped without it.
+        #[structopt(long, parse(from_os_str))]
+        expected_configs: Option<PathBuf>,
+        /// Assertions checked as by `assert`, separated by `;`.
+        #[structopt(long = "assert")]
+        assertions: Option<String>,
+        /// First block the failed extrinsics of the assertions are checked from.
+        #[structopt(long)]
+        since: Option<u32>,
+        /// Block the election snapshot is read at for the election verification, compared with
+        /// the era it elects. The verification is skipped without it or a snapshot at it.
+        #[structopt(long)]
+        election_at: Option<H256>,
+        /// Tolerated difference (in %) between the on-chain and offline backing of a winner.
+        #[structopt(long, default_value = "1")]
+        tolerance: f64,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Recomputes the election offline from the snapshot and compares it with the on-chain
+    /// result of an era.
+    #[structopt(name = "verify-election")]
+    VerifyElection {
+        /// Era whose on-chain exposures are compared. Defaults to the current era.
+        #[structopt(long)]
+        era: Option<u32>,
+        /// Block at which the election snapshot is read. Defaults to the latest block.
+        #[structopt(long)]
+        at: Option<H256>,
+        /// Number of balancing iterations of the offline solver.
+        #[structopt(long, default_value = "0")]
+        balancing: usize,
+        /// Tolerated difference (in %) between the on-chain and offline backing of a winner.
+        #[structopt(long, default_value = "1")]
+        tolerance: f64,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Submits SCALE encoded call data signed by the configured signer.
+    #[structopt(name = "submit-raw")]
+    SubmitRaw {
+        /// Hex encoded call data. More than one call requires `--batch`.
+        #[structopt(required = true)]
+        calls: Vec<String>,
+        /// Wraps the call(s) in a utility batch.
+        #[structopt(long)]
+        batch: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Submits the hex encoded signed extrinsics of a file, one per line, e.g. written with
+    /// `--offline`, and reports the outcome of each.
+    #[structopt(name = "submit-file")]
+    SubmitFile {
+        /// File with one hex encoded signed extrinsic per line. Empty lines and lines starting
+        /// with `#` are skipped.
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Builds a staking call from its arguments and prints its SCALE encoded call data and hash,
+    /// e.g. for governance proposals, multisigs or polkadot-js.
+    #[structopt(name = "encode")]
+    Encode {
+        /// Wraps the call in `sudo.sudo`.
+        #[structopt(long)]
+        sudo: bool,
+        /// Format of the result: `plain`, `table` or `json`.
+        #[structopt(long, default_value = "plain")]
+        output: output::OutputFormat,
+        #[structopt(subcommand)]
+        call: EncodeCall,
+    },
+    /// Schedules a staking call with the scheduler pallet, through sudo, to be dispatched at a
+    /// future block as Root, as an account or as every bonded CLI-created staker. The pallet
+    /// caps `unbond` at the active bond, so a large `--value` unbonds everything.
+    #[structopt(name = "schedule")]
+    Schedule {
+        /// Block to dispatch the call at: `<number>`, or `<phase>[+|-]<blocks>` relative to the
+        /// next start of an election phase as for `--submit-at`.
+        #[structopt(long)]
+        at: Schedule,
+        /// Dispatches the call as the controller of every bonded CLI-created staker.
+        #[structopt(long, conflicts_with = "as_account")]
+        managed: bool,
+        /// Dispatches the call as this account instead of Root.
+        #[structopt(long = "as")]
+        as_account: Option<AccountId32>,
+        /// Priority of the scheduled tasks, the lower the earlier.
+        #[structopt(long, default_value = "127")]
+        priority: u8,
+        #[structopt(flatten)]
+        configs: Configs,
+        #[structopt(subcommand)]
+        call: EncodeCall,
+    },
+    /// Decodes hex encoded call data and prints its call tree, looking into the calls nested in
+    /// utility and sudo wrappers.
+    #[structopt(name = "decode")]
+    Decode {
+        /// Hex encoded call data, with or without the `0x` prefix.
+        call: String,
+        /// Format of the result: `plain`, `table` or `json`.
+        #[structopt(long, default_value = "plain")]
+        output: output::OutputFormat,
+    },
+    /// Prepares a referendum changing the staking configs: prints the `set_staking_configs`
+    /// call, its preimage hash and the `Preimage.note_preimage` and `Referenda.submit` calls of
+    /// the chain and, with `--submit`, submits them signed by the signer.
+    #[structopt(name = "propose")]
+    Propose {
+        #[structopt(flatten)]
+        changes: StakingConfigChanges,
+        /// Origin of the referendum, as `<caller>:<origin>`, e.g. `system:Root` or
+        /// `Origins:StakingAdmin`.
+        #[structopt(long, default_value = "system:Root")]
+        origin: String,
+        /// Number of blocks after the approval at which the change is enacted.
+        #[structopt(long, default_value = "10")]
+        enactment_after: u32,
+        /// Submits the preimage and the referendum.
+        #[structopt(long)]
+        submit: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Checks assertions against the chain state, e.g. `validator_count >= 50`,
+    /// `queued_score.minimal_stake > 1000` or `no_failed_extrinsics`, failing if any of them
+    /// does not hold.
+    #[structopt(name = "assert")]
+    Assert {
+        /// Assertions separated by `;`, each `<metric> <op> <value>` with `op` one of `>=`, `<=`,
+        /// `>`, `<`, `==` or `!=`, or `no_failed_extrinsics`. Metrics: `validator_count`,
+        /// `validators`, `nominators`, `active_era`, `current_era`, `min_validator_bond`,
+        /// `min_nominator_bond`, `managed_accounts`, `queued_score.minimal_stake`,
+        /// `queued_score.sum_stake`, `queued_score.sum_stake_squared` and `failed_extrinsics`,
+        /// the extrinsics of the CLI-created accounts that failed since `--since`.
+        #[structopt(required = true)]
+        assertions: Vec<String>,
+        /// First block the extrinsics are checked from.
+        #[structopt(long)]
+        since: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Changes the staking configs through OpenGov on chains without sudo: notes the preimage,
+    /// submits the referendum with the origin of the track, places its decision deposit, votes
+    /// it aye with `--voters` and follows it until it is confirmed or fails.
+    #[structopt(name = "open-gov")]
+    OpenGov {
+        #[structopt(flatten)]
+        changes: StakingConfigChanges,
+        /// Origin of the referendum, as `<caller>:<origin>`, which selects its track.
+        #[structopt(long, default_value = "Origins:StakingAdmin")]
+        origin: String,
+        /// Number of blocks after the approval at which the change is enacted.
+        #[structopt(long, default_value = "10")]
+        enactment_after: u32,
+        /// Secret URIs of the accounts voting aye with 90% of their free balance, e.g. the
+        /// dev accounts of test networks. No votes are cast if empty.
+        #[structopt(
+            long,
+            use_delimiter = true,
+            default_value = "//Alice,//Bob,//Charlie,//Dave,//Eve,//Ferdie"
+        )]
+        voters: Vec<String>,
+        /// Conviction of the votes, from 0 (0.1x) to 6 (6x).
+        #[structopt(long, default_value = "1")]
+        conviction: u8,
+        /// Number of blocks to follow the referendum for before giving up.
+        #[structopt(long, default_value = "1000")]
+        max_blocks: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Converts an account between its hex public key and its SS58 addresses, and shows the
+    /// network of an SS58 address.
+    #[structopt(name = "address")]
+    Address {
+        /// Hex encoded public key, with or without the `0x` prefix, or SS58 address.
+        address: String,
+        /// SS58 prefixes the address is printed with, e.g. 0 for Polkadot or 2 for Kusama.
+        #[structopt(long, use_delimiter = true, default_value = "0,2,42")]
+        prefixes: Vec<u16>,
+        /// Format of the result: `plain`, `table` or `json`.
+        #[structopt(long, default_value = "plain")]
+        output: output::OutputFormat,
+    },
+    /// Generates the genesis config patch for a staker population, to be merged into the chain
+    /// spec. The generated accounts are recorded in the state file.
+    #[structopt(name = "genesis")]
+    Genesis {
+        /// Number of validators.
+        #[structopt(long, default_value = "10")]
+        validators: usize,
+        /// Number of nominators.
+        #[structopt(long, default_value = "100")]
+        nominators: usize,
+        /// Balance every staker bonds. Stakers are endowed with twice as much.
+        #[structopt(long, default_value = "1000000000000")]
+        bond_amount: Balance,
+        /// Number of validators every nominator nominates.
+        #[structopt(long, default_value = "6")]
+        nominations: usize,
+        /// Validator count of the staking genesis. Defaults to the number of validators.
+        #[structopt(long)]
+        validator_count: Option<u32>,
+        /// Number of generated validators set as invulnerables.
+        #[structopt(long, default_value = "0")]
+        invulnerables: usize,
+        /// Also sets the validators' sr25519 keys as their session (aura) keys.
+        #[structopt(long)]
+        session_keys: bool,
+        /// File to write the patch to. Defaults to stdout.
+        #[structopt(long, parse(from_os_str))]
+        out: Option<PathBuf>,
+        /// File where the accounts created by the CLI are kept track of.
+        #[structopt(long, parse(from_os_str), default_value = "staking-cli-state.json")]
+        state: PathBuf,
+    },
+    /// Injects a staker population directly into storage through sudo `system.set_storage`,
+    /// bypassing the staking extrinsics, and checks the staking invariants afterwards.
+    #[structopt(name = "inject")]
+    Inject {
+        /// Number of validators.
+        #[structopt(long, default_value = "10")]
+        validators: usize,
+        /// Number of nominators.
+        #[structopt(long, default_value = "1000")]
+        nominators: usize,
+        /// Balance every staker bonds. Stakers are endowed with twice as much.
+        #[structopt(long, default_value = "1000000000000")]
+        bond_amount: Balance,
+        /// Number of validators every nominator nominates.
+        #[structopt(long, default_value = "6")]
+        nominations: usize,
+        /// Number of storage items written per `set_storage` call.
+        #[structopt(long, default_value = "512")]
+        chunk_size: usize,
+        /// Creates a legacy controller distinct from the stash for every staker.
+        #[structopt(long)]
+        separate_controller: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Clones the staker population of a live network: samples its validators and nominators
+    /// and injects an equivalent population with scaled bonds.
+    #[structopt(name = "clone-stakers")]
+    CloneStakers {
+        /// RPC endpoint of the network to clone from.
+        #[structopt(long)]
+        from: String,
+        /// Number of validators to sample.
+        #[structopt(long, default_value = "100")]
+        validators: usize,
+        /// Number of nominators to sample.
+        #[structopt(long, default_value = "1000")]
+        nominators: usize,
+        /// Factor applied to the bonds of the cloned stakers.
+        #[structopt(long, default_value = "1.0")]
+        scale: f64,
+        /// Number of storage items written per `set_storage` call.
+        #[structopt(long, default_value = "512")]
+        chunk_size: usize,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Compares the validator sets, nominators and stake distributions of two chains, e.g. the
+    /// relay chain and the parachain after a staking migration.
+    #[structopt(name = "compare")]
+    Compare {
+        /// RPC endpoint of the first chain.
+        #[structopt(long)]
+        a: String,
+        /// RPC endpoint of the second chain.
+        #[structopt(long)]
+        b: String,
+        /// Tolerated difference (in %) between the stake statistics of both chains.
+        #[structopt(long, default_value = "0")]
+        tolerance: f64,
+        /// Format of the report: `plain`, `table` or `json`.
+        #[structopt(long, default_value = "plain")]
+        output: output::OutputFormat,
+    },
+    /// Launches a local relay chain and staking parachain with zombienet and, once the parachain
+    /// produces blocks, runs a command against it.
+    #[structopt(name = "spawn")]
+    Spawn {
+        /// Zombienet binary.
+        #[structopt(long, default_value = "zombienet")]
+        zombienet: String,
+        /// Zombienet provider.
+        #[structopt(long, default_value = "native")]
+        provider: String,
+        /// Zombienet network config. Defaults to the bundled `zombienet/staking-parachain.toml`.
+        #[structopt(long, parse(from_os_str))]
+        config: Option<PathBuf>,
+        /// RPC endpoint of the parachain collator in the spawned network.
+        #[structopt(long, default_value = "ws://127.0.0.1:9988")]
+        url: String,
+        /// Number of parachain blocks to wait for before running the command.
+        #[structopt(long, default_value = "2")]
+        wait_blocks: u32,
+        /// Command to run against the network, e.g. `-- validate --url ws://127.0.0.1:9988`.
+        /// Without it, the network is kept running until zombienet exits.
+        #[structopt(last = true)]
+        command: Vec<String>,
+    },
+    /// Runs the commands of a scenario file against a chain, one command line per line, e.g.
+    /// `validate --number 10`, stopping at the first failing one.
+    #[structopt(name = "run-scenario")]
+    RunScenario {
+        /// Scenario file. Empty lines and lines starting with `#` are skipped. Lines must not set
+        /// `--url`, which is added to the commands running against a chain. `expect: <assertion>`
+        /// lines, and the indented lines following an `expect:` line, are checked with `assert`
+        /// against the blocks since the start of the scenario.
+        #[structopt(parse(from_os_str))]
+        scenario: PathBuf,
+        /// RPC endpoint of the chain the scenario runs against.
+        #[structopt(long)]
+        url: String,
+        /// First runs the whole scenario against a chopsticks fork of the chain, with copies of
+        /// the state and index files, and only runs it against the chain if it succeeds.
+        #[structopt(long)]
+        rehearse: bool,
+        /// Chopsticks binary used for the rehearsal.
+        #[structopt(long, default_value = "chopsticks")]
+        chopsticks: String,
+        /// Port the rehearsal fork listens on.
+        #[structopt(long, default_value = "8000")]
+        fork_port: u16,
+    },
     #[structopt(name = "playground")]
     Playground {
         #[structopt(flatten)]
@@ -85,329 +761,16187 @@ enum Command {
     },
 }
 
-/// Arguments required for creating and sending an extrinsic to a substrate node.
-#[derive(Clone, Debug, StructOpt)]
-pub(crate) struct Configs {
-    /// RPC endpoint for the collator.
-    #[structopt(name = "url", long, short)]
-    url: String,
-}
-
-#[tokio::main]
-async fn main() -> color_eyre::Result<()> {
-    env_logger::init();
-    color_eyre::install()?;
-
-    let _configs = match Opts::from_args().command {
-        Command::Validate {
-            parachain_id,
-            number,
-            bond_amount,
-            alice,
-            configs,
-        } => commands::validate(parachain_id, number, bond_amount, alice, configs).await,
-        Command::Nominate {
-            parachain_id,
-            number,
-            bond_amount,
-            nominations,
-            alice,
-            configs,
-        } => {
-            commands::nominate(
-                parachain_id,
-                number,
-                bond_amount,
-                nominations,
-                alice,
-                configs,
-            )
-            .await
-        }
-        Command::StakersInfo { configs } => commands::stakers_info(configs).await,
-        Command::Playground { configs } => commands::playground(configs).await,
-    }?;
-
-    Ok(())
-}
-
-mod commands {
-    use super::*;
-
-    /// Bonds and sets as validators `n_validators` new validators.
-    pub(crate) async fn validate(
-        _para_id: u32,
-        n_validators: usize,
-        bond_amount: Balance,
-        alice: bool,
-        configs: Configs,
-    ) -> color_eyre::Result<Configs> {
-        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
-
-        let keypairs = if alice {
-            println!("> Bonding and setting Alice as validator..");
-            vec![dev::alice()]
-        } else {
-            println!(
-                "> Generating and funding, bonding and setting as validators {n_validators} accounts.."
-            );
-            let keypairs =
-                helpers::fund_accounts(&api, n_validators, Some(bond_amount * 2)).await?;
-            println!("Minting done for {n_validators} stakers.");
-            keypairs
-        };
-        let n_validators = keypairs.len();
-
-        let mut bond_calls: Vec<(_, _)> = vec![];
-        let mut validate_calls: Vec<(_, _)> = vec![];
-
-        // prepare both bond and validate calls for generated & funded keypairs.
-        for pair in keypairs.into_iter() {
-            let bond_tx = staking_parachain::tx()
-                .staking()
-                .bond(bond_amount, RewardDestination::Staked);
-            let validate_tx = staking_parachain::tx().staking().validate(
-                staking_parachain::runtime_types::pallet_staking::ValidatorPrefs {
-                    commission: Perbill(10),
-                    blocked: false,
-                },
-            );
-            bond_calls.push((pair.clone(), bond_tx));
-            validate_calls.push((pair, validate_tx));
+impl Command {
+    /// The RPC and signer configs of the command, if it runs against a chain.
+    fn configs(&self) -> Option<&Configs> {
+        match self {
+            Self::Validate { configs, .. }
+            | Self::Nominate { configs, .. }
+            | Self::StakersInfo { configs, .. }
+            | Self::Validators(ValidatorsCommand::List { configs, .. })
+            | Self::Validators(ValidatorsCommand::Oversubscription { configs, .. })
+            | Self::Validators(ValidatorsCommand::SelfStake { configs, .. })
+            | Self::Validators(ValidatorsCommand::Commissions { configs, .. })
+            | Self::Validators(ValidatorsCommand::Disabled { configs, .. })
+            | Self::Election(ElectionCommand::Scores { configs, .. })
+            | Self::Election(ElectionCommand::Submissions { configs, .. })
+            | Self::Election(ElectionCommand::Estimate { configs, .. })
+            | Self::Election(ElectionCommand::Plan { configs, .. })
+            | Self::Election(ElectionCommand::Mine { configs, .. })
+            | Self::Election(ElectionCommand::Timing { configs, .. })
+            | Self::Monitor(MonitorCommand::Unsigned { configs, .. })
+            | Self::Monitor(MonitorCommand::Election { configs, .. })
+            | Self::EraPoints { configs, .. }
+            | Self::RebagAll { configs, .. }
+            | Self::PutInFrontOf { configs, .. }
+            | Self::RotateNominations { configs, .. }
+            | Self::Ramp { configs, .. }
+            | Self::SetMinCommission { configs, .. }
+            | Self::FastForward { configs, .. }
+            | Self::FullExit { configs, .. }
+            | Self::Reap { configs, .. }
+            | Self::Tag { configs, .. }
+            | Self::Authorship { configs, .. }
+            | Self::Dump { configs, .. }
+            | Self::Restore { configs, .. }
+            | Self::VerifyDump { configs, .. }
+            | Self::Stats(StatsCommand::Concentration { configs, .. })
+            | Self::Offences { configs, .. }
+            | Self::StakingMessages { configs, .. }
+            | Self::Session(SessionCommand::Info { configs, .. })
+            | Self::Pool(PoolCommand::Status { configs, .. })
+            | Self::Pool(PoolCommand::Pending { configs, .. })
+            | Self::Pools(PoolsCommand::Loadtest { configs, .. })
+            | Self::Pools(PoolsCommand::Claim { configs, .. })
+            | Self::Pools(PoolsCommand::Exit { configs, .. })
+            | Self::Pools(PoolsCommand::Commission { configs, .. })
+            | Self::Pools(PoolsCommand::State { configs, .. })
+            | Self::Pools(PoolsCommand::Roles { configs, .. })
+            | Self::Delegated(DelegatedCommand::Agents { configs, .. })
+            | Self::Delegated(DelegatedCommand::Delegators { configs, .. })
+            | Self::Keys(KeysCommand::Insert { configs, .. })
+            | Self::Exposures(ExposuresCommand::Pages { configs, .. })
+            | Self::Exposures(ExposuresCommand::Page { configs, .. })
+            | Self::Nominators(NominatorsCommand::Distribution { configs, .. })
+            | Self::Bags(BagsCommand::Members { configs, .. })
+            | Self::Check(CheckCommand::Ledgers { configs, .. })
+            | Self::Check(CheckCommand::Config { configs, .. })
+            | Self::Check(CheckCommand::BelowMin { configs, .. })
+            | Self::Check(CheckCommand::VirtualStakers { configs, .. })
+            | Self::Check(CheckCommand::Authorship { configs, .. })
+            | Self::Rewards(RewardsCommand::Status { configs, .. })
+            | Self::Rewards(RewardsCommand::Payout { configs, .. })
+            | Self::Export(ExportCommand::Eras { configs, .. })
+            | Self::WaitUntilActive { configs, .. }
+            | Self::Quota { configs, .. }
+            | Self::SimulateTrimming { configs, .. }
+            | Self::Runtime { configs, .. }
+            | Self::Doctor { configs, .. }
+            | Self::VerifyElection { configs, .. }
+            | Self::CiCheck { configs, .. }
+            | Self::SubmitRaw { configs, .. }
+            | Self::SubmitFile { configs, .. }
+            | Self::Propose { configs, .. }
+            | Self::Schedule { configs, .. }
+            | Self::OpenGov { configs, .. }
+            | Self::Assert { configs, .. }
+            | Self::Inject { configs, .. }
+            | Self::CloneStakers { configs, .. }
+            | Self::Playground { configs, .. } => Some(configs),
+            Self::Encode { .. }
+            | Self::Decode { .. }
+            | Self::Address { .. }
+            | Self::Genesis { .. }
+            | Self::Compare { .. }
+            | Self::Spawn { .. }
+            | Self::RunScenario { .. } => None,
         }
+    }
 
-        let mut it = bond_calls.into_iter().peekable();
-        while let Some(next) = it.next() {
-            let (pair, bond_tx) = next;
-            let mut progress = api
-                .tx()
-                .sign_and_submit_then_watch_default(&bond_tx, &pair)
-                .await?;
-            // make sure all bonds went through before progressing.
-            if it.peek().is_none() {
-                while let Some(_) = progress.next().await {}
-            }
+    /// Whether the command follows the chain over blocks, so its reads cannot be pinned to one
+    /// block with `--read-block`.
+    fn follows_chain(&self) -> bool {
+        match self {
+            Self::FullExit { daemon, .. } => *daemon,
+            Self::Election(ElectionCommand::Scores { watch, .. }) => *watch,
+            Self::Ramp { .. }
+            | Self::FastForward { .. }
+            | Self::RotateNominations { .. }
+            | Self::WaitUntilActive { .. }
+            | Self::OpenGov { .. }
+            | Self::Monitor(_)
+            | Self::Pools(PoolsCommand::Exit { .. })
+            | Self::Pools(PoolsCommand::Loadtest { .. }) => true,
+            _ => false,
         }
-        println!("Bonding done for {n_validators} staker(s).");
+    }
 
-        let mut it = validate_calls.into_iter().peekable();
-        while let Some(next) = it.next() {
-            let (pair, validate_tx) = next;
-            let mut progress = api
-                .tx()
-                .sign_and_submit_then_watch_default(&validate_tx, &pair)
-                .await?;
-            // make sure all bonds went through before progressing.
-            if it.peek().is_none() {
-                while let Some(_) = progress.next().await {}
-            }
+    /// The configs of the population commands, which can run against several chains at once.
+    fn population_configs_mut(&mut self) -> Option<&mut Configs> {
+        match self {
+            Self::Validate { configs, .. }
+            | Self::Nominate { configs, .. }
+            | Self::CloneStakers { configs, .. } => Some(configs),
+            _ => None,
         }
-        println!("Validating done for {n_validators} staker(s).");
-        let configs = stakers_info(configs).await?;
-
-        Ok(configs)
     }
+}
 
-    /// Implements the nominate command.
-    pub(crate) async fn nominate(
-        _para_id: u32,
-        n_nominators: usize,
-        bond_amount: Balance,
-        nominations: usize,
-        alice: bool,
+#[derive(Debug, StructOpt, Clone)]
+enum ValidatorsCommand {
+    /// Lists all registered validators, classified as active or waiting.
+    #[structopt(name = "list")]
+    List {
+        #[structopt(flatten)]
         configs: Configs,
-    ) -> color_eyre::Result<Configs> {
-        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
-
-        let current_validators = helpers::get_validators(&api).await?;
-
-        let (targets, keypairs) = if alice {
-            println!("> Bonding and setting Alice as nominator..");
-            let alice_target: Target = dev::alice().public_key().into();
-
-            (vec![alice_target], vec![dev::alice()])
-        } else {
-            println!(
-                "> Generating and funding, bonding and setting as nominators {n_nominators} accounts.."
-            );
-            let targets = helpers::select_targets(nominations, current_validators.clone());
-            let keypairs =
-                helpers::fund_accounts(&api, n_nominators, Some(bond_amount * 2)).await?;
-
-            (targets, keypairs)
+    },
+    /// Reports, per elected validator, its backers against the exposure page size, the
+    /// unclaimed pages and the nominators left out of its exposure.
+    #[structopt(name = "oversubscription")]
+    Oversubscription {
+        /// Era to analyse. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Lists the self-stake of every registered validator against its nominated backing.
+    #[structopt(name = "self-stake")]
+    SelfStake {
+        /// Era of the nominated backing. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        /// Self-stake share of the total backing, in percent, below which a validator is
+        /// highlighted.
+        #[structopt(long, default_value = "1.0")]
+        low_percent: f64,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Buckets the registered validators by commission and flags the outliers.
+    #[structopt(name = "commissions")]
+    Commissions {
+        /// Width of the commission buckets, in percent.
+        #[structopt(long, default_value = "10")]
+        bucket: u32,
+        /// Also writes one CSV row per validator to this file.
+        #[structopt(long)]
+        csv: Option<PathBuf>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Reports the disabled and offending session validators with their slashes over the
+    /// bonded eras, and the ones recorded in the index by earlier runs, flagging the CLI-created
+    /// ones.
+    #[structopt(name = "disabled")]
+    Disabled {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum ElectionCommand {
+    /// Records the score of the queued solution in the index and reports its trend over eras.
+    #[structopt(name = "scores")]
+    Scores {
+        /// Keep following finalized blocks and record every new queued score.
+        #[structopt(long)]
+        watch: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Lists the signed submissions of the current round.
+    #[structopt(name = "submissions")]
+    Submissions {
+        /// Number of balancing iterations used when mining the solution to compare with.
+        #[structopt(long, default_value = "0")]
+        balancing: usize,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Mines a solution from the snapshot with the given balancing, `reduce` and trimming, and
+    /// reports its score and size next to the other solutions mined in the round, which are
+    /// recorded in the index.
+    #[structopt(name = "mine")]
+    Mine {
+        /// Number of balancing iterations after sequential phragmen.
+        #[structopt(long, default_value = "0")]
+        balancing: usize,
+        /// Runs `reduce` on the assignments, removing edges without changing the supports.
+        #[structopt(long)]
+        reduce: bool,
+        /// Voters the solution is mined from at most, the others being trimmed first.
+        #[structopt(long)]
+        max_voters: Option<usize>,
+        /// Voters trimmed first beyond `--max-voters`: `stake`, those with the least stake, or
+        /// `votes`, those with the most votes, shrinking the solution the most.
+        #[structopt(long, default_value = "stake")]
+        voter_trimming: VoterTrimming,
+        /// Backers a winner keeps at most, those with the least stake behind it being trimmed.
+        #[structopt(long)]
+        max_backers: Option<usize>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Checks whether a planned population keeps the election within its snapshot bounds and
+    /// block limits, and advises adjustments otherwise, before anything is submitted.
+    #[structopt(name = "plan")]
+    Plan {
+        /// Number of validators to create.
+        #[structopt(long, default_value = "0")]
+        validators: u32,
+        /// Number of nominators to create.
+        #[structopt(long, default_value = "0")]
+        nominators: u32,
+        /// Nominations per created nominator.
+        #[structopt(long, default_value = "16")]
+        nominations: u32,
+        /// Bond of the created nominators, the lowest one with `--max-bond`.
+        #[structopt(long, default_value = "1000000000000")]
+        bond_amount: Balance,
+        /// Highest bond of the created nominators, spread evenly from `--bond-amount`.
+        #[structopt(long)]
+        max_bond: Option<Balance>,
+        /// Name of the pallet verifying the solutions.
+        #[structopt(long, default_value = "MultiBlockElectionVerifier")]
+        verifier_pallet: String,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Reports the duration of the election phases in the rounds recorded by `monitor
+    /// election`, and their mean, deviation and range across rounds against the configured
+    /// ones. The export phase lasts until the last page of the result is processed.
+    #[structopt(name = "timing")]
+    Timing {
+        /// Only reports the last this many rounds.
+        #[structopt(long)]
+        rounds: Option<usize>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Estimates the size of the election snapshot and of a solution page from the current
+    /// voters and targets, and compares them with the snapshot bounds and block limits to
+    /// predict whether the snapshot or the backers of the winners will be trimmed.
+    #[structopt(name = "estimate")]
+    Estimate {
+        /// Name of the pallet verifying the solutions, whose bounds on the winners and their
+        /// backers are checked if the runtime has it.
+        #[structopt(long, default_value = "MultiBlockElectionVerifier")]
+        verifier_pallet: String,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum MonitorCommand {
+    /// Checks that a solution is queued by the end of every unsigned phase.
+    #[structopt(name = "unsigned")]
+    Unsigned {
+        /// Alert when no solution is queued this many blocks before the unsigned phase ends.
+        #[structopt(long, default_value = "5")]
+        alert_blocks: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Records the election phase transitions and the processing of the results of every
+    /// round in the index and alerts on skipped or overlong phases and on fallbacks.
+    #[structopt(name = "election")]
+    Election {
+        /// Blocks a phase may last over its configured duration before alerting.
+        #[structopt(long, default_value = "5")]
+        slack: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum StatsCommand {
+    /// Computes concentration metrics (Gini coefficient, Herfindahl index and top-k share)
+    /// of the validators backing and of the nominators stake.
+    #[structopt(name = "concentration")]
+    Concentration {
+        /// Era of the validators backing. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        /// Number of top stakers whose share is reported.
+        #[structopt(long, default_value = "10")]
+        top_k: usize,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum SessionCommand {
+    /// Shows the current session, its validators and queued keys, and the CLI-created
+    /// validators with keys set that are not in the session validators.
+    #[structopt(name = "info")]
+    Info {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum PoolCommand {
+    /// Shows the number and size of the extrinsics pending in the transaction pool and, with
+    /// the node's Prometheus endpoint, its ready and future counts.
+    #[structopt(name = "status")]
+    Status {
+        /// Prometheus metrics endpoint of the node, e.g. `http://127.0.0.1:9615/metrics`.
+        #[structopt(long)]
+        metrics: Option<String>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Lists the signed extrinsics pending in the transaction pool with staking calls, directly
+    /// or batched, and whether their signer is a CLI-created account.
+    #[structopt(name = "pending")]
+    Pending {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum PoolsCommand {
+    /// Creates nomination pools of CLI-created accounts, joins members to them and streams
+    /// member joins, bond extras and unbonds every era, reporting the points of each pool
+    /// against its bonded balance. `--rate` paces the submissions.
+    #[structopt(name = "loadtest")]
+    Loadtest {
+        /// The number of CLI-created pools to have, including the existing ones.
+        #[structopt(long, default_value = "2")]
+        pools: usize,
+        /// The number of CLI-created members to have, spread over the pools.
+        #[structopt(long, default_value = "20")]
+        members: usize,
+        /// Balance to create the pools and join them with.
+        #[structopt(long, default_value = "1000000000000")]
+        bond_amount: Balance,
+        /// Fraction of the members acting every era.
+        #[structopt(long, default_value = "0.2")]
+        churn: f64,
+        /// Number of eras to run the churn for.
+        #[structopt(long, default_value = "3")]
+        eras: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Claims the pending rewards of the members of the CLI-created pools, depositors
+    /// included, and checks that each balance grew by the payout, net of the fees.
+    #[structopt(name = "claim")]
+    Claim {
+        /// Also claims the pending commission of the pools with one, signed by their root.
+        #[structopt(long)]
+        commission: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Unbonds the members of the CLI-created pools, waits for their funds to unlock and
+    /// withdraws them, reporting the members still in a pool after `--max-eras`.
+    #[structopt(name = "exit")]
+    Exit {
+        /// Also destroys the pools: their state is set to destroying and, once they are the last
+        /// member, their depositors unbond and withdraw too.
+        #[structopt(long)]
+        destroy: bool,
+        /// Eras to wait for the members to leave before giving up on the stuck ones.
+        #[structopt(long, default_value = "30")]
+        max_eras: u32,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Sets the commission, its maximum and its change rate of pools, signed by their root:
+    /// the CLI-created depositor or the signer.
+    #[structopt(name = "commission")]
+    Commission {
+        /// Pool to update. Defaults to all the CLI-created pools.
+        #[structopt(long)]
+        pool: Option<u32>,
+        /// Commission in percent, or `remove`.
+        #[structopt(long)]
+        rate: Option<ConfigChange<f64>>,
+        /// Account paid the commission. Defaults to the root of the pool.
+        #[structopt(long)]
+        payee: Option<AccountId32>,
+        /// Maximum commission in percent. It can only be lowered once set.
+        #[structopt(long)]
+        max: Option<f64>,
+        /// Maximum increase of the commission, in percent, per `--min-delay` blocks.
+        #[structopt(long, requires = "min_delay")]
+        max_increase: Option<f64>,
+        /// Blocks between two commission increases.
+        #[structopt(long, requires = "max_increase")]
+        min_delay: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Sets the state of pools: `open`, `blocked` or `destroying`, signed by their root.
+    #[structopt(name = "state")]
+    State {
+        /// Pool to update. Defaults to all the CLI-created pools.
+        #[structopt(long)]
+        pool: Option<u32>,
+        state: PoolState,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Updates the root, nominator and bouncer of pools, signed by their root. Each role is an
+    /// address or `remove`, roles not given are left untouched.
+    #[structopt(name = "roles")]
+    Roles {
+        /// Pool to update. Defaults to all the CLI-created pools.
+        #[structopt(long)]
+        pool: Option<u32>,
+        #[structopt(long)]
+        root: Option<ConfigChange<AccountId32>>,
+        #[structopt(long)]
+        nominator: Option<ConfigChange<AccountId32>>,
+        #[structopt(long)]
+        bouncer: Option<ConfigChange<AccountId32>>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum DelegatedCommand {
+    /// Lists the agents of `DelegatedStaking` with their delegated, unclaimed and pending slash
+    /// totals, their active stake and the pool they are the bonded account of, if any.
+    #[structopt(name = "agents")]
+    Agents {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Lists the delegators of `DelegatedStaking` with their delegation, the balance held for
+    /// it and their pool membership, flagging the ones which do not match.
+    #[structopt(name = "delegators")]
+    Delegators {
+        /// Only lists the delegators of this agent.
+        #[structopt(long)]
+        agent: Option<AccountId32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum KeysCommand {
+    /// Inserts the session keys of the CLI-created validators into the keystores of local
+    /// nodes with `author_insertKey`, spreading the validators over the nodes round-robin.
+    #[structopt(name = "insert")]
+    Insert {
+        /// RPC endpoints of the nodes whose keystores the keys are inserted into.
+        #[structopt(long, required = true, use_delimiter = true)]
+        nodes: Vec<String>,
+        /// Key types the validator keys are inserted as.
+        #[structopt(long, default_value = "aura", use_delimiter = true)]
+        key_types: Vec<String>,
+        /// Also registers the keys on-chain with `session.set_keys` for the validators without
+        /// next session keys.
+        #[structopt(long)]
+        set_keys: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum ExposuresCommand {
+    /// Lists the exposure page count of every validator elected in an era.
+    #[structopt(name = "pages")]
+    Pages {
+        /// Era to query. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Dumps the nominators of one exposure page of a validator.
+    #[structopt(name = "page")]
+    Page {
+        #[structopt(long)]
+        validator: AccountId32,
+        #[structopt(long, default_value = "0")]
+        page: u32,
+        /// Era to query. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum NominatorsCommand {
+    /// Buckets the nominators by active bond and reports its percentiles.
+    #[structopt(name = "distribution")]
+    Distribution {
+        /// Number of equally wide buckets between the lowest and highest bond.
+        #[structopt(long, default_value = "10")]
+        buckets: usize,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum BagsCommand {
+    /// Lists the nodes of a bag, from head to tail, with their scores.
+    #[structopt(name = "members")]
+    Members {
+        /// Upper threshold of the bag, or its index in the bag thresholds when lower than the
+        /// number of bags.
+        bag: u64,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum CheckCommand {
+    /// Detects inconsistent ledgers and suggests the sudo calls repairing them.
+    #[structopt(name = "ledgers")]
+    Ledgers {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Compares the on-chain staking configs with an expected baseline and fails on drift.
+    #[structopt(name = "config")]
+    Config {
+        /// TOML file with the expected configs. Only the configs it sets are checked.
+        #[structopt(long)]
+        expected: PathBuf,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Lists the validators and nominators bonded below the current minimum bonds.
+    #[structopt(name = "below-min")]
+    BelowMin {
+        /// Chills the stakers found with `chill_other`, signed by the signer.
+        #[structopt(long)]
+        chill: bool,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Checks the virtual stakers, e.g. the pool bonded accounts of delegated staking: no
+    /// staking lock or hold on them, and a stake matching their delegations and pool points.
+    /// Fails on any violation.
+    #[structopt(name = "virtual-stakers")]
+    VirtualStakers {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Checks that the blocks of a window starting at the activation of an era were authored
+    /// by exactly the validators elected for it, bar the disabled ones, and flags the elected
+    /// validators that never authored. Fails on any mismatch.
+    #[structopt(name = "authorship")]
+    Authorship {
+        /// Era whose elected validators are checked. Defaults to the active era.
+        #[structopt(long)]
+        era: Option<u32>,
+        /// Blocks checked from the activation of the era, at least the number of session
+        /// validators so that each has a slot. Defaults to twice that number.
+        #[structopt(long)]
+        window: Option<u32>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum RewardsCommand {
+    /// Lists, for every CLI-created staker, the eras it was paid for and the outstanding
+    /// payouts, and records the paid eras in the index.
+    #[structopt(name = "status")]
+    Status {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+    /// Pays out the outstanding rewards of the CLI-created stakers, skipping the pages already
+    /// claimed.
+    #[structopt(name = "payout")]
+    Payout {
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum ExportCommand {
+    /// Exports the validator set, reward points, stake and payouts of a range of eras to CSV.
+    #[structopt(name = "eras")]
+    Eras {
+        /// First era to export.
+        #[structopt(long)]
+        from: u32,
+        /// Last era to export, inclusive. Defaults to the active era.
+        #[structopt(long)]
+        to: Option<u32>,
+        /// CSV file to write. Defaults to stdout.
+        #[structopt(long, parse(from_os_str))]
+        out: Option<PathBuf>,
+        #[structopt(flatten)]
+        configs: Configs,
+    },
+}
+
+#[derive(Debug, StructOpt, Clone)]
+enum EncodeCall {
+    /// `staking.bond`.
+    #[structopt(name = "bond")]
+    Bond {
+        #[structopt(long)]
+        value: Balance,
+        /// Reward destination: `staked`, `stash`, `none` or `account:<ADDRESS>`.
+        #[structopt(long, default_value = "staked")]
+        payee: Payee,
+    },
+    /// `staking.bond_extra`.
+    #[structopt(name = "bond-extra")]
+    BondExtra {
+        #[structopt(long)]
+        max_additional: Balance,
+    },
+    /// `staking.unbond`.
+    #[structopt(name = "unbond")]
+    Unbond {
+        #[structopt(long)]
+        value: Balance,
+    },
+    /// `staking.rebond`.
+    #[structopt(name = "rebond")]
+    Rebond {
+        #[structopt(long)]
+        value: Balance,
+    },
+    /// `staking.withdraw_unbonded`.
+    #[structopt(name = "withdraw-unbonded")]
+    WithdrawUnbonded {
+        #[structopt(long, default_value = "0")]
+        num_slashing_spans: u32,
+    },
+    /// `staking.validate`.
+    #[structopt(name = "validate")]
+    Validate {
+        /// Commission, in percent.
+        #[structopt(long, default_value = "0")]
+        commission: f64,
+        /// Blocks new nominations.
+        #[structopt(long)]
+        blocked: bool,
+    },
+    /// `staking.nominate`.
+    #[structopt(name = "nominate")]
+    Nominate {
+        /// Comma separated validator stashes.
+        #[structopt(long, required = true, use_delimiter = true)]
+        targets: Vec<AccountId32>,
+    },
+    /// `staking.chill`.
+    #[structopt(name = "chill")]
+    Chill,
+    /// `staking.set_payee`.
+    #[structopt(name = "set-payee")]
+    SetPayee {
+        /// Reward destination: `staked`, `stash`, `none` or `account:<ADDRESS>`.
+        #[structopt(long)]
+        payee: Payee,
+    },
+    /// `staking.payout_stakers`, or `staking.payout_stakers_by_page` with `--page`.
+    #[structopt(name = "payout-stakers")]
+    PayoutStakers {
+        #[structopt(long)]
+        validator_stash: AccountId32,
+        #[structopt(long)]
+        era: u32,
+        #[structopt(long)]
+        page: Option<u32>,
+    },
+    /// `staking.chill_other`.
+    #[structopt(name = "chill-other")]
+    ChillOther {
+        #[structopt(long)]
+        stash: AccountId32,
+    },
+    /// `staking.kick`.
+    #[structopt(name = "kick")]
+    Kick {
+        /// Comma separated nominator stashes.
+        #[structopt(long, required = true, use_delimiter = true)]
+        who: Vec<AccountId32>,
+    },
+    /// `staking.reap_stash`.
+    #[structopt(name = "reap-stash")]
+    ReapStash {
+        #[structopt(long)]
+        stash: AccountId32,
+        #[structopt(long, default_value = "0")]
+        num_slashing_spans: u32,
+    },
+    /// `staking.force_unstake`.
+    #[structopt(name = "force-unstake")]
+    ForceUnstake {
+        #[structopt(long)]
+        stash: AccountId32,
+        #[structopt(long, default_value = "0")]
+        num_slashing_spans: u32,
+    },
+    /// `staking.set_validator_count`.
+    #[structopt(name = "set-validator-count")]
+    SetValidatorCount {
+        #[structopt(long)]
+        new: u32,
+    },
+    /// `staking.set_min_commission`.
+    #[structopt(name = "set-min-commission")]
+    SetMinCommission {
+        /// Minimum commission, in percent.
+        #[structopt(long)]
+        percent: f64,
+    },
+    /// `staking.force_apply_min_commission`.
+    #[structopt(name = "force-apply-min-commission")]
+    ForceApplyMinCommission {
+        #[structopt(long)]
+        validator_stash: AccountId32,
+    },
+    /// `staking.force_no_eras`.
+    #[structopt(name = "force-no-eras")]
+    ForceNoEras,
+    /// `staking.force_new_era`.
+    #[structopt(name = "force-new-era")]
+    ForceNewEra,
+    /// `staking.force_new_era_always`.
+    #[structopt(name = "force-new-era-always")]
+    ForceNewEraAlways,
+}
+
+/// Changes of the staking configs, each `remove` or the new value. Unset configs are left
+/// untouched.
+#[derive(Clone, Debug, StructOpt)]
+pub(crate) struct StakingConfigChanges {
+    #[structopt(long)]
+    min_nominator_bond: Option<ConfigChange<Balance>>,
+    #[structopt(long)]
+    min_validator_bond: Option<ConfigChange<Balance>>,
+    #[structopt(long)]
+    max_nominator_count: Option<ConfigChange<u32>>,
+    #[structopt(long)]
+    max_validator_count: Option<ConfigChange<u32>>,
+    /// In percent.
+    #[structopt(long)]
+    chill_threshold: Option<ConfigChange<u8>>,
+    /// In percent.
+    #[structopt(long)]
+    min_commission: Option<ConfigChange<f64>>,
+    /// In percent.
+    #[structopt(long)]
+    max_staked_rewards: Option<ConfigChange<u8>>,
+}
+
+/// Arguments required for creating and sending an extrinsic to a substrate node.
+#[derive(Clone, Debug, StructOpt)]
+pub(crate) struct Configs {
+    /// RPC endpoint for the collator. `validate`, `nominate` and `clone-stakers` accept a comma
+    /// separated list of endpoints of different chains and run against all of them at once,
+    /// with a state and an index file per chain.
+    #[structopt(name = "url", long, short)]
+    url: String,
+    /// Secret URI of the account signing the extrinsics.
+    #[structopt(long, default_value = "//Alice")]
+    signer: String,
+    /// Wraps the submitted calls that need the Root origin, e.g. `staking.force_unstake`, in
+    /// `sudo.sudo`, signed by the signer as the sudo key. The other calls are signed as usual.
+    #[structopt(long)]
+    sudo: bool,
+    /// Uses `sudo.sudo_unchecked_weight` with the given weight instead of `sudo.sudo`.
+    #[structopt(long, number_of_values = 2, value_names = &["REF_TIME", "PROOF_SIZE"], requires = "sudo")]
+    sudo_unchecked_weight: Option<Vec<u64>>,
+    /// File where the accounts created by the CLI are kept track of.
+    #[structopt(long, parse(from_os_str), default_value = "staking-cli-state.json")]
+    state: PathBuf,
+    /// Restricts the command to the CLI-created accounts with all these tags, and tags the
+    /// accounts it creates with them.
+    #[structopt(long = "tag")]
+    tags: Vec<String>,
+    /// File where the chain data collected by the CLI is indexed.
+    #[structopt(long, parse(from_os_str), default_value = "staking-cli-index.json")]
+    index: PathBuf,
+    /// Utility call used for batched operations: `batch`, `batch_all` or `force_batch`.
+    #[structopt(long, default_value = "batch_all")]
+    batch_mode: BatchMode,
+    /// Mints funds to the funder through sudo when its balance runs low, if the signer is the
+    /// sudo key.
+    #[structopt(long)]
+    auto_topup: bool,
+    /// Starts runs even if the preflight checks report that chain limits would be violated.
+    #[structopt(long)]
+    force: bool,
+    /// Format of the command results: `plain`, `table` or `json`. Unless `--no-identities`, JSON
+    /// results get an `identities` object with the identities of the accounts they show, and the
+    /// fees paid by a run follow in a `fees` document.
+    #[structopt(long, default_value = "plain")]
+    output: output::OutputFormat,
+    /// Runs against a chopsticks fork: produces a block after every submission and follows
+    /// the best blocks instead of the finalized ones.
+    #[structopt(long)]
+    fork_mode: bool,
+    /// Slack, Discord or generic JSON webhook that daemon modes post alerts, slashes, era
+    /// changes, election failures and run completions to.
+    #[structopt(long)]
+    webhook: Option<String>,
+    /// Submits at most this many extrinsics per block, pacing them against the block
+    /// production instead of submitting them all at once.
+    #[structopt(long)]
+    rate: Option<std::num::NonZeroUsize>,
+    /// Block the state is read at: the latest `finalized` one or the `best` one.
+    #[structopt(long, default_value = "finalized")]
+    read_at: ReadAt,
+    /// Hash of a block to read the state at, instead of `--read-at`.
+    #[structopt(long)]
+    read_block: Option<H256>,
+    /// Holds the submissions until a block: `<number>`, or `<phase>[+|-]<blocks>` relative to
+    /// the next start of an election phase (`snapshot`, `signed`, `signed-validation`,
+    /// `unsigned` or `export`), e.g. `snapshot-10`. Phase starts are predicted from the rounds
+    /// recorded by `monitor election` in the index.
+    #[structopt(long)]
+    submit_at: Option<Schedule>,
+    /// Retries the submissions rejected for their priority with increasing tips, up to this
+    /// one.
+    #[structopt(long)]
+    max_tip: Option<Balance>,
+    /// Pays the fees, and tips, in this asset rather than the native token, signing with the
+    /// `ChargeAssetTxPayment` extension. Fails if the runtime has no such extension.
+    #[structopt(long)]
+    fee_asset: Option<u32>,
+    /// Times a submission rejected for a stale or already pooled nonce, or stuck, is re-signed
+    /// with the next nonce of the pool.
+    #[structopt(long, default_value = "3")]
+    max_resubmits: u32,
+    /// Resubmits, re-signed with a fresh nonce, the extrinsics neither included nor in the pool
+    /// after this many blocks.
+    #[structopt(long)]
+    stuck_after: Option<u32>,
+    /// Signs the extrinsics and appends them, hex encoded, to this file instead of submitting
+    /// them. Nonces are tracked locally from the next index of each signer on the chain. Still
+    /// reads the chain, and commands depending on the outcome of an extrinsic fail.
+    #[structopt(long, parse(from_os_str))]
+    offline: Option<PathBuf>,
+    /// First nonce of the signers with `--offline`, instead of their next index on the chain.
+    #[structopt(long, requires = "offline")]
+    offline_nonce: Option<u64>,
+    /// Next nonce of every signer with `--offline`.
+    #[structopt(skip)]
+    offline_nonces: std::sync::Arc<std::sync::Mutex<std::collections::BTreeMap<AccountId32, u64>>>,
+    /// Skips resolving the on-chain identities of the accounts shown in the command results,
+    /// keeping the JSON results without their `identities` object.
+    #[structopt(long)]
+    no_identities: bool,
+    /// RPC endpoint of the chain holding the identities, e.g. a people chain. Defaults to
+    /// `--url`.
+    #[structopt(long)]
+    identity_url: Option<String>,
+    /// Aborts if the spec name of the connected chain is not this one.
+    #[structopt(long)]
+    expect_spec: Option<String>,
+    /// Aborts if the genesis hash of the connected chain is not this one.
+    #[structopt(long)]
+    expect_genesis: Option<H256>,
+    /// Aborts if the spec version of the connected chain is lower than this one.
+    #[structopt(long)]
+    min_spec_version: Option<u32>,
+    /// Before running, waits up to this many seconds for the node to be reachable, synced and
+    /// producing blocks.
+    #[structopt(long)]
+    wait_sync: Option<u64>,
+    /// Address daemon modes serve their status on, as JSON over HTTP.
+    #[structopt(long)]
+    status_addr: Option<std::net::SocketAddr>,
+    /// Status of the running command, shared with the status endpoint.
+    #[structopt(skip)]
+    status: status::StatusHandle,
+}
+
+/// Semantics of the utility call wrapping batched operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BatchMode {
+    /// Stops at the first failed call, keeping the effects of the previous ones.
+    Batch,
+    /// Reverts all calls if any of them fails.
+    BatchAll,
+    /// Dispatches all calls, regardless of failures.
+    ForceBatch,
+}
+
+impl FromStr for BatchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "batch" => Ok(Self::Batch),
+            "batch_all" => Ok(Self::BatchAll),
+            "force_batch" => Ok(Self::ForceBatch),
+            _ => Err(format!("unknown batch mode {s}")),
+        }
+    }
+}
+
+/// Block the state used for decisions is read at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReadAt {
+    Best,
+    Finalized,
+}
+
+impl FromStr for ReadAt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "best" => Ok(Self::Best),
+            "finalized" => Ok(Self::Finalized),
+            _ => Err(format!("unknown block to read at {s}")),
+        }
+    }
+}
+
+/// Block at which the submissions of a command are scheduled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Schedule {
+    /// At a given block number.
+    Block(u32),
+    /// `offset` blocks after (or before, if negative) the next start of an election phase.
+    Phase { phase: &'static str, offset: i64 },
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(block) = s.parse() {
+            return Ok(Self::Block(block));
+        }
+
+        let split = s
+            .rfind(['+', '-'])
+            .and_then(|i| Some((i, s[i + 1..].parse::<i64>().ok()?)));
+        let (anchor, offset) = match split {
+            Some((i, blocks)) if &s[i..=i] == "-" => (&s[..i], -blocks),
+            Some((i, blocks)) => (&s[..i], blocks),
+            None => (s, 0),
+        };
+        let phase = match anchor {
+            "snapshot" => "Snapshot",
+            "signed" => "Signed",
+            "signed-validation" => "SignedValidation",
+            "unsigned" => "Unsigned",
+            "export" => "Export",
+            _ => return Err(format!("unknown schedule {s}")),
+        };
+
+        Ok(Self::Phase { phase, offset })
+    }
+}
+
+/// Voters trimmed first when mining from fewer voters than the snapshot has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum VoterTrimming {
+    /// The voters with the least stake, as the snapshot does.
+    Stake,
+    /// The voters with the most votes.
+    Votes,
+}
+
+impl FromStr for VoterTrimming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stake" => Ok(Self::Stake),
+            "votes" => Ok(Self::Votes),
+            _ => Err(format!("unknown voter trimming {s}")),
+        }
+    }
+}
+
+/// Comparison of an assertion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CmpOp {
+    Ge,
+    Le,
+    Gt,
+    Lt,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    pub(crate) fn holds(&self, actual: u128, expected: u128) -> bool {
+        match self {
+            Self::Ge => actual >= expected,
+            Self::Le => actual <= expected,
+            Self::Gt => actual > expected,
+            Self::Lt => actual < expected,
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+        }
+    }
+}
+
+/// An assertion of `assert`: a metric of the chain compared to a value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Assertion {
+    pub(crate) metric: String,
+    pub(crate) op: CmpOp,
+    pub(crate) value: u128,
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "no_failed_extrinsics" {
+            return Ok(Self {
+                metric: "failed_extrinsics".to_string(),
+                op: CmpOp::Eq,
+                value: 0,
+            });
+        }
+
+        let ops = [
+            (">=", CmpOp::Ge),
+            ("<=", CmpOp::Le),
+            ("==", CmpOp::Eq),
+            ("!=", CmpOp::Ne),
+            (">", CmpOp::Gt),
+            ("<", CmpOp::Lt),
+        ];
+        let Some((metric, op, value)) = ops.iter().find_map(|(token, op)| {
+            s.split_once(token)
+                .map(|(metric, value)| (metric.trim(), *op, value.trim()))
+        }) else {
+            return Err(format!(
+                "invalid assertion {s}, expected <metric> <op> <value>"
+            ));
+        };
+        let value = value
+            .replace('_', "")
+            .parse()
+            .map_err(|_| format!("invalid value {value} in assertion {s}"))?;
+
+        Ok(Self {
+            metric: metric.to_string(),
+            op,
+            value,
+        })
+    }
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self.op {
+            CmpOp::Ge => ">=",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Lt => "<",
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+        };
+        write!(f, "{} {op} {}", self.metric, self.value)
+    }
+}
+
+/// Change of a staking config: `remove` or the new value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ConfigChange<T> {
+    Set(T),
+    Remove,
+}
+
+impl<T: FromStr> FromStr for ConfigChange<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "remove" => Ok(Self::Remove),
+            _ => s
+                .parse()
+                .map(Self::Set)
+                .map_err(|_| format!("invalid config value {s}")),
+        }
+    }
+}
+
+impl FromStr for PoolState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "blocked" => Ok(Self::Blocked),
+            "destroying" => Ok(Self::Destroying),
+            _ => Err(format!("unknown pool state {s}")),
+        }
+    }
+}
+
+/// Reward destination of the stakers created by the CLI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Payee {
+    Staked,
+    Stash,
+    Account(AccountId32),
+    None,
+    /// A random destination for every staker.
+    Random,
+}
+
+impl FromStr for Payee {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "staked" => Ok(Self::Staked),
+            "stash" => Ok(Self::Stash),
+            "none" => Ok(Self::None),
+            "random" => Ok(Self::Random),
+            _ => match s.strip_prefix("account:") {
+                Some(address) => AccountId32::from_str(address)
+                    .map(Self::Account)
+                    .map_err(|e| format!("invalid payee account {address}: {e:?}")),
+                None => Err(format!("unknown payee {s}")),
+            },
+        }
+    }
+}
+
+impl Payee {
+    /// The reward destination set when bonding. Random destinations pay to the signer account.
+    pub(crate) fn reward_destination(
+        &self,
+        configs: &Configs,
+    ) -> color_eyre::Result<RewardDestination<AccountId32>> {
+        let destination = match self {
+            Self::Staked => RewardDestination::Staked,
+            Self::Stash => RewardDestination::Stash,
+            Self::Account(who) => RewardDestination::Account(who.clone()),
+            Self::None => RewardDestination::None,
+            Self::Random => match rand::thread_rng().gen_range(0..4) {
+                0 => RewardDestination::Staked,
+                1 => RewardDestination::Stash,
+                2 => RewardDestination::Account(configs.signer()?.public_key().into()),
+                _ => RewardDestination::None,
+            },
+        };
+
+        Ok(destination)
+    }
+}
+
+impl Configs {
+    /// Derives the key pair of the configured signer.
+    pub(crate) fn signer(&self) -> color_eyre::Result<Keypair> {
+        let uri = SecretUri::from_str(&self.signer)?;
+
+        Ok(Keypair::from_uri(&uri)?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    env_logger::init();
+    color_eyre::install()?;
+
+    run(Opts::from_args().command).await
+}
+
+/// Runs a parsed command.
+async fn run(command: Command) -> color_eyre::Result<()> {
+    if let Some(configs) = command.configs() {
+        if configs.url.contains(',') {
+            return commands::multi_chain(command).await;
+        }
+        if let Some(timeout) = configs.wait_sync {
+            helpers::wait_for_sync(configs, std::time::Duration::from_secs(timeout)).await?;
+        }
+        helpers::ensure_chain(configs).await?;
+        if configs.read_block.is_some() && command.follows_chain() {
+            bail!("--read-block pins every read to one block, the command follows the chain");
+        }
+        if configs.offline.is_some() && command.follows_chain() {
+            bail!("--offline submits nothing, the command follows its effects on the chain");
+        }
+    }
+
+    let configs = match command {
+        Command::Validate {
+            parachain_id,
+            number,
+            bond_amount,
+            payee,
+            alice,
+            configs,
+        } => commands::validate(parachain_id, number, bond_amount, payee, alice, configs).await,
+        Command::Nominate {
+            parachain_id,
+            number,
+            bond_amount,
+            nominations,
+            payee,
+            alice,
+            configs,
+        } => {
+            commands::nominate(
+                parachain_id,
+                number,
+                bond_amount,
+                nominations,
+                payee,
+                alice,
+                configs,
+            )
+            .await
+        }
+        Command::StakersInfo { configs } => commands::stakers_info(configs).await,
+        Command::Validators(ValidatorsCommand::List { configs }) => {
+            commands::validators_list(configs).await
+        }
+        Command::Validators(ValidatorsCommand::Oversubscription { era, configs }) => {
+            commands::validators_oversubscription(era, configs).await
+        }
+        Command::Validators(ValidatorsCommand::SelfStake {
+            era,
+            low_percent,
+            configs,
+        }) => commands::validators_self_stake(era, low_percent, configs).await,
+        Command::Validators(ValidatorsCommand::Commissions {
+            bucket,
+            csv,
+            configs,
+        }) => commands::validators_commissions(bucket, csv, configs).await,
+        Command::Validators(ValidatorsCommand::Disabled { configs }) => {
+            commands::validators_disabled(configs).await
+        }
+        Command::Election(ElectionCommand::Scores { watch, configs }) => {
+            commands::election_scores(watch, configs).await
+        }
+        Command::Election(ElectionCommand::Submissions { balancing, configs }) => {
+            commands::election_submissions(balancing, configs).await
+        }
+        Command::Election(ElectionCommand::Estimate {
+            verifier_pallet,
+            configs,
+        }) => commands::election_estimate(verifier_pallet, configs).await,
+        Command::Election(ElectionCommand::Plan {
+            validators,
+            nominators,
+            nominations,
+            bond_amount,
+            max_bond,
+            verifier_pallet,
+            configs,
+        }) => {
+            commands::election_plan(
+                validators,
+                nominators,
+                nominations,
+                bond_amount,
+                max_bond,
+                verifier_pallet,
+                configs,
+            )
+            .await
+        }
+        Command::Election(ElectionCommand::Mine {
+            balancing,
+            reduce,
+            max_voters,
+            voter_trimming,
+            max_backers,
+            configs,
+        }) => {
+            let params = helpers::MiningParams {
+                balancing,
+                reduce,
+                max_voters,
+                voter_trimming,
+                max_backers,
+            };
+            commands::election_mine(params, configs).await
+        }
+        Command::Election(ElectionCommand::Timing { rounds, configs }) => {
+            commands::election_timing(rounds, configs).await
+        }
+        Command::Monitor(MonitorCommand::Unsigned {
+            alert_blocks,
+            configs,
+        }) => commands::monitor_unsigned(alert_blocks, configs).await,
+        Command::Monitor(MonitorCommand::Election { slack, configs }) => {
+            commands::monitor_election(slack, configs).await
+        }
+        Command::EraPoints {
+            era,
+            managed,
+            configs,
+        } => commands::era_points(era, managed, configs).await,
+        Command::RebagAll { configs } => commands::rebag_all(configs).await,
+        Command::PutInFrontOf { nominator, configs } => {
+            commands::put_in_front_of(nominator, configs).await
+        }
+        Command::RotateNominations {
+            fraction,
+            nominations,
+            every,
+            configs,
+        } => commands::rotate_nominations(fraction, nominations, every, configs).await,
+        Command::Ramp {
+            step,
+            decrease,
+            eras,
+            configs,
+        } => commands::ramp(step, decrease, eras, configs).await,
+        Command::SetMinCommission {
+            percent,
+            force_apply,
+            configs,
+        } => commands::set_min_commission(percent, force_apply, configs).await,
+        Command::FastForward { eras, configs } => commands::fast_forward(eras, configs).await,
+        Command::FullExit { daemon, configs } => commands::full_exit(daemon, configs).await,
+        Command::Tag {
+            add,
+            remove,
+            role,
+            first,
+            configs,
+        } => commands::tag(add, remove, role, first, configs).await,
+        Command::Reap { configs } => commands::reap(configs).await,
+        Command::Authorship { from, to, configs } => commands::authorship(from, to, configs).await,
+        Command::Dump {
+            out,
+            at,
+            pallets,
+            scale,
+            configs,
+        } => commands::dump(out, at, pallets, scale, configs).await,
+        Command::Restore {
+            dump,
+            clear,
+            chunk_size,
+            genesis_out,
+            configs,
+        } => commands::restore(dump, clear, chunk_size, genesis_out, configs).await,
+        Command::VerifyDump { dump, configs } => commands::verify_dump(dump, configs).await,
+        Command::Stats(StatsCommand::Concentration {
+            era,
+            top_k,
+            configs,
+        }) => commands::stats_concentration(era, top_k, configs).await,
+        Command::Offences { from, to, configs } => commands::offences(from, to, configs).await,
+        Command::StakingMessages {
+            rc_client_pallet,
+            configs,
+        } => commands::staking_messages(rc_client_pallet, configs).await,
+        Command::Session(SessionCommand::Info { configs }) => commands::session_info(configs).await,
+        Command::Pool(PoolCommand::Status { metrics, configs }) => {
+            commands::pool_status(metrics, configs).await
+        }
+        Command::Pool(PoolCommand::Pending { configs }) => commands::pool_pending(configs).await,
+        Command::Pools(PoolsCommand::Loadtest {
+            pools,
+            members,
+            bond_amount,
+            churn,
+            eras,
+            configs,
+        }) => commands::pools_loadtest(pools, members, bond_amount, churn, eras, configs).await,
+        Command::Pools(PoolsCommand::Claim {
+            commission,
+            configs,
+        }) => commands::pools_claim(commission, configs).await,
+        Command::Pools(PoolsCommand::Exit {
+            destroy,
+            max_eras,
+            configs,
+        }) => commands::pools_exit(destroy, max_eras, configs).await,
+        Command::Pools(PoolsCommand::Commission {
+            pool,
+            rate,
+            payee,
+            max,
+            max_increase,
+            min_delay,
+            configs,
+        }) => {
+            let change_rate = max_increase.zip(min_delay);
+            commands::pools_commission(pool, rate, payee, max, change_rate, configs).await
+        }
+        Command::Pools(PoolsCommand::State {
+            pool,
+            state,
+            configs,
+        }) => commands::pools_state(pool, state, configs).await,
+        Command::Pools(PoolsCommand::Roles {
+            pool,
+            root,
+            nominator,
+            bouncer,
+            configs,
+        }) => commands::pools_roles(pool, [root, nominator, bouncer], configs).await,
+        Command::Delegated(DelegatedCommand::Agents { configs }) => {
+            commands::delegated_agents(configs).await
+        }
+        Command::Delegated(DelegatedCommand::Delegators { agent, configs }) => {
+            commands::delegated_delegators(agent, configs).await
+        }
+        Command::Keys(KeysCommand::Insert {
+            nodes,
+            key_types,
+            set_keys,
+            configs,
+        }) => commands::keys_insert(nodes, key_types, set_keys, configs).await,
+        Command::Exposures(ExposuresCommand::Pages { era, configs }) => {
+            commands::exposure_pages(era, configs).await
+        }
+        Command::Exposures(ExposuresCommand::Page {
+            validator,
+            page,
+            era,
+            configs,
+        }) => commands::exposure_page(validator, page, era, configs).await,
+        Command::Nominators(NominatorsCommand::Distribution { buckets, configs }) => {
+            commands::nominators_distribution(buckets, configs).await
+        }
+        Command::Bags(BagsCommand::Members { bag, configs }) => {
+            commands::bag_members(bag, configs).await
+        }
+        Command::Check(CheckCommand::Ledgers { configs }) => commands::check_ledgers(configs).await,
+        Command::Check(CheckCommand::Config { expected, configs }) => {
+            commands::check_config(expected, configs).await
+        }
+        Command::Check(CheckCommand::BelowMin { chill, configs }) => {
+            commands::check_below_min(chill, configs).await
+        }
+        Command::Check(CheckCommand::VirtualStakers { configs }) => {
+            commands::check_virtual_stakers(configs).await
+        }
+        Command::Check(CheckCommand::Authorship {
+            era,
+            window,
+            configs,
+        }) => commands::check_authorship(era, window, configs).await,
+        Command::Rewards(RewardsCommand::Status { configs }) => {
+            commands::rewards_status(configs).await
+        }
+        Command::Rewards(RewardsCommand::Payout { configs }) => {
+            commands::rewards_payout(configs).await
+        }
+        Command::Export(ExportCommand::Eras {
+            from,
+            to,
+            out,
+            configs,
+        }) => commands::export_eras(from, to, out, configs).await,
+        Command::WaitUntilActive {
+            fraction,
+            max_eras,
+            configs,
+        } => commands::wait_until_active(fraction, max_eras, configs).await,
+        Command::Quota {
+            bonds,
+            accounts,
+            configs,
+        } => commands::quota(bonds, accounts, configs).await,
+        Command::SimulateTrimming { managed, configs } => {
+            commands::simulate_trimming(managed, configs).await
+        }
+        Command::Runtime { configs } => commands::runtime(configs).await,
+        Command::Doctor {
+            finality_timeout,
+            min_balance,
+            configs,
+        } => commands::doctor(finality_timeout, min_balance, configs).await,
+        Command::CiCheck {
+            expected_configs,
+            assertions,
+            since,
+            election_at,
+            tolerance,
+            configs,
+        } => {
+            commands::ci_check(
+                expected_configs,
+                assertions,
+                since,
+                election_at,
+                tolerance,
+                configs,
+            )
+            .await
+        }
+        Command::VerifyElection {
+            era,
+            at,
+            balancing,
+            tolerance,
+            configs,
+        } => commands::verify_election(era, at, balancing, tolerance, configs).await,
+        Command::SubmitRaw {
+            calls,
+            batch,
+            configs,
+        } => commands::submit_raw(calls, batch, configs).await,
+        Command::SubmitFile { file, configs } => commands::submit_file(file, configs).await,
+        Command::Encode { sudo, output, call } => return commands::encode(call, sudo, output),
+        Command::Schedule {
+            at,
+            managed,
+            as_account,
+            priority,
+            configs,
+            call,
+        } => commands::schedule(at, managed, as_account, priority, call, configs).await,
+        Command::Decode { call, output } => return commands::decode(call, output),
+        Command::Assert {
+            assertions,
+            since,
+            configs,
+        } => commands::assert(assertions.join(" "), since, configs).await,
+        Command::OpenGov {
+            changes,
+            origin,
+            enactment_after,
+            voters,
+            conviction,
+            max_blocks,
+            configs,
+        } => {
+            commands::open_gov(
+                changes,
+                origin,
+                enactment_after,
+                voters,
+                conviction,
+                max_blocks,
+                configs,
+            )
+            .await
+        }
+        Command::Address {
+            address,
+            prefixes,
+            output,
+        } => return commands::address(address, prefixes, output),
+        Command::Propose {
+            changes,
+            origin,
+            enactment_after,
+            submit,
+            configs,
+        } => commands::propose(changes, origin, enactment_after, submit, configs).await,
+        Command::Genesis {
+            validators,
+            nominators,
+            bond_amount,
+            nominations,
+            validator_count,
+            invulnerables,
+            session_keys,
+            out,
+            state,
+        } => {
+            return commands::genesis(
+                validators,
+                nominators,
+                bond_amount,
+                nominations,
+                validator_count,
+                invulnerables,
+                session_keys,
+                out,
+                state,
+            )
+        }
+        Command::Inject {
+            validators,
+            nominators,
+            bond_amount,
+            nominations,
+            chunk_size,
+            separate_controller,
+            configs,
+        } => {
+            commands::inject(
+                validators,
+                nominators,
+                bond_amount,
+                nominations,
+                chunk_size,
+                separate_controller,
+                configs,
+            )
+            .await
+        }
+        Command::CloneStakers {
+            from,
+            validators,
+            nominators,
+            scale,
+            chunk_size,
+            configs,
+        } => {
+            commands::clone_stakers(from, validators, nominators, scale, chunk_size, configs).await
+        }
+        Command::Compare {
+            a,
+            b,
+            tolerance,
+            output,
+        } => return commands::compare(a, b, tolerance, output).await,
+        Command::Spawn {
+            zombienet,
+            provider,
+            config,
+            url,
+            wait_blocks,
+            command,
+        } => {
+            let mut network =
+                commands::spawn(zombienet, provider, config, &url, wait_blocks).await?;
+            if command.is_empty() {
+                println!("> Network running, stop it with Ctrl-C.");
+                network.wait()?;
+                return Ok(());
+            }
+            let command =
+                Command::from_iter(std::iter::once("staking-cli".to_string()).chain(command));
+            return Box::pin(run(command)).await;
+        }
+        Command::RunScenario {
+            scenario,
+            url,
+            rehearse,
+            chopsticks,
+            fork_port,
+        } => return commands::run_scenario(scenario, url, rehearse, chopsticks, fork_port).await,
+        Command::Playground { configs } => commands::playground(configs).await,
+    }?;
+
+    let fees = configs.status.snapshot().fees;
+    if !fees.is_empty() {
+        match configs.output {
+            // a `{"fees"}` document following the one of the command result.
+            output::OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "fees": fees }))?
+            ),
+            format => output::render(format, &output::FeeReport(fees))?,
+        }
+    }
+
+    Ok(())
+}
+
+mod commands {
+    use super::*;
+    use index::Index;
+    use state::{Role, State};
+
+    /// Makes sure there are `n_validators` CLI-created validators, bonding and setting as
+    /// validators new accounts and the existing ones which are not yet.
+    pub(crate) async fn validate(
+        _para_id: u32,
+        n_validators: usize,
+        bond_amount: Balance,
+        payee: Payee,
+        alice: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let keypairs = if alice {
+            println!("> Bonding and setting Alice as validator..");
+            vec![(dev::alice(), dev::alice())]
+        } else {
+            println!(
+                "> Generating and funding, bonding and setting as validators {n_validators} accounts.."
+            );
+            helpers::managed_stakers(&api, &configs, Role::Validator, n_validators, bond_amount)
+                .await?
+        };
+
+        let mut bond_calls = vec![];
+        let mut validate_calls = vec![];
+
+        // prepare both bond and validate calls for the keypairs which need them.
+        for (pair, controller) in keypairs.into_iter() {
+            let status = helpers::staker_status(&api, &configs, &pair.public_key().into()).await?;
+            if !status.bonded {
+                let bond_call = RuntimeCall::Staking(StakingCall::bond {
+                    value: bond_amount,
+                    payee: payee.reward_destination(&configs)?,
+                });
+                bond_calls.push((pair, bond_call));
+            }
+            if !status.validating {
+                let validate_call = RuntimeCall::Staking(StakingCall::validate {
+                    prefs: staking_parachain::runtime_types::pallet_staking::ValidatorPrefs {
+                        commission: Perbill(10),
+                        blocked: false,
+                    },
+                });
+                validate_calls.push((controller, validate_call));
+            }
+        }
+
+        let n_bonded = bond_calls.len();
+        helpers::submit_all(&api, &configs, bond_calls).await?;
+        println!("Bonding done for {n_bonded} staker(s).");
+
+        let n_validators = validate_calls.len();
+        helpers::submit_all(&api, &configs, validate_calls).await?;
+        println!("Validating done for {n_validators} staker(s).");
+        let configs = stakers_info(configs).await?;
+
+        Ok(configs)
+    }
+
+    /// Makes sure there are `n_nominators` CLI-created nominators, bonding and nominating with
+    /// new accounts and the existing ones which are not nominating yet.
+    pub(crate) async fn nominate(
+        _para_id: u32,
+        n_nominators: usize,
+        bond_amount: Balance,
+        nominations: usize,
+        payee: Payee,
+        alice: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let current_validators = helpers::get_validators(&api, &configs).await?;
+
+        let targets = if alice {
+            let alice_target: Target = dev::alice().public_key().into();
+            vec![alice_target]
+        } else {
+            helpers::select_targets(nominations, current_validators)
+        };
+        let max_nominations = helpers::max_nominations(&api, &configs).await?;
+        let targets = helpers::preflight_targets(&api, &configs, targets, max_nominations).await?;
+
+        let keypairs = if alice {
+            println!("> Bonding and setting Alice as nominator..");
+            vec![(dev::alice(), dev::alice())]
+        } else {
+            println!(
+                "> Generating and funding, bonding and setting as nominators {n_nominators} accounts.."
+            );
+            helpers::managed_stakers(&api, &configs, Role::Nominator, n_nominators, bond_amount)
+                .await?
+        };
+
+        let mut bond_calls = vec![];
+        let mut nominate_calls = vec![];
+        let mut quotas = std::collections::BTreeMap::new();
+        let mut n_clamped = 0;
+
+        // prepare both bond and nominate calls for the keypairs which need them.
+        for (pair, controller) in keypairs.into_iter() {
+            let status = helpers::staker_status(&api, &configs, &pair.public_key().into()).await?;
+            if status.nominating {
+                continue;
+            }
+            let bond = if status.bonded {
+                helpers::active_bond(&api, &configs, &controller.public_key().into()).await?
+            } else {
+                bond_amount
+            };
+            let quota = helpers::nominations_quota(&api, &configs, &mut quotas, bond).await?;
+            let mut targets = targets.clone();
+            if let Some(quota) = quota.filter(|quota| (*quota as usize) < targets.len()) {
+                targets.truncate(quota as usize);
+                n_clamped += 1;
+            }
+            if targets.is_empty() {
+                let who: AccountId32 = pair.public_key().into();
+                println!(" a bond of {bond} allows no nomination, skipping {who}.");
+                continue;
+            }
+            if !status.bonded {
+                let bond_call = RuntimeCall::Staking(StakingCall::bond {
+                    value: bond_amount,
+                    payee: payee.reward_destination(&configs)?,
+                });
+                bond_calls.push((pair, bond_call));
+            }
+            let nominate_call = RuntimeCall::Staking(StakingCall::nominate { targets });
+            nominate_calls.push((controller, nominate_call));
+        }
+        if n_clamped > 0 {
+            println!(" nominations clamped to the quota of their bond for {n_clamped} staker(s).");
+        }
+
+        let n_bonded = bond_calls.len();
+        helpers::submit_all(&api, &configs, bond_calls).await?;
+        println!("Bonding done for {n_bonded} staker(s).");
+
+        let n_nominators = nominate_calls.len();
+        helpers::submit_all(&api, &configs, nominate_calls).await?;
+        println!("Nominations done for {n_nominators} staker(s).");
+        let configs = stakers_info(configs).await?;
+
+        Ok(configs)
+    }
+
+    /// Fetches the current stakers info.
+    pub(crate) async fn stakers_info(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let validators = helpers::get_validators(&api, &configs).await?;
+        let nominators = helpers::get_nominators(&api, &configs).await?;
+        let (active_era, active, waiting) = helpers::classify_validators(&api, &configs).await?;
+
+        helpers::render(
+            &configs,
+            &output::StakersInfo {
+                validators: validators.len(),
+                active_era,
+                active: active.len(),
+                waiting: waiting.len(),
+                nominators: nominators.len(),
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Lists all registered validators, split into the active set (with their era points) and
+    /// the waiting ones.
+    pub(crate) async fn validators_list(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let (active_era, active, waiting) = helpers::classify_validators(&api, &configs).await?;
+        let total_points: u32 = active.iter().map(|(_, points)| points).sum();
+
+        helpers::render(
+            &configs,
+            &output::ValidatorsList {
+                active_era,
+                active: active
+                    .into_iter()
+                    .map(|(validator, points)| output::ActiveValidator { validator, points })
+                    .collect(),
+                total_points,
+                waiting,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Records the score of the currently queued election solution in the index, keyed by the
+    /// era being elected, and prints how the recorded scores evolved across eras.
+    pub(crate) async fn election_scores(
+        watch: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let mut index = Index::load(&configs.index)?;
+
+        let mut previous = None;
+        let mut recorded = vec![];
+        for (era, score) in index.election_scores.iter() {
+            recorded.push(output::EraScore::new(*era, score, previous));
+            previous = Some(score);
+        }
+        helpers::render(&configs, &output::ElectionScores(recorded)).await?;
+
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        loop {
+            if let Some((era, score)) = helpers::queued_score(&api, &configs).await? {
+                if index.election_scores.get(&era) != Some(&score) {
+                    let previous = index
+                        .election_scores
+                        .range(..era)
+                        .next_back()
+                        .map(|(_, s)| s);
+                    helpers::render(&configs, &output::EraScore::new(era, &score, previous))
+                        .await?;
+
+                    index.election_scores.insert(era, score);
+                    index.save(&configs.index)?;
+                }
+            }
+
+            if !watch {
+                break;
+            }
+            match blocks.next().await {
+                Some(block) => drop(block?),
+                None => break,
+            }
+        }
+
+        Ok(configs)
+    }
+
+    /// Estimates the encoded size of the voter snapshot, from the nominations of every
+    /// nominator and the self votes of the validators, and of a page of a solution electing
+    /// `ValidatorCount` winners, and compares them with the snapshot bounds, the max proof size
+    /// and length of a block, and the bounds of `verifier_pallet` if the runtime has it. Both
+    /// sizes are estimates: the solution one assumes every voter backs its share of winners.
+    pub(crate) async fn election_estimate(
+        verifier_pallet: String,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+
+        let targets = storage
+            .fetch_or_default(&query.counter_for_validators())
+            .await?;
+        let desired_targets = storage.fetch_or_default(&query.validator_count()).await?;
+        let mut voters = targets as u64;
+        let mut votes = targets as u64;
+        let mut backers: BTreeMap<AccountId32, u32> = BTreeMap::new();
+        let mut nominators = storage.iter(query.nominators_iter()).await?;
+        while let Some(Ok((_, nominations))) = nominators.next().await {
+            voters += 1;
+            votes += nominations.targets.0.len() as u64;
+            for target in nominations.targets.0 {
+                *backers.entry(target).or_default() += 1;
+            }
+        }
+        let most_backers = backers.values().copied().max().unwrap_or_default();
+
+        let estimate = helpers::estimate_election(
+            &api,
+            &verifier_pallet,
+            voters,
+            votes,
+            targets,
+            desired_targets,
+            most_backers,
+        )?;
+        helpers::render(&configs, &estimate).await?;
+
+        Ok(configs)
+    }
+
+    /// Computes the duration of every phase of the rounds recorded in the index, as the blocks
+    /// until the next recorded phase, the next round's first one for the last phase of a round,
+    /// and reports them with their statistics across rounds for the last `rounds` rounds.
+    pub(crate) async fn election_timing(
+        rounds: Option<usize>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let configured = helpers::phase_durations(&api)?;
+        let recorded = Index::load(&configs.index)?.election_phases;
+        if recorded.is_empty() {
+            bail!("no election round recorded, run `monitor election` first");
+        }
+
+        let mut timings = vec![];
+        let mut rounds_iter = recorded.iter().peekable();
+        while let Some((round, records)) = rounds_iter.next() {
+            let next_start = rounds_iter
+                .peek()
+                .and_then(|(_, next)| next.first())
+                .map(|r| r.block);
+            let mut durations = BTreeMap::new();
+            for (i, record) in records.iter().enumerate() {
+                if record.phase == "ResultsProcessed" {
+                    continue;
+                }
+                let end = records.get(i + 1).map(|r| r.block).or(next_start);
+                if let Some(end) = end {
+                    durations.insert(record.phase.clone(), end.saturating_sub(record.block));
+                }
+            }
+            timings.push(output::RoundTiming {
+                round: *round,
+                durations,
+            });
+        }
+        if let Some(rounds) = rounds {
+            timings = timings.split_off(timings.len().saturating_sub(rounds));
+        }
+
+        let mut per_phase: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for timing in timings.iter() {
+            for (phase, blocks) in timing.durations.iter() {
+                per_phase.entry(phase.clone()).or_default().push(*blocks);
+            }
+        }
+        let phases = per_phase
+            .into_iter()
+            .map(|(phase, blocks)| {
+                let n = blocks.len() as f64;
+                let mean = blocks.iter().map(|b| *b as f64).sum::<f64>() / n;
+                let variance = blocks
+                    .iter()
+                    .map(|b| (*b as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / n;
+                output::PhaseStats {
+                    configured: configured.get(phase.as_str()).copied(),
+                    rounds: blocks.len(),
+                    mean,
+                    stddev: variance.sqrt(),
+                    min: blocks.iter().copied().min().unwrap_or_default(),
+                    max: blocks.iter().copied().max().unwrap_or_default(),
+                    phase,
+                }
+            })
+            .collect();
+
+        helpers::render(
+            &configs,
+            &output::ElectionTiming {
+                rounds: timings,
+                phases,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Mines a solution from the snapshot with `params`, records its score in the index under
+    /// the current round and reports it with the solutions mined before in the round.
+    pub(crate) async fn election_mine(
+        params: helpers::MiningParams,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use sp_npos_elections::EvaluateSupport;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let (targets, voters) = helpers::get_snapshot(&api, &storage).await?;
+        if targets.is_empty() || voters.is_empty() {
+            bail!("no election snapshot to mine from");
+        }
+        let round = storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .election_provider_multi_block()
+                    .round(),
+            )
+            .await?;
+        let desired_targets = helpers::desired_targets(&api, &storage, round).await?;
+
+        let n_voters = voters.len();
+        let mined = helpers::mine(desired_targets, targets, voters, &params)?;
+        let score = mined.supports.evaluate();
+        let record = index::MinedRecord {
+            params: params.to_string(),
+            edges: mined.edges,
+            score: index::ScoreRecord {
+                round,
+                minimal_stake: score.minimal_stake,
+                sum_stake: score.sum_stake,
+                sum_stake_squared: score.sum_stake_squared,
+            },
+        };
+
+        let mut index = Index::load(&configs.index)?;
+        let mined_in_round = index.mined_solutions.entry(round).or_default();
+        mined_in_round.push(record.clone());
+        let others = mined_in_round[..mined_in_round.len() - 1].to_vec();
+        index.save(&configs.index)?;
+
+        helpers::render(
+            &configs,
+            &output::MinedSolution {
+                round,
+                params: record.params,
+                voters: n_voters,
+                trimmed_voters: mined.trimmed_voters,
+                trimmed_backers: mined.trimmed_backers,
+                winners: mined.supports.len(),
+                edges: mined.edges,
+                reduced_edges: mined.reduced_edges,
+                // compact solution: a voter and a target index, and a target index and a PerU16
+                // share per extra edge.
+                size_bytes: mined.edges * 4,
+                score: score.into(),
+                others,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Checks whether creating `validators` validators and `nominators` nominators with
+    /// `nominations` nominations each, bonding from `bond_amount` up to `max_bond` spread evenly,
+    /// keeps the election within its snapshot bounds and block limits, and advises adjustments
+    /// otherwise. Nothing is submitted. The planned nominations are assumed to spread evenly
+    /// over the validators.
+    pub(crate) async fn election_plan(
+        validators: u32,
+        nominators: u32,
+        nominations: u32,
+        bond_amount: Balance,
+        max_bond: Option<Balance>,
+        verifier_pallet: String,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+
+        let targets_now = storage
+            .fetch_or_default(&query.counter_for_validators())
+            .await?;
+        let desired_targets = storage.fetch_or_default(&query.validator_count()).await?;
+        let minimum_validators = storage
+            .fetch_or_default(&query.minimum_validator_count())
+            .await?;
+        let (mut voters_now, mut votes_now) = (targets_now as u64, targets_now as u64);
+        let mut backers: BTreeMap<AccountId32, u32> = BTreeMap::new();
+        let mut existing = storage.iter(query.nominators_iter()).await?;
+        while let Some(Ok((_, nominations))) = existing.next().await {
+            voters_now += 1;
+            votes_now += nominations.targets.0.len() as u64;
+            for target in nominations.targets.0 {
+                *backers.entry(target).or_default() += 1;
+            }
+        }
+        let mut stakes = helpers::nominator_stakes(&api, &configs, &storage).await?;
+        let most_backers_now = backers.values().copied().max().unwrap_or_default();
+
+        let targets = targets_now + validators;
+        let voters = voters_now + validators as u64 + nominators as u64;
+        let planned_votes = nominators as u64 * nominations as u64;
+        let votes = votes_now + validators as u64 + planned_votes;
+        let most_backers = most_backers_now + planned_votes.div_ceil(targets.max(1) as u64) as u32;
+        let estimate = |votes| {
+            helpers::estimate_election(
+                &api,
+                &verifier_pallet,
+                voters,
+                votes,
+                targets,
+                desired_targets,
+                most_backers,
+            )
+        };
+        let planned = estimate(votes)?;
+
+        let mut advice = vec![];
+        // the snapshot keeps the voters with the most stake, the validators being assumed in.
+        let bonds: Vec<Balance> = (0..nominators as u128)
+            .map(|i| match max_bond {
+                Some(max) if nominators > 1 => {
+                    bond_amount + (max.saturating_sub(bond_amount)) * i / (nominators as u128 - 1)
+                }
+                _ => bond_amount,
+            })
+            .collect();
+        let room = planned.max_voters.saturating_sub(targets as u64) as usize;
+        let (mut trimmed, mut cutoff) = (0, None);
+        if stakes.len() + bonds.len() > room {
+            stakes.extend(bonds.iter().copied());
+            stakes.sort_unstable_by(|a, b| b.cmp(a));
+            let last_in = stakes
+                .get(room.saturating_sub(1))
+                .copied()
+                .unwrap_or_default();
+            trimmed = bonds.iter().filter(|bond| **bond < last_in).count();
+            cutoff = Some(last_in);
+            if trimmed > 0 {
+                advice.push(format!(
+                    "{trimmed} planned nominators would be left out of the snapshot: bond at least {last_in} each, or create at most {} nominators",
+                    room.saturating_sub(stakes.len() - bonds.len())
+                ));
+            }
+        }
+        if targets > planned.max_targets {
+            advice.push(format!(
+                "create at most {} validators to fit the target snapshot",
+                planned.max_targets.saturating_sub(targets_now)
+            ));
+        }
+        if desired_targets > targets {
+            advice.push(format!(
+                "create at least {} validators to fill the {desired_targets} desired targets",
+                desired_targets - targets_now
+            ));
+        }
+        if minimum_validators > targets {
+            advice.push(format!(
+                "create at least {} validators to reach the minimum validator count of {minimum_validators}",
+                minimum_validators - targets_now
+            ));
+        }
+        if planned.solution_page_bytes > planned.max_length as u64 {
+            let mut fitting = None;
+            for n in (1..nominations).rev() {
+                let votes = votes - planned_votes + nominators as u64 * n as u64;
+                if estimate(votes)?.solution_page_bytes <= planned.max_length as u64 {
+                    fitting = Some(n);
+                    break;
+                }
+            }
+            advice.push(match fitting {
+                Some(n) => format!("lower --nominations to {n} for a solution page to fit a block"),
+                None => "create fewer nominators for a solution page to fit a block".to_string(),
+            });
+        }
+        if let Some(max_backers) = planned.max_backers.filter(|max| most_backers > *max) {
+            let per_target = max_backers.saturating_sub(most_backers_now).max(1) as u64;
+            advice.push(format!(
+                "spread the nominations over at least {} validators to stay within {max_backers} backers per winner",
+                planned_votes.div_ceil(per_target)
+            ));
+        }
+
+        helpers::render(
+            &configs,
+            &output::ElectionPlan {
+                estimate: planned,
+                trimmed,
+                cutoff,
+                advice,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Lists the signed submissions of the current round and checks whether a solution mined by
+    /// the CLI from the snapshot would beat the best of them.
+    pub(crate) async fn election_submissions(
+        balancing: usize,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use sp_npos_elections::EvaluateSupport;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let signed = staking_parachain::storage().election_signed_pallet();
+
+        let round = storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .election_provider_multi_block()
+                    .round(),
+            )
+            .await?;
+        let deposit_per_page = api.constants().at(&staking_parachain::constants()
+            .election_signed_pallet()
+            .deposit_per_page())?;
+        let submissions = storage
+            .fetch(&signed.sorted_scores(round))
+            .await?
+            .map(|scores| scores.0)
+            .unwrap_or_default();
+
+        let mut result = output::ElectionSubmissions {
+            round,
+            submissions: vec![],
+            mined: None,
+            mined_beats_best: None,
+        };
+        for (who, score) in submissions.iter() {
+            let pages = storage
+                .fetch(&signed.submission_metadata_storage(round, who))
+                .await?
+                .map(|metadata| metadata.pages)
+                .unwrap_or_default();
+            result.submissions.push(output::Submission {
+                who: who.clone(),
+                pages,
+                deposit: deposit_per_page * pages as Balance,
+                score: score.into(),
+            });
+        }
+
+        let (targets, voters) = helpers::get_snapshot(&api, &storage).await?;
+        if !targets.is_empty() && !voters.is_empty() {
+            let desired_targets = helpers::desired_targets(&api, &storage, round).await?;
+            let mined =
+                helpers::mine_solution(desired_targets, targets, voters, balancing)?.evaluate();
+            result.mined = Some(mined.into());
+            result.mined_beats_best = submissions
+                .iter()
+                .map(|(_, score)| helpers::to_npos_score(score))
+                .max()
+                .map(|best| mined > best);
+        }
+        helpers::render(&configs, &result).await?;
+
+        Ok(configs)
+    }
+
+    /// Follows finalized blocks and checks, for every round, that a solution is queued by the end
+    /// of the unsigned phase. Alerts when a round is about to fall back to the emergency phase.
+    pub(crate) async fn monitor_unsigned(
+        alert_blocks: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let epm = staking_parachain::storage().election_provider_multi_block();
+        let unsigned_phase = api.constants().at(&staking_parachain::constants()
+            .election_provider_multi_block()
+            .unsigned_phase())?;
+
+        helpers::serve_status(&configs).await?;
+        println!("> Monitoring the unsigned phase of every election round..");
+
+        let mut was_unsigned = false;
+        let mut alerted_round = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let now = block.number();
+            let storage = block.storage();
+            helpers::notify_staking_events(&configs, &block.events().await?).await?;
+
+            let phase = storage.fetch_or_default(&epm.current_phase()).await?;
+            let round = storage.fetch_or_default(&epm.round()).await?;
+            let queued = storage
+                .fetch(
+                    &staking_parachain::storage()
+                        .election_verifier_pallet()
+                        .queued_solution_score(),
+                )
+                .await?
+                .is_some();
+
+            match phase {
+                Phase::Unsigned(started_at) => {
+                    let remaining = (started_at + unsigned_phase).saturating_sub(now);
+                    if !queued && remaining <= alert_blocks && alerted_round != Some(round) {
+                        alerted_round = Some(round);
+                        helpers::alert(&configs, &format!(
+                            "round {round}: no solution queued with {remaining} block(s) left in the unsigned phase"
+                        )).await;
+                    }
+                }
+                Phase::Emergency if alerted_round != Some(round) => {
+                    alerted_round = Some(round);
+                    helpers::alert(
+                        &configs,
+                        &format!("round {round}: election fell back to the emergency phase"),
+                    )
+                    .await;
+                }
+                _ => {}
+            }
+
+            let is_unsigned = matches!(phase, Phase::Unsigned(_));
+            if was_unsigned && !is_unsigned {
+                if queued {
+                    println!(" round {round}: solution queued by the end of the unsigned phase.");
+                } else if alerted_round != Some(round) {
+                    alerted_round = Some(round);
+                    helpers::alert(
+                        &configs,
+                        &format!("round {round}: unsigned phase ended without a queued solution"),
+                    )
+                    .await;
+                }
+            }
+            was_unsigned = is_unsigned;
+        }
+
+        Ok(configs)
+    }
+
+    /// Follows blocks and records, per round, the block at which each election phase started and
+    /// the one at which the last page of the result was processed by staking. Alerts when a phase
+    /// with a non-zero duration is skipped, when a phase lasts `slack` blocks longer than
+    /// configured, when the export starts without a queued solution (so the fallback is used), and
+    /// on emergency, halted or failed elections.
+    pub(crate) async fn monitor_election(
+        slack: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let epm = staking_parachain::storage().election_provider_multi_block();
+        let mut index = Index::load(&configs.index)?;
+        let durations = helpers::phase_durations(&api)?;
+        let order = [
+            "Off",
+            "Snapshot",
+            "Signed",
+            "SignedValidation",
+            "Unsigned",
+            "Export",
+        ];
+
+        helpers::serve_status(&configs).await?;
+        println!("> Monitoring the election phases of every round..");
+
+        // phase, block it started at and whether it was alerted as overlong.
+        let mut current: Option<(&str, u32, bool)> = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let now = block.number();
+            let storage = block.storage();
+            let phase = storage.fetch_or_default(&epm.current_phase()).await?;
+            let round = storage.fetch_or_default(&epm.round()).await?;
+            let name = helpers::phase_name(&phase);
+
+            let events = block.events().await?;
+            helpers::notify_staking_events(&configs, &events).await?;
+            match current.as_mut() {
+                Some((prev, _, _)) if *prev == name => {}
+                prev => {
+                    if let Some((prev, _, _)) = prev {
+                        let from = order.iter().position(|p| p == prev);
+                        let to = order.iter().position(|p| *p == name);
+                        if let (Some(from), Some(to)) = (from, to) {
+                            for skipped in order.iter().take(to).skip(from + 1) {
+                                if durations.get(skipped).is_some_and(|d| *d > 0) {
+                                    helpers::alert(&configs, &format!(
+                                        "round {round}: phase {skipped} skipped ({prev} -> {name} at #{now})"
+                                    )).await;
+                                }
+                            }
+                        }
+                    }
+                    println!(" round {round}: {name} at #{now}.");
+                    index
+                        .election_phases
+                        .entry(round)
+                        .or_default()
+                        .push(index::PhaseRecord {
+                            phase: name.to_string(),
+                            block: now,
+                        });
+                    index.save(&configs.index)?;
+
+                    match phase {
+                        Phase::Export(_) => {
+                            let queued = storage
+                                .fetch(
+                                    &staking_parachain::storage()
+                                        .election_verifier_pallet()
+                                        .queued_solution_score(),
+                                )
+                                .await?
+                                .is_some();
+                            if !queued {
+                                helpers::alert(&configs, &format!(
+                                    "round {round}: export started without a queued solution, falling back"
+                                )).await;
+                            }
+                        }
+                        Phase::Emergency | Phase::Halted => {
+                            helpers::alert(
+                                &configs,
+                                &format!(
+                                    "round {round}: election entered the {name} phase at #{now}"
+                                ),
+                            )
+                            .await
+                        }
+                        _ => {}
+                    }
+                    current = Some((name, now, false));
+                }
+            }
+
+            // the pages of the result are processed by staking during the export phase, the
+            // last one marks the end of the round. The round may have moved on by the block
+            // the last page is processed at.
+            let processed = events.iter().filter_map(|e| e.ok()).any(|e| {
+                e.pallet_name() == "Staking" && e.variant_name() == "PagedElectionProceeded"
+            });
+            let exported = |r: &u32| {
+                index
+                    .election_phases
+                    .get(r)
+                    .is_some_and(|records| records.iter().any(|r| r.phase == "Export"))
+            };
+            let exporting = if exported(&round) {
+                Some(round)
+            } else {
+                round.checked_sub(1).filter(exported)
+            };
+            if let (true, Some(exporting)) = (processed, exporting) {
+                let records = index.election_phases.entry(exporting).or_default();
+                match records.last_mut() {
+                    Some(record) if record.phase == "ResultsProcessed" => record.block = now,
+                    _ => records.push(index::PhaseRecord {
+                        phase: "ResultsProcessed".to_string(),
+                        block: now,
+                    }),
+                }
+                index.save(&configs.index)?;
+            }
+
+            if let Some((name, started_at, alerted)) = current.as_mut() {
+                let expected = durations.get(name).copied();
+                let lasted = now.saturating_sub(*started_at);
+                if !*alerted && expected.is_some_and(|e| lasted > e + slack) {
+                    *alerted = true;
+                    helpers::alert(
+                        &configs,
+                        &format!(
+                            "round {round}: phase {name} lasted {lasted} blocks, {} configured",
+                            expected.unwrap_or_default()
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        Ok(configs)
+    }
+
+    /// Shows the reward points of every validator that earned some in `era` (the active era by
+    /// default), only the CLI-created ones if `managed`.
+    pub(crate) async fn era_points(
+        era: Option<u32>,
+        managed: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+        let points = helpers::storage_at(&api, &configs)
+            .await?
+            .fetch(
+                &staking_parachain::storage()
+                    .staking()
+                    .eras_reward_points(era),
+            )
+            .await?;
+        let (total, individual) = points.map(|p| (p.total, p.individual)).unwrap_or_default();
+
+        let managed_validators: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .map(|a| a.account_id())
+            .collect();
+        let validators = individual
+            .into_iter()
+            .map(|(validator, points)| output::ValidatorPoints {
+                managed: managed_validators.contains(&validator),
+                validator,
+                points,
+            })
+            .filter(|v| !managed || v.managed)
+            .collect();
+
+        helpers::render(
+            &configs,
+            &output::EraPoints {
+                era,
+                total,
+                validators,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Submits `rebag` for every voter list node whose bag does not match its score, in batches
+    /// that fit in the extrinsic weight limit.
+    pub(crate) async fn rebag_all(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let signer = configs.signer()?;
+
+        let dislocated = helpers::dislocated_nodes(&api, &configs).await?;
+        if dislocated.is_empty() {
+            println!("All voter list nodes are in the right bag.");
+            return Ok(configs);
+        }
+        let rebag = |who: &AccountId32| {
+            RuntimeCall::VoterList(VoterListCall::rebag {
+                dislocated: Id(who.clone()),
+            })
+        };
+        let per_batch = helpers::calls_per_batch(&api, rebag(&dislocated[0].0)).await?;
+
+        println!(
+            "> Rebagging {} node(s) in batches of up to {per_batch}..",
+            dislocated.len()
+        );
+        let mut failed = 0;
+        for chunk in dislocated.chunks(per_batch) {
+            let calls = chunk.iter().map(|(who, _, _)| rebag(who)).collect();
+            let progress =
+                helpers::submit(&api, &configs, helpers::batch(&configs, calls), &signer).await?;
+            let events = helpers::wait_for_success(&configs, progress).await?;
+            failed += helpers::batch_failures(&events, chunk.len())?.len();
+        }
+
+        let left = helpers::dislocated_nodes(&api, &configs).await?.len();
+        println!(
+            "Rebagging done, {} node(s) moved, {failed} rebag(s) failed, {left} still dislocated.",
+            dislocated.len().saturating_sub(left)
+        );
+
+        Ok(configs)
+    }
+
+    /// Finds the first node ahead of `nominator` in its bag which has a lower score and submits
+    /// `put_in_front_of` with it, signed by the nominator, improving its position in the
+    /// snapshot.
+    pub(crate) async fn put_in_front_of(
+        nominator: AccountId32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let pair = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Nominator)
+            .find(|a| a.account_id() == nominator)
+            .map(|a| a.keypair())
+            .ok_or(eyre!("{nominator} is not a CLI-created nominator"))?;
+
+        let bag = helpers::bag_of(&api, &configs, &nominator).await?;
+        let position = bag
+            .iter()
+            .position(|(who, _)| who == &nominator)
+            .expect("the bag of a node contains it");
+        let score = bag[position].1;
+        println!(
+            "> {nominator} (score {score}) is at position {position}/{} of its bag.",
+            bag.len()
+        );
+
+        let Some((lighter, lighter_score)) =
+            bag[..position].iter().find(|(_, s)| *s < score).cloned()
+        else {
+            println!("No lighter node ahead of {nominator}, nothing to do.");
+            return Ok(configs);
+        };
+        println!(" putting it in front of {lighter} (score {lighter_score})..");
+        let call = RuntimeCall::VoterList(VoterListCall::put_in_front_of {
+            lighter: Id(lighter),
+        });
+        helpers::submit_all(&api, &configs, vec![(pair, call)]).await?;
+
+        let position = helpers::bag_of(&api, &configs, &nominator)
+            .await?
+            .iter()
+            .position(|(who, _)| who == &nominator);
+        println!("{nominator} is now at position {position:?} of its bag.");
+
+        Ok(configs)
+    }
+
+    /// Re-nominates random targets with a random `fraction` of the CLI-created nominators. With
+    /// `every`, keeps rotating each time `every` eras have passed since the last rotation.
+    pub(crate) async fn rotate_nominations(
+        fraction: f64,
+        nominations: usize,
+        every: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            bail!("fraction must be within (0, 1], got {fraction}");
+        }
+        if every == Some(0) {
+            bail!("--every must be at least 1 era");
+        }
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let Some(every) = every else {
+            helpers::rotate_nominations(&api, &configs, fraction, nominations).await?;
+            return Ok(configs);
+        };
+
+        helpers::serve_status(&configs).await?;
+        let mut last_rotation = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+            let era = helpers::active_era(&api, &configs).await?;
+            if last_rotation.is_some_and(|last| era < last + every) {
+                continue;
+            }
+            println!("> Era {era}:");
+            helpers::rotate_nominations(&api, &configs, fraction, nominations).await?;
+            last_rotation = Some(era);
+            configs.status.update(|s| s.last_era = Some(era));
+        }
+
+        bail!("block subscription ended")
+    }
+
+    /// Ramps the bonds of the bonded CLI-created stakers by `step` once per era, for `eras`
+    /// eras starting with the current one, up or, with `decrease`, down.
+    pub(crate) async fn ramp(
+        step: Balance,
+        decrease: bool,
+        eras: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if step == 0 {
+            bail!("--step must be positive");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        helpers::serve_status(&configs).await?;
+        let mut ramped = 0;
+        let mut last_era = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+            let era = helpers::active_era(&api, &configs).await?;
+            if last_era == Some(era) {
+                continue;
+            }
+            println!("> Era {era}, ramp {}/{eras}:", ramped + 1);
+            helpers::ramp_bonds(&api, &configs, step, decrease).await?;
+            last_era = Some(era);
+            configs.status.update(|s| s.last_era = Some(era));
+            ramped += 1;
+            if ramped == eras {
+                helpers::notify(
+                    &configs,
+                    "completed",
+                    &format!("bonds ramped over {eras} era(s)"),
+                )
+                .await;
+                return Ok(configs);
+            }
+        }
+
+        bail!("block subscription ended")
+    }
+
+    /// Sets `MinCommission` to `percent` with sudo and sweeps the registered validators for the
+    /// ones with a lower commission. With `force_apply`, their commission is raised to the new
+    /// minimum with `force_apply_min_commission`, signed by the signer.
+    pub(crate) async fn set_min_commission(
+        percent: f64,
+        force_apply: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if !(0.0..=100.0).contains(&percent) {
+            bail!("commission must be within [0, 100], got {percent}");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let min = (percent * 10_000_000.0).round() as u32;
+
+        println!("> Setting the minimum commission to {percent}% ({min} ppb)..");
+        helpers::submit_sudo(
+            &api,
+            &configs,
+            RuntimeCall::Staking(StakingCall::set_min_commission { new: Perbill(min) }),
+        )
+        .await?;
+
+        let mut below = vec![];
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut results = storage
+            .iter(staking_parachain::storage().staking().validators_iter())
+            .await?;
+        while let Some(Ok((key, prefs))) = results.next().await {
+            if prefs.commission.0 < min {
+                below.push(output::ValidatorCommission {
+                    validator: helpers::account_from_key(&key),
+                    commission: prefs.commission.0,
+                    blocked: prefs.blocked,
+                    below_min: true,
+                    full: false,
+                });
+            }
+        }
+        let sweep = output::MinCommissionSweep {
+            min_commission: min,
+            below,
+        };
+        helpers::render(&configs, &sweep).await?;
+
+        if force_apply && !sweep.below.is_empty() {
+            let calls: Vec<RuntimeCall> = sweep
+                .below
+                .into_iter()
+                .map(|v| {
+                    RuntimeCall::Staking(StakingCall::force_apply_min_commission {
+                        validator_stash: v.validator,
+                    })
+                })
+                .collect();
+            let n_calls = calls.len();
+            let progress = helpers::submit(
+                &api,
+                &configs,
+                helpers::batch(&configs, calls),
+                &configs.signer()?,
+            )
+            .await?;
+            let events = helpers::wait_for_success(&configs, progress).await?;
+            let failed = helpers::batch_failures(&events, n_calls)?;
+            println!(
+                "Minimum commission applied to {}/{n_calls} validator(s).",
+                n_calls - failed.len()
+            );
+        }
+
+        Ok(configs)
+    }
+
+    /// Sets `ForceEra` to `ForceAlways` with sudo until the active era advanced by `eras`, then
+    /// restores the previous forcing mode. In fork mode the blocks are produced on demand, so
+    /// the eras pass as fast as the fork builds blocks.
+    pub(crate) async fn fast_forward(eras: u32, configs: Configs) -> color_eyre::Result<Configs> {
+        use staking_parachain::runtime_types::frame_system::pallet::Call as SystemCall;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let force_era = staking_parachain::storage().staking().force_era();
+        let previous = helpers::storage_at(&api, &configs)
+            .await?
+            .fetch_or_default(&force_era)
+            .await?;
+        let from = helpers::active_era(&api, &configs).await?;
+        let target = from + eras;
+
+        helpers::serve_status(&configs).await?;
+        println!("> Fast-forwarding from era {from} to era {target}..");
+        helpers::submit_sudo(
+            &api,
+            &configs,
+            RuntimeCall::Staking(StakingCall::force_new_era_always),
+        )
+        .await?;
+
+        // the forcing mode is restored however following the eras ends.
+        let fast_forwarded = async {
+            let mut era = from;
+            let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+            while era < target {
+                if configs.fork_mode {
+                    helpers::new_block(&configs).await?;
+                }
+                match blocks.next().await {
+                    Some(block) => {
+                        helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+                    }
+                    None => bail!("block subscription ended at era {era}"),
+                }
+                let active = helpers::active_era(&api, &configs).await?;
+                if active != era {
+                    println!(" era {active}.");
+                    era = active;
+                    configs.status.update(|s| s.last_era = Some(era));
+                }
+            }
+            color_eyre::Result::<_>::Ok(era)
+        }
+        .await;
+
+        let restore = RuntimeCall::System(SystemCall::set_storage {
+            items: vec![(api.storage().address_bytes(&force_era)?, previous.encode())],
+        });
+        let restored = helpers::submit_sudo(&api, &configs, restore).await;
+        if let (Err(_), Err(e)) = (&fast_forwarded, &restored) {
+            eprintln!("! forcing mode not restored to {previous:?}: {e}");
+        }
+        let era = fast_forwarded?;
+        restored?;
+        println!("Fast-forwarded to era {era}, forcing mode restored to {previous:?}.");
+        helpers::notify(
+            &configs,
+            "completed",
+            &format!("fast-forwarded to era {era}"),
+        )
+        .await;
+
+        Ok(configs)
+    }
+
+    /// Chills and unbonds the whole active bond of every bonded CLI-created staker. With
+    /// `daemon`, waits until the current era reaches the last unlocking chunk, withdraws the
+    /// unbonded funds and reaps the accounts (see `reap`).
+    pub(crate) async fn full_exit(daemon: bool, configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let query = staking_parachain::storage().staking();
+        let accounts = State::load_for(&configs).await?.accounts;
+
+        let mut chill_calls = vec![];
+        let mut unbond_calls = vec![];
+        let mut bonded = vec![];
+        let storage = helpers::storage_at(&api, &configs).await?;
+        for account in accounts.iter() {
+            let status = helpers::staker_status(&api, &configs, &account.account_id()).await?;
+            if !status.bonded {
+                continue;
+            }
+            if status.validating || status.nominating {
+                chill_calls.push((
+                    account.controller_keypair(),
+                    RuntimeCall::Staking(StakingCall::chill),
+                ));
+            }
+            let active = storage
+                .fetch(&query.ledger(account.controller_id()))
+                .await?
+                .map(|l| l.active)
+                .unwrap_or_default();
+            if active > 0 {
+                unbond_calls.push((
+                    account.controller_keypair(),
+                    RuntimeCall::Staking(StakingCall::unbond { value: active }),
+                ));
+            }
+            bonded.push(account);
+        }
+
+        let n_chilled = chill_calls.len();
+        helpers::submit_all(&api, &configs, chill_calls).await?;
+        println!("Chilling done for {n_chilled} staker(s).");
+        let n_unbonded = unbond_calls.len();
+        helpers::submit_all(&api, &configs, unbond_calls).await?;
+        println!("Unbonding done for {n_unbonded} staker(s).");
+
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut unlock_era = 0;
+        for account in bonded.iter() {
+            if let Some(ledger) = storage
+                .fetch(&query.ledger(account.controller_id()))
+                .await?
+            {
+                let last = ledger.unlocking.0.iter().map(|c| c.era).max();
+                unlock_era = unlock_era.max(last.unwrap_or_default());
+            }
+        }
+        if !daemon {
+            println!("Funds can be withdrawn from era {unlock_era}, or rerun with --daemon.");
+            return Ok(configs);
+        }
+
+        helpers::serve_status(&configs).await?;
+        println!("> Waiting for era {unlock_era} to withdraw..");
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+            let current_era = helpers::storage_at(&api, &configs)
+                .await?
+                .fetch(&query.current_era())
+                .await?
+                .unwrap_or_default();
+            if current_era >= unlock_era {
+                break;
+            }
+        }
+
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut withdraw_calls = vec![];
+        for account in bonded.iter() {
+            let num_slashing_spans = storage
+                .fetch(&query.slashing_spans(account.account_id()))
+                .await?
+                .map(|spans| spans.prior.len() as u32 + 1)
+                .unwrap_or_default();
+            withdraw_calls.push((
+                account.controller_keypair(),
+                RuntimeCall::Staking(StakingCall::withdraw_unbonded { num_slashing_spans }),
+            ));
+        }
+        let n_withdrawn = withdraw_calls.len();
+        helpers::submit_all(&api, &configs, withdraw_calls).await?;
+        println!("Withdrawing done for {n_withdrawn} staker(s).");
+
+        let configs = reap(configs).await?;
+        helpers::notify(
+            &configs,
+            "completed",
+            &format!("full exit of {} staker(s) done", bonded.len()),
+        )
+        .await;
+
+        Ok(configs)
+    }
+
+    /// Adds the `add` tags to and removes the `remove` tags from the CLI-created accounts of
+    /// the chain in the scope of `--tag`, restricted to `role` and to the `first` ones.
+    pub(crate) async fn tag(
+        add: Vec<String>,
+        remove: Vec<String>,
+        role: Option<Role>,
+        first: Option<usize>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if add.is_empty() && remove.is_empty() {
+            bail!("nothing to do, pass tags to --add or --remove");
+        }
+        let mut state = State::load_for(&configs).await?;
+        let mut tagged = 0;
+        for account in state
+            .accounts
+            .iter_mut()
+            .filter(|a| role.map_or(true, |role| a.role == role))
+            .take(first.unwrap_or(usize::MAX))
+        {
+            account.tags.extend(add.iter().cloned());
+            account.tags.retain(|tag| !remove.contains(tag));
+            tagged += 1;
+        }
+        state.save(&configs.state)?;
+
+        let mut tags = std::collections::BTreeMap::new();
+        for tag in state.accounts.iter().flat_map(|a| a.tags.iter()) {
+            *tags.entry(tag.clone()).or_insert(0) += 1;
+        }
+        helpers::render(&configs, &output::Tags { tagged, tags }).await?;
+
+        Ok(configs)
+    }
+
+    /// Transfers all the funds of the unbonded CLI-created accounts (and of their controllers)
+    /// to the signer with `transfer_all(keep_alive = false)`. Accounts whose `System::Account`
+    /// entry is gone afterwards are removed from the state file, bonded ones are skipped.
+    pub(crate) async fn reap(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let dest: AccountId32 = configs.signer()?.public_key().into();
+        let mut state = State::load_for(&configs).await?;
+
+        let mut calls = vec![];
+        let mut candidates = vec![];
+        for account in state.accounts.iter() {
+            let stash = account.account_id();
+            if helpers::staker_status(&api, &configs, &stash).await?.bonded {
+                println!(" {stash} is still bonded, skipping it.");
+                continue;
+            }
+            let mut pairs = vec![account.keypair()];
+            if account.controller_seed.is_some() {
+                pairs.push(account.controller_keypair());
+            }
+            for pair in pairs {
+                calls.push((
+                    pair,
+                    RuntimeCall::Balances(BalancesCall::transfer_all {
+                        dest: Id(dest.clone()),
+                        keep_alive: false,
+                    }),
+                ));
+            }
+            candidates.push(account.clone());
+        }
+
+        println!("> Reaping {} account(s)..", candidates.len());
+        helpers::submit_all(&api, &configs, calls).await?;
+
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut reaped = vec![];
+        for account in candidates.iter() {
+            let mut ids = vec![account.account_id()];
+            if account.controller_seed.is_some() {
+                ids.push(account.controller_id());
+            }
+            let mut alive = vec![];
+            for id in ids {
+                if storage
+                    .fetch(&staking_parachain::storage().system().account(&id))
+                    .await?
+                    .is_some()
+                {
+                    alive.push(id);
+                }
+            }
+            match alive.is_empty() {
+                true => reaped.push(account.seed.clone()),
+                false => println!(" ! {alive:?} still exist after transferring all funds."),
+            }
+        }
+
+        state.accounts.retain(|a| !reaped.contains(&a.seed));
+        state.save(&configs.state)?;
+        println!(
+            "Reaping done for {}/{} account(s).",
+            reaped.len(),
+            candidates.len()
+        );
+
+        Ok(configs)
+    }
+
+    /// For every validator elected in `era`, reports how many nominators back it, how many
+    /// exposure pages that took, which pages were not claimed yet, and the nominators that
+    /// nominated it before the election but are in none of its pages.
+    pub(crate) async fn validators_oversubscription(
+        era: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+        let page_size = api.constants().at(&staking_parachain::constants()
+            .staking()
+            .max_exposure_page_size())?;
+
+        let mut nominations: BTreeMap<AccountId32, Vec<AccountId32>> = BTreeMap::new();
+        let mut results = storage.iter(query.nominators_iter()).await?;
+        while let Some(Ok((key, nominations_of))) = results.next().await {
+            if nominations_of.submitted_in >= era {
+                continue;
+            }
+            let nominator = helpers::account_from_key(&key);
+            for target in nominations_of.targets.0 {
+                nominations
+                    .entry(target)
+                    .or_default()
+                    .push(nominator.clone());
+            }
+        }
+
+        let mut validators = vec![];
+        for (validator, overview) in helpers::get_era_exposures(&storage, era).await? {
+            let pages =
+                helpers::exposure_pages(&storage, era, &validator, overview.page_count).await?;
+            let claimed = storage
+                .fetch(&query.claimed_rewards(era, &validator))
+                .await?
+                .unwrap_or_default();
+            let unclaimed_pages: Vec<u32> = (0..overview.page_count)
+                .filter(|p| !claimed.contains(p))
+                .collect();
+            let unexposed = nominations
+                .get(&validator)
+                .into_iter()
+                .flatten()
+                .filter(|n| !pages.iter().flatten().any(|(who, _)| &who == n))
+                .cloned()
+                .collect();
+            validators.push(output::ValidatorOversubscription {
+                validator,
+                nominators: overview.nominator_count,
+                page_count: overview.page_count,
+                unclaimed_pages,
+                unexposed,
+            });
+        }
+
+        helpers::render(
+            &configs,
+            &output::Oversubscription {
+                era,
+                page_size,
+                validators,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Attributes every block in `from..=to` to its Aura author and compares the blocks
+    /// authored by each session validator with the ones expected from a fair round-robin over
+    /// the session validators of each block.
+    pub(crate) async fn authorship(
+        from: u32,
+        to: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let to = match to {
+            Some(to) => to,
+            None => api.blocks().at_latest().await?.number(),
+        };
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .map(|a| a.account_id())
+            .collect();
+
+        // (authored, expected) blocks per validator.
+        let mut stats: BTreeMap<AccountId32, (u32, f64)> = BTreeMap::new();
+        let rpc = helpers::legacy_rpc(&configs).await?;
+        for number in from..=to {
+            let hash = helpers::block_hash(&rpc, number).await?;
+            let (author, validators) = helpers::block_author(&api, hash).await?;
+            for validator in validators.iter() {
+                stats.entry(validator.clone()).or_default().1 += 1.0 / validators.len() as f64;
+            }
+            if let Some(author) = author {
+                stats.entry(author).or_default().0 += 1;
+            }
+        }
+
+        let validators = stats
+            .into_iter()
+            .map(
+                |(validator, (authored, expected))| output::ValidatorAuthorship {
+                    managed: managed.contains(&validator),
+                    validator,
+                    authored,
+                    expected,
+                },
+            )
+            .collect();
+
+        helpers::render(
+            &configs,
+            &output::Authorship {
+                from,
+                to,
+                validators,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Dumps all the raw storage entries of `pallets` at block `at` to `out`, together with the
+    /// block and the runtime version and metadata hash they were read with.
+    pub(crate) async fn dump(
+        out: PathBuf,
+        at: Option<H256>,
+        pallets: Vec<String>,
+        scale: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let block = match at {
+            Some(hash) => api.blocks().at(hash).await?,
+            None => api.blocks().at_latest().await?,
+        };
+        let storage = api.storage().at(block.hash());
+        let pallets = match pallets.is_empty() {
+            true => dump::PALLETS.iter().map(|p| p.to_string()).collect(),
+            false => pallets,
+        };
+
+        let mut entries = vec![];
+        for pallet in pallets.iter() {
+            let pallet_entries = helpers::pallet_entries(&storage, pallet).await?;
+            println!(" {pallet}: {} entries.", pallet_entries.len());
+            entries.extend(pallet_entries);
+        }
+
+        let version = api.runtime_version();
+        let dump = dump::StorageDump {
+            version: dump::VERSION,
+            block_hash: block.hash(),
+            block_number: block.number(),
+            spec_version: version.spec_version,
+            transaction_version: version.transaction_version,
+            metadata_hash: api.metadata().hasher().hash(),
+            pallets,
+            entries,
+        };
+        dump.save(&out, scale)?;
+        println!(
+            "> {} entries of block #{} dumped to {}.",
+            dump.entries.len(),
+            dump.block_number,
+            out.display()
+        );
+
+        Ok(configs)
+    }
+
+    /// Writes all the entries of `dump` onto the chain with sudo `set_storage` calls of
+    /// `chunk_size` items, first removing the storage of the dumped pallets with `clear`. With
+    /// `genesis_out`, writes the entries as a raw genesis patch instead.
+    pub(crate) async fn restore(
+        dump: PathBuf,
+        clear: bool,
+        chunk_size: usize,
+        genesis_out: Option<PathBuf>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use staking_parachain::runtime_types::frame_system::pallet::Call as SystemCall;
+
+        let dump = dump::StorageDump::load(&dump)?;
+        println!(
+            "> Dump of block #{} ({:?}), spec version {}, {} entries.",
+            dump.block_number,
+            dump.block_hash,
+            dump.spec_version,
+            dump.entries.len()
+        );
+
+        if let Some(path) = genesis_out {
+            let top: serde_json::Map<String, serde_json::Value> = dump
+                .entries
+                .iter()
+                .map(|e| {
+                    (
+                        format!("0x{}", hex::encode(&e.key)),
+                        format!("0x{}", hex::encode(&e.value)).into(),
+                    )
+                })
+                .collect();
+            let patch = serde_json::json!({ "genesis": { "raw": { "top": top } } });
+            helpers::write_json(&path, &patch)?;
+            println!("Raw genesis patch written to {}.", path.display());
+            return Ok(configs);
+        }
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let spec_version = api.runtime_version().spec_version;
+        if spec_version != dump.spec_version {
+            println!(
+                " ! the chain runs spec version {spec_version}, the dump was taken with {}.",
+                dump.spec_version
+            );
+        }
+
+        if clear {
+            // keys removed by a `kill_prefix` call, which is repeated until none is left.
+            const CLEAR_LIMIT: u32 = 1_000;
+
+            for pallet in dump.pallets.iter() {
+                let prefix = sp_core::hashing::twox_128(pallet.as_bytes()).to_vec();
+                let mut left = helpers::count_keys(&api, &prefix).await?;
+                let mut calls = 0;
+                while left > 0 {
+                    let call = RuntimeCall::System(SystemCall::kill_prefix {
+                        prefix: prefix.clone(),
+                        subkeys: CLEAR_LIMIT,
+                    });
+                    let submitted = helpers::submit_sudo(&api, &configs, call).await?;
+                    calls += 1;
+                    let remaining = match submitted {
+                        Some(_) => helpers::count_keys(&api, &prefix).await?,
+                        // offline, the keys are expected to go by the limit.
+                        None => left.saturating_sub(CLEAR_LIMIT as usize),
+                    };
+                    if remaining >= left {
+                        bail!("{pallet} storage not cleared, {remaining} keys left");
+                    }
+                    left = remaining;
+                }
+                println!(" {pallet} storage cleared with {calls} call(s).");
+            }
+        }
+
+        let items = dump.entries.into_iter().map(|e| (e.key, e.value)).collect();
+        helpers::set_storage_chunked(&api, &configs, items, chunk_size).await?;
+
+        let violations =
+            helpers::staking_invariants(&helpers::storage_at(&api, &configs).await?).await?;
+        for violation in violations.iter() {
+            println!(" ! {violation}");
+        }
+        println!(
+            "Restore done, {} staking invariant(s) violated.",
+            violations.len()
+        );
+
+        Ok(configs)
+    }
+
+    /// Compares every entry of `dump` with the latest storage of the chain and lists the keys
+    /// missing on chain, the ones with a different value, and the keys of the dumped pallets
+    /// that are not in the dump. Fails on any divergence.
+    pub(crate) async fn verify_dump(
+        dump: PathBuf,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let dump = dump::StorageDump::load(&dump)?;
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+
+        let mut onchain: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+        for pallet in dump.pallets.iter() {
+            onchain.extend(
+                helpers::pallet_entries(&storage, pallet)
+                    .await?
+                    .into_iter()
+                    .map(|e| (e.key, e.value)),
+            );
+        }
+
+        let mut divergences = vec![];
+        let divergence = |key: &[u8], kind: &str| output::DumpDivergence {
+            key: format!("0x{}", hex::encode(key)),
+            kind: kind.to_string(),
+        };
+        for entry in dump.entries.iter() {
+            match onchain.remove(&entry.key) {
+                None => divergences.push(divergence(&entry.key, "missing")),
+                Some(value) if value != entry.value => {
+                    divergences.push(divergence(&entry.key, "changed"))
+                }
+                Some(_) => {}
+            }
+        }
+        divergences.extend(onchain.keys().map(|key| divergence(key, "extra")));
+
+        helpers::render(
+            &configs,
+            &output::DumpVerification {
+                block_number: dump.block_number,
+                entries: dump.entries.len(),
+                divergences: divergences.clone(),
+            },
+        )
+        .await?;
+        if !divergences.is_empty() {
+            bail!("{} divergence(s) from the dump", divergences.len());
+        }
+
+        Ok(configs)
+    }
+
+    /// Computes the concentration of the total backing of the validators elected in `era` and
+    /// of the active bond of the nominators.
+    pub(crate) async fn stats_concentration(
+        era: Option<u32>,
+        top_k: usize,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+
+        let backings: Vec<Balance> = helpers::get_era_exposures(&storage, era)
+            .await?
+            .into_iter()
+            .map(|(_, overview)| overview.total)
+            .collect();
+
+        let stakes = helpers::nominator_stakes(&api, &configs, &storage).await?;
+
+        helpers::render(
+            &configs,
+            &output::ConcentrationStats {
+                era,
+                validators_backing: helpers::concentration(backings, top_k),
+                nominators_stake: helpers::concentration(stakes, top_k),
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Reads the staking message flow with the relay chain: the last session report processed
+    /// and the one being received in pages, the validator set waiting to be sent and its
+    /// retries left, the offences queued for processing, and the number of messages waiting in
+    /// the upward (`ParachainSystem`) and downward (`MessageQueue`) queues. Everything is read
+    /// dynamically, the bundled runtime predates the asynchronous staking, and the entries the
+    /// runtime does not have are reported as such.
+    pub(crate) async fn staking_messages(
+        rc_client: String,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use subxt::{
+            dynamic::Value,
+            ext::scale_value::{At, ValueDef},
+        };
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let rc_client = rc_client.as_str();
+        let number = |value: Option<&Value<u32>>| value.and_then(|v| v.as_u128());
+        let len = |value: Option<&Value<u32>>| match value.map(|v| &v.value) {
+            Some(ValueDef::Composite(composite)) => {
+                // bounded vecs are wrapped in a composite of one vec.
+                match composite.values().next().map(|v| &v.value) {
+                    Some(ValueDef::Composite(inner)) if composite.len() == 1 => inner.len(),
+                    _ => composite.len(),
+                }
+            }
+            _ => 0,
+        };
+
+        let last_session_report = helpers::dynamic_entry(
+            &api,
+            &storage,
+            rc_client,
+            "LastSessionReportEndingIndex",
+            vec![],
+        )
+        .await?
+        .and_then(|index| index.as_u128());
+        let incomplete_session_report =
+            helpers::dynamic_entry(&api, &storage, rc_client, "IncompleteSessionReport", vec![])
+                .await?
+                .map(|report| output::IncompleteSessionReport {
+                    end_index: number(report.at("end_index")),
+                    validator_points: len(report.at("validator_points")),
+                });
+        let outgoing_validator_set =
+            helpers::dynamic_entry(&api, &storage, rc_client, "OutgoingValidatorSet", vec![])
+                .await?
+                .map(|outgoing| {
+                    // newer runtimes keep the report along with its retries left.
+                    let report = outgoing
+                        .at(0)
+                        .filter(|r| r.at("id").is_some())
+                        .unwrap_or(&outgoing);
+                    output::OutgoingValidatorSet {
+                        id: number(report.at("id")),
+                        validators: len(report.at("new_validator_set")),
+                        retries_left: number(outgoing.at(1)),
+                    }
+                });
+
+        let offence_queue_eras =
+            helpers::dynamic_entry(&api, &storage, "Staking", "OffenceQueueEras", vec![])
+                .await?
+                .map(|eras| len(Some(&eras)));
+        let processing_offence =
+            helpers::dynamic_entry(&api, &storage, "Staking", "ProcessingOffence", vec![])
+                .await?
+                .is_some();
+        let mut queued_offences = None;
+        if helpers::dynamic_entry_exists(&api, "Staking", "OffenceQueue") {
+            let mut count = 0;
+            let mut results = storage
+                .iter(subxt::dynamic::storage(
+                    "Staking",
+                    "OffenceQueue",
+                    Vec::<Value>::new(),
+                ))
+                .await?;
+            while let Some(Ok(_)) = results.next().await {
+                count += 1;
+            }
+            queued_offences = Some(count);
+        }
+
+        let upward_queue = helpers::dynamic_entry(
+            &api,
+            &storage,
+            "ParachainSystem",
+            "PendingUpwardMessages",
+            vec![],
+        )
+        .await?
+        .map(|messages| len(Some(&messages)));
+        let parent = Value::unnamed_variant("Parent", []);
+        let downward_queue =
+            helpers::dynamic_entry(&api, &storage, "MessageQueue", "BookStateFor", vec![parent])
+                .await?
+                .and_then(|book| number(book.at("message_count")));
+
+        helpers::render(
+            &configs,
+            &output::StakingMessages {
+                rc_client: rc_client.to_string(),
+                rc_client_found: api.metadata().pallet_by_name(rc_client).is_some(),
+                last_session_report,
+                incomplete_session_report,
+                outgoing_validator_set,
+                offence_queue_eras,
+                queued_offences,
+                processing_offence,
+                upward_queue,
+                downward_queue,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Scans the events of the blocks `from..=to` for the offences reported to staking
+    /// (`Staking::SlashReported`) and the slashes applied (`Staking::Slashed`), without the
+    /// offence kind and session (see `validators_disabled`).
+    pub(crate) async fn offences(
+        from: u32,
+        to: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use staking_parachain::staking::events::{SlashReported, Slashed};
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let to = match to {
+            Some(to) => to,
+            None => api.blocks().at_latest().await?.number(),
+        };
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts
+            .iter()
+            .map(|a| a.account_id())
+            .collect();
+
+        let mut reports = vec![];
+        let mut slashes = vec![];
+        let rpc = helpers::legacy_rpc(&configs).await?;
+        for number in from..=to {
+            let hash = helpers::block_hash(&rpc, number).await?;
+            let events = api.blocks().at(hash).await?.events().await?;
+            for report in events.find::<SlashReported>() {
+                let SlashReported {
+                    validator,
+                    fraction,
+                    slash_era,
+                } = report?;
+                reports.push(output::OffenceReport {
+                    block: number,
+                    managed: managed.contains(&validator),
+                    validator,
+                    fraction: fraction.0,
+                    slash_era,
+                });
+            }
+            for slash in events.find::<Slashed>() {
+                let Slashed { staker, amount } = slash?;
+                slashes.push(output::SlashEvent {
+                    block: number,
+                    managed: managed.contains(&staker),
+                    staker,
+                    amount,
+                });
+            }
+        }
+
+        helpers::render(
+            &configs,
+            &output::Offences {
+                from,
+                to,
+                reports,
+                slashes,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Reports the current session index and validators, the keys queued for the next
+    /// session, and the CLI-created validators which have set their session keys but are not
+    /// session validators (yet).
+    pub(crate) async fn session_info(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let session = staking_parachain::storage().session();
+
+        let index = storage.fetch_or_default(&session.current_index()).await?;
+        let validators = storage.fetch_or_default(&session.validators()).await?;
+        let disabled = storage
+            .fetch_or_default(&session.disabled_validators())
+            .await?;
+        let queued_changed = storage.fetch_or_default(&session.queued_changed()).await?;
+        let queued = storage
+            .fetch_or_default(&session.queued_keys())
+            .await?
+            .into_iter()
+            .map(|(who, keys)| (who, format!("0x{}", hex::encode(keys.encode()))))
+            .collect();
+
+        let mut pending = vec![];
+        for account in State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+        {
+            let who = account.account_id();
+            if validators.contains(&who) {
+                continue;
+            }
+            if storage.fetch(&session.next_keys(&who)).await?.is_some() {
+                pending.push(who);
+            }
+        }
+
+        helpers::render(
+            &configs,
+            &output::SessionInfo {
+                index,
+                validators,
+                disabled,
+                queued_changed,
+                queued,
+                pending,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Inserts the sr25519 key of every CLI-created validator, its session key, as `key_types`
+    /// into the keystore of one of `nodes`, round-robin, and checks it with `author_hasKey`.
+    /// With `set_keys`, the validators without next session keys register theirs.
+    pub(crate) async fn keys_insert(
+        nodes: Vec<String>,
+        key_types: Vec<String>,
+        set_keys: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use subxt::backend::rpc::{rpc_params, RpcClient};
+
+        let validators: Vec<_> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .cloned()
+            .collect();
+        let mut rpcs = vec![];
+        for node in nodes.iter() {
+            rpcs.push(RpcClient::from_insecure_url(node).await?);
+        }
+
+        println!(
+            "> Inserting the keys of {} validator(s) into {} node(s)..",
+            validators.len(),
+            nodes.len()
+        );
+        for (i, validator) in validators.iter().enumerate() {
+            let (node, rpc) = (&nodes[i % nodes.len()], &rpcs[i % nodes.len()]);
+            let public = format!("0x{}", hex::encode(validator.keypair().public_key().0));
+            for key_type in key_types.iter() {
+                let () = rpc
+                    .request(
+                        "author_insertKey",
+                        rpc_params![key_type, validator.secret_uri(), &public],
+                    )
+                    .await?;
+                let inserted: bool = rpc
+                    .request("author_hasKey", rpc_params![&public, key_type])
+                    .await?;
+                if !inserted {
+                    bail!("{node} does not have the {key_type} key {public} after inserting it");
+                }
+            }
+            println!(" {} -> {node}", validator.account_id());
+        }
+
+        if set_keys {
+            use staking_parachain::runtime_types::{
+                pallet_session::pallet::Call as SessionCall,
+                sp_consensus_aura::sr25519::app_sr25519, sp_core::sr25519,
+                staking_rococo_runtime::SessionKeys,
+            };
+
+            let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+            let storage = helpers::storage_at(&api, &configs).await?;
+            let mut calls = vec![];
+            for validator in validators.iter() {
+                let next_keys = staking_parachain::storage()
+                    .session()
+                    .next_keys(validator.account_id());
+                if storage.fetch(&next_keys).await?.is_some() {
+                    continue;
+                }
+                let aura = app_sr25519::Public(sr25519::Public(validator.keypair().public_key().0));
+                calls.push((
+                    validator.controller_keypair(),
+                    RuntimeCall::Session(SessionCall::set_keys {
+                        keys: SessionKeys { aura },
+                        proof: vec![],
+                    }),
+                ));
+            }
+            let n_set = calls.len();
+            helpers::submit_all(&api, &configs, calls).await?;
+            println!("Setting keys done for {n_set} validator(s).");
+        }
+
+        Ok(configs)
+    }
+
+    /// Reports the extrinsics pending in the transaction pool (`author_pendingExtrinsics`, the
+    /// ready ones) and their encoded size. The pool RPCs do not tell ready and future
+    /// transactions apart, so those counts are read from the node's Prometheus `metrics`.
+    pub(crate) async fn pool_status(
+        metrics: Option<String>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let pending = helpers::pending_extrinsics(&configs).await?;
+
+        let (mut ready, mut future) = (None, None);
+        if let Some(url) = metrics {
+            let body = reqwest::get(&url).await?.error_for_status()?.text().await?;
+            ready = helpers::prometheus_gauge(&body, "substrate_ready_transactions_number");
+            future = helpers::prometheus_gauge(&body, "substrate_future_transactions_number");
+        }
+
+        helpers::render(
+            &configs,
+            &output::PoolStatus {
+                pending: pending.len(),
+                pending_bytes: pending.iter().map(|xt| xt.len()).sum(),
+                ready,
+                future,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Decodes the extrinsics pending in the transaction pool and lists the signed ones with
+    /// staking, bags list, nomination pools or fast unstake calls (also within utility batches
+    /// and sudo).
+    pub(crate) async fn pool_pending(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let metadata = api.metadata();
+        let managed: std::collections::BTreeSet<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts
+            .iter()
+            .flat_map(|a| [a.account_id(), a.controller_id()])
+            .collect();
+
+        let pending = helpers::pending_extrinsics(&configs).await?;
+        let mut staking = vec![];
+        for extrinsic in pending.iter() {
+            let Some((signer, nonce, call)) =
+                helpers::decode_signed_extrinsic(&metadata, extrinsic)?
+            else {
+                continue;
+            };
+            let calls = helpers::staking_calls(&call);
+            if calls.is_empty() {
+                continue;
+            }
+            staking.push(output::PendingExtrinsic {
+                managed: managed.contains(&signer),
+                signer,
+                nonce,
+                call: helpers::call_name(&metadata, &call),
+                staking_calls: calls
+                    .into_iter()
+                    .map(|c| helpers::call_name(&metadata, c))
+                    .collect(),
+            });
+        }
+
+        helpers::render(
+            &configs,
+            &output::PendingExtrinsics {
+                total: pending.len(),
+                staking,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Creates the missing CLI-created pools, nominating random validators, and joins the
+    /// missing members to them round-robin. Then, for `eras` eras, a `churn` fraction of the
+    /// members acts every era: the ones out of their pool join it again, withdrawing their
+    /// unlocked funds first, the others either bond extra or unbond all of their points.
+    pub(crate) async fn pools_loadtest(
+        n_pools: usize,
+        n_members: usize,
+        bond_amount: Balance,
+        churn: f64,
+        eras: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if !(churn > 0.0 && churn <= 1.0) {
+            bail!("churn must be within (0, 1], got {churn}");
+        }
+        if n_pools == 0 {
+            bail!("--pools must be at least 1");
+        }
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let funder = configs.signer()?;
+        let mut state = State::load_for(&configs).await?;
+
+        let n_new_pools = n_pools.saturating_sub(state.pools.len());
+        if n_new_pools > 0 {
+            println!("> Generating, funding and creating {n_new_pools} pool(s)..");
+            let depositors = helpers::fund_accounts(
+                &api,
+                &configs,
+                &funder,
+                n_new_pools,
+                Some(bond_amount * 2),
+                Role::Nominator,
+            )
+            .await?;
+            let calls = depositors
+                .iter()
+                .map(|depositor| {
+                    let who: Target = depositor.account_id().into();
+                    (
+                        depositor.keypair(),
+                        RuntimeCall::NominationPools(PoolsCall::create {
+                            amount: bond_amount,
+                            root: who.clone(),
+                            nominator: who.clone(),
+                            bouncer: who,
+                        }),
+                    )
+                })
+                .collect();
+            helpers::submit_all(&api, &configs, calls).await?;
+
+            let validators = helpers::get_validators(&api, &configs).await?;
+            let mut nominate_calls = vec![];
+            for depositor in depositors {
+                let Some(member) =
+                    helpers::pool_member(&api, &configs, &depositor.account_id()).await?
+                else {
+                    println!(
+                        " pool of {} not created, skipping it.",
+                        depositor.account_id()
+                    );
+                    continue;
+                };
+                let validators = helpers::select_targets(16, validators.clone())
+                    .into_iter()
+                    .filter_map(|target| match target {
+                        Id(who) => Some(who),
+                        _ => None,
+                    })
+                    .collect();
+                nominate_calls.push((
+                    depositor.keypair(),
+                    RuntimeCall::NominationPools(PoolsCall::nominate {
+                        pool_id: member.pool_id,
+                        validators,
+                    }),
+                ));
+                state.pools.push(state::ManagedPool {
+                    id: member.pool_id,
+                    depositor: depositor.seed,
+                    members: vec![],
+                    genesis: None,
+                });
+            }
+            state.save(&configs.state)?;
+            let n_nominating = nominate_calls.len();
+            helpers::submit_all(&api, &configs, nominate_calls).await?;
+            println!("Pools created and nominating: {n_nominating}.");
+        }
+        if state.pools.is_empty() {
+            bail!("no pool could be created");
+        }
+
+        let n_new_members =
+            n_members.saturating_sub(state.pools.iter().map(|p| p.members.len()).sum());
+        if n_new_members > 0 {
+            println!("> Generating, funding and joining {n_new_members} member(s)..");
+            let created = helpers::fund_accounts(
+                &api,
+                &configs,
+                &funder,
+                n_new_members,
+                Some(bond_amount * 2),
+                Role::Nominator,
+            )
+            .await?;
+            let mut calls = vec![];
+            for (i, member) in created.into_iter().enumerate() {
+                let pool = &mut state.pools[i % n_pools.min(state.pools.len())];
+                calls.push((
+                    member.keypair(),
+                    RuntimeCall::NominationPools(PoolsCall::join {
+                        amount: bond_amount,
+                        pool_id: pool.id,
+                    }),
+                ));
+                pool.members.push(member.seed);
+            }
+            state.save(&configs.state)?;
+            let n_joined = calls.len();
+            helpers::submit_all(&api, &configs, calls).await?;
+            println!("Joining done for {n_joined} member(s).");
+        }
+
+        helpers::serve_status(&configs).await?;
+        let mut samples = vec![];
+        let mut last_era = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+            let era = helpers::active_era(&api, &configs).await?;
+            if last_era == Some(era) {
+                continue;
+            }
+            last_era = Some(era);
+            configs.status.update(|s| s.last_era = Some(era));
+
+            let sample = helpers::pools_sample(&api, &configs, era, &state.pools).await?;
+            println!("> Era {era}:");
+            for pool in sample.pools.iter() {
+                println!(
+                    " pool {}: {} member(s), {} points, {} bonded",
+                    pool.pool_id, pool.members, pool.points, pool.balance
+                );
+            }
+            samples.push(sample);
+            if samples.len() > eras as usize {
+                break;
+            }
+
+            let members: Vec<String> = state
+                .pools
+                .iter()
+                .flat_map(|p| p.members.iter().cloned())
+                .collect();
+            let n_acting = ((members.len() as f64 * churn).ceil() as usize).min(members.len());
+            let mut calls = vec![];
+            for seed in members.choose_multiple(&mut rand::thread_rng(), n_acting) {
+                let pair = helpers::signer_from_seed(seed);
+                let who: AccountId32 = pair.public_key().into();
+                let call = match helpers::pool_member(&api, &configs, &who).await? {
+                    None => {
+                        let Some(pool) = state.pools.iter().find(|p| p.members.contains(seed))
+                        else {
+                            continue;
+                        };
+                        PoolsCall::join {
+                            amount: bond_amount,
+                            pool_id: pool.id,
+                        }
+                    }
+                    Some(member) if member.points == 0 => {
+                        if !member
+                            .unbonding_eras
+                            .0
+                            .iter()
+                            .any(|(unlock, _)| *unlock <= era)
+                        {
+                            continue;
+                        }
+                        PoolsCall::withdraw_unbonded {
+                            member_account: who.into(),
+                            num_slashing_spans: 0,
+                        }
+                    }
+                    Some(member) => {
+                        if rand::thread_rng().gen_bool(0.5) {
+                            PoolsCall::bond_extra {
+                                extra: BondExtra::FreeBalance(bond_amount / 10),
+                            }
+                        } else {
+                            PoolsCall::unbond {
+                                member_account: who.into(),
+                                unbonding_points: member.points,
+                            }
+                        }
+                    }
+                };
+                calls.push((pair, RuntimeCall::NominationPools(call)));
+            }
+            let n_calls = calls.len();
+            helpers::submit_all(&api, &configs, calls).await?;
+            println!(" {n_calls} member operation(s) submitted.");
+        }
+
+        helpers::render(&configs, &output::PoolsLoadtest { eras: samples }).await?;
+
+        Ok(configs)
+    }
+
+    /// Claims the payouts of the members of the CLI-created pools and, with `commission`, the
+    /// commission of the pools with a commission payee. Every claim is checked against the
+    /// balance of the account credited: it must grow by the amount claimed, net of the fees it
+    /// paid.
+    pub(crate) async fn pools_claim(
+        commission: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let state = State::load_for(&configs).await?;
+        if state.pools.is_empty() {
+            bail!(
+                "no CLI-created pool in {}, create them with `pools loadtest`",
+                configs.state.display()
+            );
+        }
+        let storage = helpers::storage_at(&api, &configs).await?;
+
+        // (pool, claim, credited account, signer, call) of every claim.
+        let mut to_claim = vec![];
+        for pool in state.pools.iter() {
+            for seed in std::iter::once(&pool.depositor).chain(pool.members.iter()) {
+                let pair = helpers::signer_from_seed(seed);
+                let who: AccountId32 = pair.public_key().into();
+                if helpers::pool_member(&api, &configs, &who).await?.is_none() {
+                    continue;
+                }
+                let call = RuntimeCall::NominationPools(PoolsCall::claim_payout);
+                to_claim.push((pool.id, "payout", who, pair, call));
+            }
+            if !commission {
+                continue;
+            }
+            let payee = storage
+                .fetch(
+                    &staking_parachain::storage()
+                        .nomination_pools()
+                        .bonded_pools(pool.id),
+                )
+                .await?
+                .and_then(|bonded| bonded.commission.current)
+                .map(|(_, payee)| payee);
+            let Some(payee) = payee else {
+                println!(" pool {} has no commission, skipping its claim.", pool.id);
+                continue;
+            };
+            let call =
+                RuntimeCall::NominationPools(PoolsCall::claim_commission { pool_id: pool.id });
+            to_claim.push((
+                pool.id,
+                "commission",
+                payee,
+                helpers::signer_from_seed(&pool.depositor),
+                call,
+            ));
+        }
+
+        println!(
+            "> Claiming {} payout(s) and commission(s)..",
+            to_claim.len()
+        );
+        let mut claims = vec![];
+        // one claim at a time, a depositor can be credited both its payout and the commission.
+        for (pool_id, kind, who, pair, call) in to_claim {
+            let before = helpers::staker_status(&api, &configs, &who).await?.free;
+            let submission = helpers::submit(&api, &configs, call, &pair).await?;
+            let events = match helpers::wait_for_success(&configs, submission).await {
+                Ok(events) => events,
+                Err(e) => {
+                    println!(" {kind} claim of {who} in pool {pool_id} failed: {e}");
+                    continue;
+                }
+            };
+            let claimed = match kind {
+                "payout" => events.find_first::<PaidOut>()?.map(|e| e.payout),
+                _ => events
+                    .find_first::<PoolCommissionClaimed>()?
+                    .map(|e| e.commission),
+            }
+            .unwrap_or_default();
+            let fees = events
+                .find_first::<TransactionFeePaid>()?
+                .filter(|fee| fee.who == who)
+                .map_or(0, |fee| fee.actual_fee + fee.tip);
+            let after = helpers::staker_status(&api, &configs, &who).await?.free;
+            claims.push(output::PoolClaim {
+                pool_id,
+                kind: kind.to_string(),
+                who,
+                claimed,
+                fees,
+                before,
+                after,
+                verified: after + fees == before + claimed,
+            });
+        }
+
+        helpers::render(&configs, &output::PoolClaims { claims }).await?;
+
+        Ok(configs)
+    }
+
+    /// Walks the members of the CLI-created pools out of them, once per block: the bonded
+    /// members unbond all their points and the ones with unlocked funds withdraw them. With
+    /// `destroy`, the pools are set to destroying and their depositors, which can only leave a
+    /// destroying pool last, follow. Gives up after `max_eras` eras, reporting why every member
+    /// left is stuck. Destroyed pools are removed from the state file.
+    pub(crate) async fn pools_exit(
+        destroy: bool,
+        max_eras: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeSet;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let query = staking_parachain::storage();
+        let mut state = State::load_for(&configs).await?;
+        if state.pools.is_empty() {
+            bail!(
+                "no CLI-created pool in {}, create them with `pools loadtest`",
+                configs.state.display()
+            );
+        }
+
+        helpers::serve_status(&configs).await?;
+        // calls submitted in an era are not retried in the same era.
+        let mut attempted = BTreeSet::new();
+        let mut first_era = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        let stuck = loop {
+            let storage = helpers::storage_at(&api, &configs).await?;
+            let era = storage
+                .fetch(&query.staking().current_era())
+                .await?
+                .unwrap_or_default();
+            let first_era = *first_era.get_or_insert(era);
+
+            let mut calls = vec![];
+            let mut stuck = vec![];
+            for pool in state.pools.iter() {
+                let Some(bonded) = storage
+                    .fetch(&query.nomination_pools().bonded_pools(pool.id))
+                    .await?
+                else {
+                    continue;
+                };
+                let destroying = matches!(bonded.state, PoolState::Destroying);
+                if destroy
+                    && !destroying
+                    && attempted.insert((era, pool.depositor.clone(), "destroy"))
+                {
+                    calls.push((
+                        helpers::signer_from_seed(&pool.depositor),
+                        RuntimeCall::NominationPools(PoolsCall::set_state {
+                            pool_id: pool.id,
+                            state: PoolState::Destroying,
+                        }),
+                    ));
+                }
+                let num_slashing_spans = storage
+                    .fetch(
+                        &query
+                            .staking()
+                            .slashing_spans(helpers::pool_account(&api, pool.id)?),
+                    )
+                    .await?
+                    .map(|spans| spans.prior.len() as u32 + 1)
+                    .unwrap_or_default();
+
+                let depositor = destroy.then_some(&pool.depositor);
+                for seed in depositor.into_iter().chain(pool.members.iter()) {
+                    let pair = helpers::signer_from_seed(seed);
+                    let who: AccountId32 = pair.public_key().into();
+                    let Some(member) = storage
+                        .fetch(&query.nomination_pools().pool_members(&who))
+                        .await?
+                    else {
+                        continue;
+                    };
+                    let is_depositor = seed == &pool.depositor;
+                    let unlocked = member
+                        .unbonding_eras
+                        .0
+                        .iter()
+                        .any(|(unlock, _)| *unlock <= era);
+                    let last_unlock = member
+                        .unbonding_eras
+                        .0
+                        .iter()
+                        .map(|(unlock, _)| *unlock)
+                        .max();
+
+                    let reason = if member.points > 0
+                        && is_depositor
+                        && !(destroying && bonded.member_counter == 1)
+                    {
+                        format!(
+                            "depositor with {} points in a {:?} pool of {} member(s)",
+                            member.points, bonded.state, bonded.member_counter
+                        )
+                    } else if member.points > 0 {
+                        if attempted.insert((era, seed.clone(), "unbond")) {
+                            calls.push((
+                                pair,
+                                RuntimeCall::NominationPools(PoolsCall::unbond {
+                                    member_account: who.clone().into(),
+                                    unbonding_points: member.points,
+                                }),
+                            ));
+                        }
+                        format!("{} points not unbonded", member.points)
+                    } else if unlocked {
+                        if attempted.insert((era, seed.clone(), "withdraw")) {
+                            calls.push((
+                                pair,
+                                RuntimeCall::NominationPools(PoolsCall::withdraw_unbonded {
+                                    member_account: who.clone().into(),
+                                    num_slashing_spans,
+                                }),
+                            ));
+                        }
+                        "unlocked funds not withdrawn".to_string()
+                    } else {
+                        format!("unbonding until era {}", last_unlock.unwrap_or_default())
+                    };
+                    stuck.push(output::StuckMember {
+                        pool_id: pool.id,
+                        who,
+                        reason,
+                    });
+                }
+            }
+
+            if stuck.is_empty() || era >= first_era + max_eras {
+                break stuck;
+            }
+            if !calls.is_empty() {
+                println!(
+                    "> Era {era}: {} member(s) in the pools, submitting {} call(s)..",
+                    stuck.len(),
+                    calls.len()
+                );
+                helpers::submit_all(&api, &configs, calls).await?;
+            }
+
+            if configs.fork_mode {
+                helpers::new_block(&configs).await?;
+            }
+            match blocks.next().await {
+                Some(block) => {
+                    helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+                }
+                None => bail!("block subscription ended at era {era}"),
+            }
+            configs.status.update(|s| s.last_era = Some(era));
+        };
+
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut destroyed = vec![];
+        for pool in state.pools.iter() {
+            if storage
+                .fetch(&query.nomination_pools().bonded_pools(pool.id))
+                .await?
+                .is_none()
+            {
+                destroyed.push(pool.id);
+            }
+        }
+        state.pools.retain(|pool| !destroyed.contains(&pool.id));
+        state.save(&configs.state)?;
+
+        helpers::render(&configs, &output::PoolsExit { destroyed, stuck }).await?;
+
+        Ok(configs)
+    }
+
+    /// Updates the commission of `pool`, or of all the CLI-created pools: first its maximum,
+    /// then its change rate, as `(max increase, min delay)`, and last its rate, paid to `payee`
+    /// or to the root.
+    pub(crate) async fn pools_commission(
+        pool: Option<u32>,
+        rate: Option<ConfigChange<f64>>,
+        payee: Option<AccountId32>,
+        max: Option<f64>,
+        change_rate: Option<(f64, u32)>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if rate.is_none() && max.is_none() && change_rate.is_none() {
+            bail!("nothing to set, give --rate, --max or --max-increase and --min-delay");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        for (pool_id, root) in helpers::pool_roots(&api, &configs, pool).await? {
+            let mut calls = vec![];
+            if let Some(max) = max {
+                calls.push(PoolsCall::set_commission_max {
+                    pool_id,
+                    max_commission: helpers::perbill(max)?,
+                });
+            }
+            if let Some((max_increase, min_delay)) = change_rate {
+                calls.push(PoolsCall::set_commission_change_rate {
+                    pool_id,
+                    change_rate: CommissionChangeRate {
+                        max_increase: helpers::perbill(max_increase)?,
+                        min_delay,
+                    },
+                });
+            }
+            match rate.clone() {
+                Some(ConfigChange::Set(rate)) => {
+                    let payee = payee.clone().unwrap_or_else(|| root.public_key().into());
+                    calls.push(PoolsCall::set_commission {
+                        pool_id,
+                        new_commission: Some((helpers::perbill(rate)?, payee)),
+                    });
+                }
+                Some(ConfigChange::Remove) => calls.push(PoolsCall::set_commission {
+                    pool_id,
+                    new_commission: None,
+                }),
+                None => {}
+            }
+            helpers::submit_pool_admin(&api, &configs, pool_id, &root, calls).await?;
+        }
+
+        pools_info(&api, pool, configs).await
+    }
+
+    /// Sets the state of `pool`, or of all the CLI-created pools.
+    pub(crate) async fn pools_state(
+        pool: Option<u32>,
+        state: PoolState,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        for (pool_id, root) in helpers::pool_roots(&api, &configs, pool).await? {
+            let call = PoolsCall::set_state {
+                pool_id,
+                state: state.clone(),
+            };
+            helpers::submit_pool_admin(&api, &configs, pool_id, &root, vec![call]).await?;
+        }
+
+        pools_info(&api, pool, configs).await
+    }
+
+    /// Updates the `[root, nominator, bouncer]` roles of `pool`, or of all the CLI-created
+    /// pools.
+    pub(crate) async fn pools_roles(
+        pool: Option<u32>,
+        roles: [Option<ConfigChange<AccountId32>>; 3],
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if roles.iter().all(Option::is_none) {
+            bail!("nothing to update, give --root, --nominator or --bouncer");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let [new_root, new_nominator, new_bouncer] = roles.map(|role| match role {
+            Some(ConfigChange::Set(who)) => ConfigOp::Set(who),
+            Some(ConfigChange::Remove) => ConfigOp::Remove,
+            None => ConfigOp::Noop,
+        });
+        for (pool_id, root) in helpers::pool_roots(&api, &configs, pool).await? {
+            let call = PoolsCall::update_roles {
+                pool_id,
+                new_root: new_root.clone(),
+                new_nominator: new_nominator.clone(),
+                new_bouncer: new_bouncer.clone(),
+            };
+            helpers::submit_pool_admin(&api, &configs, pool_id, &root, vec![call]).await?;
+        }
+
+        pools_info(&api, pool, configs).await
+    }
+
+    /// Shows the state, commission and roles of `pool`, or of all the CLI-created pools.
+    async fn pools_info(
+        api: &OnlineClient<SubstrateConfig>,
+        pool: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let ids = match pool {
+            Some(pool) => vec![pool],
+            None => State::load_for(&configs)
+                .await?
+                .pools
+                .iter()
+                .map(|p| p.id)
+                .collect(),
+        };
+        let storage = helpers::storage_at(api, &configs).await?;
+        let mut pools = vec![];
+        for pool_id in ids {
+            let Some(bonded) = storage
+                .fetch(
+                    &staking_parachain::storage()
+                        .nomination_pools()
+                        .bonded_pools(pool_id),
+                )
+                .await?
+            else {
+                continue;
+            };
+            let percent = |p: Perbill| p.0 as f64 / 10_000_000.0;
+            let commission = bonded.commission;
+            pools.push(output::PoolInfo {
+                pool_id,
+                state: format!("{:?}", bonded.state),
+                commission: commission.current.as_ref().map(|(p, _)| percent(p.clone())),
+                commission_payee: commission.current.map(|(_, payee)| payee),
+                max_commission: commission.max.map(percent),
+                change_rate: commission
+                    .change_rate
+                    .map(|rate| (percent(rate.max_increase), rate.min_delay)),
+                root: bonded.roles.root,
+                nominator: bonded.roles.nominator,
+                bouncer: bonded.roles.bouncer,
+            });
+        }
+
+        helpers::render(&configs, &output::PoolsInfo { pools }).await?;
+
+        Ok(configs)
+    }
+
+    /// Lists the agents of `DelegatedStaking`, correlated with their staking ledger and the
+    /// pools they are the bonded account of.
+    pub(crate) async fn delegated_agents(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let agents = helpers::agents(&api, &storage).await?;
+        let delegations = helpers::delegations(&api, &storage).await?;
+        let pools = helpers::pool_accounts(&api, &storage).await?;
+
+        let mut result = vec![];
+        for (agent, ledger) in agents {
+            let active = storage
+                .fetch(&staking_parachain::storage().staking().ledger(&agent))
+                .await?
+                .map(|ledger| ledger.active)
+                .unwrap_or_default();
+            result.push(output::Agent {
+                pool: pools.get(&agent).copied(),
+                delegators: delegations.iter().filter(|(_, d)| d.agent == agent).count(),
+                agent,
+                payee: ledger.payee,
+                total_delegated: ledger.total_delegated,
+                unclaimed_withdrawals: ledger.unclaimed_withdrawals,
+                pending_slash: ledger.pending_slash,
+                active,
+            });
+        }
+
+        helpers::render(&configs, &output::Agents { agents: result }).await?;
+
+        Ok(configs)
+    }
+
+    /// Lists the delegators of `DelegatedStaking`, or of `agent` only, with the balance held
+    /// for their delegation and their pool membership. A delegator is flagged when the held
+    /// balance differs from its delegation, or when the agent is a pool it is not a member of.
+    pub(crate) async fn delegated_delegators(
+        agent: Option<AccountId32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let pools = helpers::pool_accounts(&api, &storage).await?;
+
+        let mut delegators = vec![];
+        for (who, delegation) in helpers::delegations(&api, &storage).await? {
+            if agent
+                .as_ref()
+                .is_some_and(|agent| *agent != delegation.agent)
+            {
+                continue;
+            }
+            let held = helpers::held(&storage, &who, "DelegatedStaking").await?;
+            let member = storage
+                .fetch(
+                    &staking_parachain::storage()
+                        .nomination_pools()
+                        .pool_members(&who),
+                )
+                .await?;
+
+            let mut issues = vec![];
+            if held != delegation.amount {
+                issues.push(format!("{held} held for {} delegated", delegation.amount));
+            }
+            let agent_pool = pools.get(&delegation.agent).copied();
+            match (&member, agent_pool) {
+                (Some(member), Some(pool)) if member.pool_id != pool => issues.push(format!(
+                    "member of pool {} but delegating to pool {pool}",
+                    member.pool_id
+                )),
+                (None, Some(pool)) => {
+                    issues.push(format!("delegating to pool {pool} without being a member"))
+                }
+                _ => {}
+            }
+            delegators.push(output::Delegator {
+                who,
+                agent: delegation.agent,
+                amount: delegation.amount,
+                held,
+                pool: member.as_ref().map(|m| m.pool_id),
+                points: member.map(|m| m.points),
+                issues,
+            });
+        }
+
+        helpers::render(&configs, &output::Delegators { delegators }).await?;
+
+        Ok(configs)
+    }
+
+    /// Lists the exposure metadata of every validator elected in `era`. Eras exposed before
+    /// paged exposures only have a legacy `ErasStakers` entry, counted as a single page.
+    pub(crate) async fn exposure_pages(
+        era: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+
+        let mut validators: Vec<output::ExposureOverview> =
+            helpers::get_era_exposures(&storage, era)
+                .await?
+                .into_iter()
+                .map(|(validator, overview)| output::ExposureOverview {
+                    validator,
+                    total: overview.total,
+                    own: overview.own,
+                    nominators: overview.nominator_count,
+                    page_count: overview.page_count,
+                    legacy: false,
+                })
+                .collect();
+        let query = staking_parachain::storage()
+            .staking()
+            .eras_stakers_iter1(era);
+        let mut legacy = storage.iter(query).await?;
+        while let Some(Ok((key, exposure))) = legacy.next().await {
+            let validator = helpers::account_from_key(&key);
+            if validators.iter().any(|v| v.validator == validator) {
+                continue;
+            }
+            validators.push(output::ExposureOverview {
+                validator,
+                total: exposure.total,
+                own: exposure.own,
+                nominators: exposure.others.len() as u32,
+                page_count: 1,
+                legacy: true,
+            });
+        }
+
+        helpers::render(&configs, &output::ExposurePages { era, validators }).await?;
+
+        Ok(configs)
+    }
+
+    /// Dumps page `page` of the exposure of `validator` in `era`.
+    pub(crate) async fn exposure_page(
+        validator: AccountId32,
+        page: u32,
+        era: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+
+        let others = helpers::exposure_page(&storage, era, &validator, page)
+            .await?
+            .ok_or(eyre!(
+                "{validator} has no exposure page {page} in era {era}"
+            ))?
+            .into_iter()
+            .map(|(who, value)| output::Backing { who, value })
+            .collect();
+
+        helpers::render(
+            &configs,
+            &output::ExposurePage {
+                era,
+                validator,
+                page,
+                others,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Reports the active self-stake of every registered validator and, for the ones elected in
+    /// `era`, the backing nominated to them, highlighting the validators whose self-stake is
+    /// below `low_percent` of their total backing (or of the median self-stake when not elected).
+    pub(crate) async fn validators_self_stake(
+        era: Option<u32>,
+        low_percent: f64,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+        let exposures = helpers::get_era_exposures(&storage, era).await?;
+
+        let mut validators = vec![];
+        for target in helpers::get_validators(&api, &configs).await? {
+            let Id(validator) = target else { continue };
+            let own = match storage.fetch(&query.bonded(&validator)).await? {
+                Some(controller) => storage
+                    .fetch(&query.ledger(&controller))
+                    .await?
+                    .map(|l| l.active)
+                    .unwrap_or_default(),
+                None => 0,
+            };
+            let nominated = exposures
+                .iter()
+                .find(|(v, _)| v == &validator)
+                .map(|(_, overview)| overview.total.saturating_sub(overview.own));
+            validators.push(output::ValidatorSelfStake {
+                validator,
+                own,
+                nominated,
+                low: false,
+            });
+        }
+
+        let mut owns: Vec<Balance> = validators.iter().map(|v| v.own).collect();
+        owns.sort_unstable();
+        let median = owns.get(owns.len() / 2).copied().unwrap_or_default();
+        let share = low_percent / 100.0;
+        for v in validators.iter_mut() {
+            let reference = match v.nominated {
+                Some(nominated) => v.own + nominated,
+                None => median,
+            };
+            v.low = (v.own as f64) < reference as f64 * share;
+        }
+
+        helpers::render(&configs, &output::SelfStakes { era, validators }).await?;
+
+        Ok(configs)
+    }
+
+    /// Buckets the registered validators by commission, in `bucket` percent wide buckets, and
+    /// flags the ones with a 100% commission or below `MinCommission`.
+    pub(crate) async fn validators_commissions(
+        bucket: u32,
+        csv: Option<PathBuf>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if !(1..=100).contains(&bucket) {
+            bail!("bucket must be within [1, 100], got {bucket}");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+        let min_commission = storage.fetch_or_default(&query.min_commission()).await?.0;
+
+        let mut validators = vec![];
+        let mut results = storage.iter(query.validators_iter()).await?;
+        while let Some(Ok((key, prefs))) = results.next().await {
+            validators.push(output::ValidatorCommission {
+                validator: helpers::account_from_key(&key),
+                commission: prefs.commission.0,
+                blocked: prefs.blocked,
+                below_min: prefs.commission.0 < min_commission,
+                full: prefs.commission.0 >= 1_000_000_000,
+            });
+        }
+
+        // commissions in ppb, a `bucket` percent is `bucket * 10^7` ppb.
+        let width = bucket * 10_000_000;
+        let mut buckets: Vec<output::CommissionBucket> = (0..100 / bucket + 1)
+            .map(|i| output::CommissionBucket {
+                from_percent: i * bucket,
+                validators: 0,
+            })
+            .filter(|b| b.from_percent <= 100)
+            .collect();
+        for v in validators.iter() {
+            let i = ((v.commission / width) as usize).min(buckets.len() - 1);
+            buckets[i].validators += 1;
+        }
+
+        if let Some(path) = csv {
+            let mut lines = vec!["validator,commission_ppb,blocked,below_min,full".to_string()];
+            lines.extend(validators.iter().map(|v| {
+                format!(
+                    "{},{},{},{},{}",
+                    v.validator, v.commission, v.blocked, v.below_min, v.full
+                )
+            }));
+            std::fs::write(&path, lines.join("\n") + "\n")?;
+            eprintln!("> Commissions exported to {}.", path.display());
+        }
+
+        helpers::render(
+            &configs,
+            &output::Commissions {
+                min_commission,
+                buckets,
+                validators,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Reports the session validators disabled by the session pallet or marked as offending by
+    /// staking, together with their slashes. The applied (`ValidatorSlashInEra`) and deferred
+    /// (`UnappliedSlashes`) slashes of the bonded eras are recorded in the index, and the ones
+    /// recorded by earlier runs are reported too. The runtime has no offences pallet, so the
+    /// slash records are the only trace of the offences.
+    pub(crate) async fn validators_disabled(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage();
+
+        let session_validators = storage
+            .fetch_or_default(&query.session().validators())
+            .await?;
+        let disabled = storage
+            .fetch_or_default(&query.session().disabled_validators())
+            .await?;
+        let offending = storage
+            .fetch_or_default(&query.staking().offending_validators())
+            .await?;
+        let eras: Vec<u32> = storage
+            .fetch_or_default(&query.staking().bonded_eras())
+            .await?
+            .into_iter()
+            .map(|(era, _)| era)
+            .collect();
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .map(|a| a.account_id())
+            .collect();
+        let mut index = Index::load(&configs.index)?;
+
+        let mut unapplied = vec![];
+        for era in eras.iter() {
+            for slash in storage
+                .fetch_or_default(&query.staking().unapplied_slashes(era))
+                .await?
+            {
+                unapplied.push((*era, slash));
+            }
+        }
+
+        let mut validators = vec![];
+        for (position, validator) in session_validators.iter().enumerate() {
+            let position = position as u32;
+            let is_disabled = disabled.contains(&position);
+            let is_offending = offending.iter().any(|(i, _)| *i == position);
+            if !is_disabled && !is_offending {
+                continue;
+            }
+            let recorded = index.slashes.entry(validator.clone()).or_default();
+            for (era, s) in unapplied.iter().filter(|(_, s)| &s.validator == validator) {
+                let slash = index::SlashRecord {
+                    fraction: 0,
+                    amount: s.own,
+                    applied: false,
+                };
+                recorded.insert(*era, slash);
+            }
+            // recorded after the deferred ones, an applied slash supersedes them.
+            for era in eras.iter() {
+                if let Some((fraction, amount)) = storage
+                    .fetch(&query.staking().validator_slash_in_era(era, validator))
+                    .await?
+                {
+                    let slash = index::SlashRecord {
+                        fraction: fraction.0,
+                        amount,
+                        applied: true,
+                    };
+                    recorded.insert(*era, slash);
+                }
+            }
+            validators.push(output::DisabledValidator {
+                validator: validator.clone(),
+                index: Some(position),
+                disabled: is_disabled,
+                offending: is_offending,
+                managed: managed.contains(validator),
+                slashes: output::Slash::from_records(recorded),
+            });
+        }
+        // validators slashed by earlier runs that are no longer disabled nor offending.
+        for (validator, recorded) in index.slashes.iter() {
+            if recorded.is_empty() || validators.iter().any(|v| &v.validator == validator) {
+                continue;
+            }
+            validators.push(output::DisabledValidator {
+                validator: validator.clone(),
+                index: session_validators
+                    .iter()
+                    .position(|v| v == validator)
+                    .map(|p| p as u32),
+                disabled: false,
+                offending: false,
+                managed: managed.contains(validator),
+                slashes: output::Slash::from_records(recorded),
+            });
+        }
+
+        index.save(&configs.index)?;
+
+        helpers::render(&configs, &output::DisabledValidators { validators }).await?;
+
+        Ok(configs)
+    }
+
+    /// Reports the distribution of the active bond of all the nominators: `buckets` equally wide
+    /// buckets between the lowest and highest bond, and the bond percentiles.
+    pub(crate) async fn nominators_distribution(
+        buckets: usize,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if buckets == 0 {
+            bail!("at least one bucket is needed");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+
+        let stakes = helpers::nominator_stakes(&api, &configs, &storage).await?;
+        let Some(distribution) = helpers::bond_distribution(stakes, buckets) else {
+            bail!("no nominators found");
+        };
+
+        helpers::render(&configs, &distribution).await?;
+
+        Ok(configs)
+    }
+
+    /// Lists the nodes of the bag with upper threshold (or index) `bag` and their scores,
+    /// flagging the CLI-created ones.
+    pub(crate) async fn bag_members(bag: u64, configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let thresholds = api
+            .constants()
+            .at(&staking_parachain::constants().voter_list().bag_thresholds())?;
+        let bag_upper = thresholds.get(bag as usize).copied().unwrap_or(bag);
+
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts
+            .iter()
+            .map(|a| a.account_id())
+            .collect();
+        let members = helpers::bag_members(&api, &configs, bag_upper)
+            .await?
+            .into_iter()
+            .map(|(who, score)| output::BagMember {
+                managed: managed.contains(&who),
+                who,
+                score,
+            })
+            .collect();
+
+        helpers::render(&configs, &output::BagMembers { bag_upper, members }).await?;
+
+        Ok(configs)
+    }
+
+    /// Checks every ledger for a missing or mismatching bonded entry, a stash bonded twice and a
+    /// staking lock different from the ledger total, virtual stakers aside. The runtime has no
+    /// `restore_ledger`, so the suggested repairs are `system.set_storage` and
+    /// `staking.force_unstake` calls, to be submitted with `submit-raw --sudo`.
+    pub(crate) async fn check_ledgers(configs: Configs) -> color_eyre::Result<Configs> {
+        use staking_parachain::runtime_types::{
+            bounded_collections::weak_bounded_vec::WeakBoundedVec,
+            frame_system::pallet::Call as SystemCall,
+            pallet_balances::types::{BalanceLock, Reasons},
+        };
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage();
+        let set_storage = |key: Vec<u8>, value: Vec<u8>| {
+            RuntimeCall::System(SystemCall::set_storage {
+                items: vec![(key, value)],
+            })
+        };
+
+        let virtual_stakers = helpers::virtual_stakers(&api, &storage).await?;
+        let mut ledgers = vec![];
+        let mut results = storage.iter(query.staking().ledger_iter()).await?;
+        while let Some(Ok((key, ledger))) = results.next().await {
+            ledgers.push((helpers::account_from_key(&key), ledger));
+        }
+
+        let mut issues = vec![];
+        for (controller, ledger) in ledgers.iter() {
+            let stash = &ledger.stash;
+            let mut issue = |issue: String, repair: RuntimeCall| {
+                issues.push(output::LedgerIssue {
+                    stash: stash.clone(),
+                    controller: controller.clone(),
+                    issue,
+                    repair: format!("0x{}", hex::encode(repair.encode())),
+                })
+            };
+
+            let bonded_key = api
+                .storage()
+                .address_bytes(&query.staking().bonded(stash))?;
+            match storage.fetch(&query.staking().bonded(stash)).await? {
+                None => issue(
+                    "missing bonded entry".to_string(),
+                    set_storage(bonded_key, controller.encode()),
+                ),
+                Some(bonded) if &bonded != controller => issue(
+                    format!("bonded to {bonded} but the ledger is under {controller}"),
+                    set_storage(bonded_key, controller.encode()),
+                ),
+                Some(_) => (),
+            }
+
+            let claims = ledgers.iter().filter(|(_, l)| &l.stash == stash).count();
+            let controls_other =
+                stash != controller && ledgers.iter().any(|(c, l)| c == stash && &l.stash != stash);
+            if claims > 1 || controls_other {
+                issue(
+                    "double-bonded stash".to_string(),
+                    RuntimeCall::Staking(StakingCall::force_unstake {
+                        stash: stash.clone(),
+                        num_slashing_spans: 0,
+                    }),
+                );
+            }
+
+            let mut locks = storage
+                .fetch(&query.balances().locks(stash))
+                .await?
+                .map(|locks| locks.0)
+                .unwrap_or_default();
+            let locked = locks
+                .iter()
+                .find(|l| l.id == helpers::STAKING_LOCK)
+                .map(|l| l.amount)
+                .unwrap_or_default();
+            if locked != ledger.total && !virtual_stakers.contains(stash) {
+                locks.retain(|l| l.id != helpers::STAKING_LOCK);
+                locks.push(BalanceLock {
+                    id: helpers::STAKING_LOCK,
+                    amount: ledger.total,
+                    reasons: Reasons::All,
+                });
+                issue(
+                    format!("staking lock {locked} != ledger total {}", ledger.total),
+                    set_storage(
+                        api.storage()
+                            .address_bytes(&query.balances().locks(stash))?,
+                        WeakBoundedVec(locks).encode(),
+                    ),
+                );
+            }
+        }
+
+        helpers::render(
+            &configs,
+            &output::LedgerCheck {
+                ledgers: ledgers.len(),
+                issues,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Compares every staking config set in the `expected` TOML file with its on-chain value,
+    /// failing if any of them drifted. Perbill and percent configs are in parts per billion and
+    /// percent, respectively.
+    pub(crate) async fn check_config(
+        expected: PathBuf,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let expected: output::StakingConfigs = toml::from_str(&std::fs::read_to_string(&expected)?)
+            .map_err(|e| eyre!("invalid expected configs {}: {e}", expected.display()))?;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let actual = helpers::staking_configs(&api, &configs).await?;
+
+        let drifts = expected.drifts(&actual);
+        helpers::render(
+            &configs,
+            &output::ConfigCheck {
+                actual,
+                drifts: drifts.clone(),
+            },
+        )
+        .await?;
+        if !drifts.is_empty() {
+            bail!("{} staking config(s) drifted", drifts.len());
+        }
+
+        Ok(configs)
+    }
+
+    /// Checks every virtual staker: it has no staking lock nor hold, as its funds are held by
+    /// its delegators instead, and a ledger. The stake of a `DelegatedStaking` agent, with its
+    /// unclaimed withdrawals and pending slash, must not exceed its delegations, which must add
+    /// up to the delegators' ones, and match them for a pool, all of whose funds are bonded.
+    /// The points of a pool must add up to its members' active points. With `DelegatedStaking`,
+    /// every pool bonded account must be a virtual staker.
+    pub(crate) async fn check_virtual_stakers(configs: Configs) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage();
+        let virtual_stakers = helpers::virtual_stakers(&api, &storage).await?;
+        let pools = helpers::pool_accounts(&api, &storage).await?;
+        let delegated = api.metadata().pallet_by_name("DelegatedStaking").is_some();
+
+        let (agents, delegations) = if delegated {
+            (
+                helpers::agents(&api, &storage).await?,
+                helpers::delegations(&api, &storage).await?,
+            )
+        } else {
+            (vec![], vec![])
+        };
+        let mut delegated_to = BTreeMap::<AccountId32, Balance>::new();
+        for (_, delegation) in delegations.iter() {
+            *delegated_to.entry(delegation.agent.clone()).or_default() += delegation.amount;
+        }
+        let mut member_points = BTreeMap::<u32, (u32, u128)>::new();
+        let mut members = storage
+            .iter(query.nomination_pools().pool_members_iter())
+            .await?;
+        while let Some(Ok((_, member))) = members.next().await {
+            let (count, points) = member_points.entry(member.pool_id).or_default();
+            *count += 1;
+            *points += member.points;
+        }
+
+        let mut stakers = vec![];
+        for who in virtual_stakers.iter() {
+            let mut violations = vec![];
+            let locked = storage
+                .fetch(&query.balances().locks(who))
+                .await?
+                .map(|locks| locks.0)
+                .unwrap_or_default()
+                .iter()
+                .find(|l| l.id == helpers::STAKING_LOCK)
+                .map(|l| l.amount)
+                .unwrap_or_default();
+            if locked > 0 {
+                violations.push(format!("staking lock of {locked}"));
+            }
+            let held = helpers::held(&storage, who, "Staking").await?;
+            if held > 0 {
+                violations.push(format!("{held} held by staking"));
+            }
+            let ledger = storage.fetch(&query.staking().ledger(who)).await?;
+            if ledger.is_none() {
+                violations.push("no ledger".to_string());
+            }
+            let (total, active) = ledger.map_or((0, 0), |l| (l.total, l.active));
+
+            let pool = pools.get(who).copied();
+            let agent = agents
+                .iter()
+                .find(|(agent, _)| agent == who)
+                .map(|(_, l)| l);
+            if let Some(agent) = agent {
+                let sum = delegated_to.get(who).copied().unwrap_or_default();
+                if sum != agent.total_delegated {
+                    violations.push(format!(
+                        "delegated {} but its delegators delegated {sum}",
+                        agent.total_delegated
+                    ));
+                }
+                let accounted = total + agent.unclaimed_withdrawals + agent.pending_slash;
+                if accounted > agent.total_delegated
+                    || (pool.is_some() && accounted != agent.total_delegated)
+                {
+                    violations.push(format!(
+                        "staked {total} + unclaimed {} + pending slash {} for {} delegated",
+                        agent.unclaimed_withdrawals, agent.pending_slash, agent.total_delegated
+                    ));
+                }
+            } else if delegated {
+                violations.push("not a DelegatedStaking agent".to_string());
+            }
+
+            let mut pool_points = None;
+            if let Some(pool_id) = pool {
+                if let Some(bonded) = storage
+                    .fetch(&query.nomination_pools().bonded_pools(pool_id))
+                    .await?
+                {
+                    let (count, points) = member_points.get(&pool_id).copied().unwrap_or_default();
+                    if points != bonded.points {
+                        violations.push(format!(
+                            "pool {pool_id} has {} points but its members {points}",
+                            bonded.points
+                        ));
+                    }
+                    if count != bonded.member_counter {
+                        violations.push(format!(
+                            "pool {pool_id} counts {} members but has {count}",
+                            bonded.member_counter
+                        ));
+                    }
+                    pool_points = Some(bonded.points);
+                }
+            }
+
+            stakers.push(output::VirtualStaker {
+                who: who.clone(),
+                pool,
+                delegated: agent.map(|a| a.total_delegated),
+                total,
+                active,
+                pool_points,
+                violations,
+            });
+        }
+        if delegated {
+            for (account, pool_id) in pools.iter() {
+                let exists = storage
+                    .fetch(&query.nomination_pools().bonded_pools(pool_id))
+                    .await?
+                    .is_some();
+                if exists && !virtual_stakers.contains(account) {
+                    stakers.push(output::VirtualStaker {
+                        who: account.clone(),
+                        pool: Some(*pool_id),
+                        delegated: None,
+                        total: 0,
+                        active: 0,
+                        pool_points: None,
+                        violations: vec!["pool bonded account not a virtual staker".to_string()],
+                    });
+                }
+            }
+        }
+
+        let n_violations: usize = stakers.iter().map(|s| s.violations.len()).sum();
+        helpers::render(&configs, &output::VirtualStakers { stakers }).await?;
+        if n_violations > 0 {
+            bail!("{n_violations} virtual staker invariant(s) violated");
+        }
+
+        Ok(configs)
+    }
+
+    /// Attributes the first `window` blocks of `era` (the active one by default) to their Aura
+    /// author and compares the authors with the validators elected for the era. The elected
+    /// validators disabled at any block of the window are not expected to author, and none is
+    /// expected to until the blocks produced since the activation give each a slot.
+    pub(crate) async fn check_authorship(
+        era: Option<u32>,
+        window: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeSet;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let era = match era {
+            Some(era) => era,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let elected: BTreeSet<AccountId32> = helpers::get_era_exposures(&storage, era)
+            .await?
+            .into_iter()
+            .map(|(validator, _)| validator)
+            .collect();
+        if elected.is_empty() {
+            bail!("no validators elected for era {era}, or its exposures were pruned");
+        }
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .map(|a| a.account_id())
+            .collect();
+
+        let rpc = helpers::legacy_rpc(&configs).await?;
+        let from = helpers::era_activation_block(&api, &rpc, era).await?;
+        let n_session = api
+            .storage()
+            .at(helpers::block_hash(&rpc, from).await?)
+            .fetch_or_default(&staking_parachain::storage().session().validators())
+            .await?
+            .len() as u32;
+        let window = window.unwrap_or(2 * n_session);
+        if window < n_session {
+            bail!("--window {window} is below the {n_session} session validators");
+        }
+        let latest = api.blocks().at_latest().await?.number();
+        let to = (from + window.saturating_sub(1)).min(latest);
+        // the window may be clipped at the latest block right after the activation.
+        let complete = to - from + 1 >= n_session;
+
+        let (mut authors, mut session, mut disabled) =
+            (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+        for number in from..=to {
+            let hash = helpers::block_hash(&rpc, number).await?;
+            let (author, validators) = helpers::block_author(&api, hash).await?;
+            for index in api
+                .storage()
+                .at(hash)
+                .fetch_or_default(&staking_parachain::storage().session().disabled_validators())
+                .await?
+            {
+                if let Some(validator) = validators.get(index as usize) {
+                    disabled.insert(validator.clone());
+                }
+            }
+            session.extend(validators);
+            authors.extend(author);
+        }
+
+        let never_authored: Vec<output::SilentValidator> = elected
+            .iter()
+            .filter(|v| complete && !authors.contains(*v) && !disabled.contains(*v))
+            .map(|v| output::SilentValidator {
+                validator: v.clone(),
+                managed: managed.contains(v),
+                in_session: session.contains(v),
+            })
+            .collect();
+        let unexpected: Vec<AccountId32> = authors.difference(&elected).cloned().collect();
+        let check = output::AuthorshipCheck {
+            era,
+            from,
+            to,
+            complete,
+            elected: elected.len(),
+            authors: authors.len(),
+            disabled: disabled.intersection(&elected).cloned().collect(),
+            never_authored,
+            unexpected,
+        };
+
+        helpers::render(&configs, &check).await?;
+        if !check.never_authored.is_empty() || !check.unexpected.is_empty() {
+            bail!(
+                "{} elected validator(s) never authored and {} author(s) were not elected",
+                check.never_authored.len(),
+                check.unexpected.len()
+            );
+        }
+
+        Ok(configs)
+    }
+
+    /// Lists the validators bonded below `MinValidatorBond` and the nominators bonded below
+    /// `MinNominatorBond` and, with `chill`, submits `chill_other` for all of them.
+    pub(crate) async fn check_below_min(
+        chill: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage();
+
+        let min_validator_bond = storage
+            .fetch_or_default(&query.staking().min_validator_bond())
+            .await?;
+        let min_nominator_bond = storage
+            .fetch_or_default(&query.staking().min_nominator_bond())
+            .await?;
+
+        let mut stakers = vec![];
+        for (role, targets, min) in [
+            (
+                Role::Validator,
+                helpers::get_validators(&api, &configs).await?,
+                min_validator_bond,
+            ),
+            (
+                Role::Nominator,
+                helpers::get_nominators(&api, &configs).await?,
+                min_nominator_bond,
+            ),
+        ] {
+            for target in targets {
+                let Id(stash) = target else { continue };
+                let Some(controller) = storage.fetch(&query.staking().bonded(&stash)).await? else {
+                    continue;
+                };
+                let active = storage
+                    .fetch(&query.staking().ledger(&controller))
+                    .await?
+                    .map(|l| l.active)
+                    .unwrap_or_default();
+                if active < min {
+                    stakers.push(output::BelowMinStaker {
+                        stash,
+                        role,
+                        active,
+                    });
+                }
+            }
+        }
+
+        let calls: Vec<RuntimeCall> = stakers
+            .iter()
+            .map(|s| {
+                RuntimeCall::Staking(StakingCall::chill_other {
+                    stash: s.stash.clone(),
+                })
+            })
+            .collect();
+        helpers::render(
+            &configs,
+            &output::BelowMin {
+                min_validator_bond,
+                min_nominator_bond,
+                stakers,
+            },
+        )
+        .await?;
+
+        if chill && !calls.is_empty() {
+            let n_calls = calls.len();
+            println!("> Chilling {n_calls} staker(s)..");
+            let progress = helpers::submit(
+                &api,
+                &configs,
+                helpers::batch(&configs, calls),
+                &configs.signer()?,
+            )
+            .await?;
+            let events = helpers::wait_for_success(&configs, progress).await?;
+            let failed = helpers::batch_failures(&events, n_calls)?;
+            println!("Chilled {}/{n_calls} staker(s).", n_calls - failed.len());
+        }
+
+        Ok(configs)
+    }
+
+    /// Reports the paid and outstanding rewards of every CLI-created staker over the payable
+    /// eras, recording the eras they were fully paid for in the index. The eras already recorded
+    /// are not checked on chain again.
+    pub(crate) async fn rewards_status(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let mut index = Index::load(&configs.index)?;
+        let eras = helpers::payable_eras(&api, &configs).await?;
+
+        let mut stakers = vec![];
+        for account in State::load_for(&configs).await?.accounts.iter() {
+            let who = account.account_id();
+            let recorded = index.claimed_rewards.entry(who.clone()).or_default();
+            let unrecorded: Vec<u32> = eras.clone().filter(|era| !recorded.contains(era)).collect();
+            let (claimed, outstanding) =
+                helpers::staker_rewards(&storage, &who, account.role, unrecorded).await?;
+            recorded.extend(claimed);
+            let claimed = eras.clone().filter(|era| recorded.contains(era)).collect();
+            stakers.push(output::StakerRewards {
+                who,
+                role: account.role,
+                claimed,
+                outstanding,
+            });
+        }
+        index.save(&configs.index)?;
+
+        helpers::render(
+            &configs,
+            &output::RewardsStatus {
+                from_era: *eras.start(),
+                to_era: *eras.end(),
+                stakers,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Pays out the outstanding pages of the CLI-created stakers with one
+    /// `payout_stakers_by_page` per page, so that a failing page does not hold back the others,
+    /// and reports the validator/era/page combinations still unpaid afterwards.
+    pub(crate) async fn rewards_payout(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let signer = configs.signer()?;
+
+        let outstanding = helpers::outstanding_payouts(&api, &configs).await?;
+        if outstanding.is_empty() {
+            println!("No outstanding payouts.");
+            return Ok(configs);
+        }
+
+        println!("> Paying out {} outstanding page(s)..", outstanding.len());
+        let mut pending = vec![];
+        for (era, validator_stash, page) in outstanding.iter().cloned() {
+            let call = RuntimeCall::Staking(StakingCall::payout_stakers_by_page {
+                validator_stash: validator_stash.clone(),
+                era,
+                page,
+            });
+            let progress = helpers::submit(&api, &configs, call, &signer).await;
+            pending.push(((era, validator_stash, page), progress));
+        }
+        let mut n_failed = 0;
+        for ((era, validator, page), progress) in pending {
+            let result = match progress {
+                Ok(progress) => helpers::wait_for_success(&configs, progress).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                n_failed += 1;
+                println!(" ! payout of era {era} page {page} of {validator} failed: {e}");
+            }
+        }
+        println!(
+            "Payouts done for {} page(s), {n_failed} failed.",
+            outstanding.len() - n_failed
+        );
+
+        let unpaid: Vec<output::OutstandingPayout> = helpers::outstanding_payouts(&api, &configs)
+            .await?
+            .into_iter()
+            .map(|(era, validator, page)| output::OutstandingPayout {
+                era,
+                validator,
+                page,
+            })
+            .collect();
+        let n_unpaid = unpaid.len();
+        helpers::render(&configs, &output::Unpaid(unpaid)).await?;
+        if n_unpaid > 0 {
+            bail!("{n_unpaid} page(s) remain unpaid");
+        }
+
+        Ok(configs)
+    }
+
+    /// Writes one CSV row per era and elected validator for the eras `from..=to`, with the
+    /// validator's reward points, backing and payout share next to the era totals.
+    pub(crate) async fn export_eras(
+        from: u32,
+        to: Option<u32>,
+        out: Option<PathBuf>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let staking = staking_parachain::storage().staking();
+        let to = match to {
+            Some(to) => to,
+            None => helpers::active_era(&api, &configs).await?,
+        };
+
+        let mut csv = vec![
+            "era,validator,reward_points,total_backing,own_stake,nominators,era_total_stake,era_total_points,era_payout,validator_payout"
+                .to_string(),
+        ];
+        for era in from..=to {
+            let total_stake = storage
+                .fetch_or_default(&staking.eras_total_stake(era))
+                .await?;
+            let payout = storage
+                .fetch(&staking.eras_validator_reward(era))
+                .await?
+                .unwrap_or_default();
+            let points = storage.fetch(&staking.eras_reward_points(era)).await?;
+            let (total_points, individual) =
+                points.map(|p| (p.total, p.individual)).unwrap_or_default();
+
+            for (validator, exposure) in helpers::get_era_exposures(&storage, era).await? {
+                let validator_points = individual
+                    .iter()
+                    .find(|(who, _)| who == &validator)
+                    .map(|(_, p)| *p)
+                    .unwrap_or_default();
+                let validator_payout =
+                    payout * validator_points as Balance / total_points.max(1) as Balance;
+                csv.push(format!(
+                    "{era},{validator},{validator_points},{},{},{},{total_stake},{total_points},{payout},{validator_payout}",
+                    exposure.total, exposure.own, exposure.nominator_count
+                ));
+            }
+        }
+
+        let csv = csv.join("\n") + "\n";
+        match out {
+            Some(path) => {
+                std::fs::write(&path, csv)?;
+                println!("> Eras {from}..={to} exported to {}.", path.display());
+            }
+            None => print!("{csv}"),
+        }
+
+        Ok(configs)
+    }
+
+    /// Waits until at least `fraction` of the validators created by the CLI are part of the
+    /// active set. Fails if that did not happen after `max_eras` eras.
+    pub(crate) async fn wait_until_active(
+        fraction: f64,
+        max_eras: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            bail!("fraction must be within (0, 1], got {fraction}");
+        }
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let managed: Vec<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts_with_role(Role::Validator)
+            .map(|a| a.account_id())
+            .collect();
+        if managed.is_empty() {
+            bail!(
+                "no CLI-created validators found in {}",
+                configs.state.display()
+            );
+        }
+        let expected = ((managed.len() as f64) * fraction).ceil() as usize;
+        let deadline = helpers::active_era(&api, &configs).await? + max_eras;
+
+        println!(
+            "> Waiting until {expected}/{} CLI-created validators are active (until era {deadline})..",
+            managed.len()
+        );
+
+        helpers::serve_status(&configs).await?;
+        let mut last_era = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        while let Some(block) = blocks.next().await {
+            helpers::notify_staking_events(&configs, &block?.events().await?).await?;
+            let era = helpers::active_era(&api, &configs).await?;
+            if last_era == Some(era) {
+                continue;
+            }
+            last_era = Some(era);
+            configs.status.update(|s| s.last_era = Some(era));
+
+            let (_, active, _) = helpers::classify_validators(&api, &configs).await?;
+            let n_active = managed
+                .iter()
+                .filter(|m| active.iter().any(|(a, _)| &a == m))
+                .count();
+            println!(" era {era}: {n_active}/{} active.", managed.len());
+
+            if n_active >= expected {
+                println!("Validators active in era {era}.");
+                helpers::notify(
+                    &configs,
+                    "completed",
+                    &format!(
+                        "{n_active}/{} validators active in era {era}",
+                        managed.len()
+                    ),
+                )
+                .await;
+                return Ok(configs);
+            }
+            if era >= deadline {
+                let message =
+                    format!("only {n_active}/{expected} expected validators active by era {era}");
+                helpers::alert(&configs, &message).await;
+                bail!(message);
+            }
+        }
+
+        bail!("block subscription ended before the validators became active")
+    }
+
+    /// Runs the staking invariants, the drift check against `expected_configs`, the election
+    /// verification over the snapshot at `election_at` and `assertions`, and renders all their
+    /// results before failing if any of them failed.
+    pub(crate) async fn ci_check(
+        expected_configs: Option<PathBuf>,
+        assertions: Option<String>,
+        since: Option<u32>,
+        election_at: Option<H256>,
+        tolerance: f64,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let mut checks = vec![];
+
+        let violations =
+            helpers::staking_invariants(&helpers::storage_at(&api, &configs).await?).await?;
+        checks.push(output::Check::new(
+            "invariants",
+            violations.is_empty(),
+            if violations.is_empty() {
+                "staking invariants hold".to_string()
+            } else {
+                violations.join("; ")
+            },
+        ));
+
+        if let Some(expected) = expected_configs {
+            let expected: output::StakingConfigs =
+                toml::from_str(&std::fs::read_to_string(&expected)?)
+                    .map_err(|e| eyre!("invalid expected configs {}: {e}", expected.display()))?;
+            let drifts = expected.drifts(&helpers::staking_configs(&api, &configs).await?);
+            let detail = if drifts.is_empty() {
+                "no staking config drifted".to_string()
+            } else {
+                drifts
+                    .iter()
+                    .map(|d| format!("{} expected {} but is {}", d.config, d.expected, d.actual))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            checks.push(output::Check::new(
+                "config-drift",
+                drifts.is_empty(),
+                detail,
+            ));
+        }
+
+        // the snapshot at a block is the one of the election of the era after its current one.
+        let comparison = match election_at {
+            Some(at) => {
+                let era = api
+                    .storage()
+                    .at(at)
+                    .fetch(&staking_parachain::storage().staking().current_era())
+                    .await?
+                    .unwrap_or_default()
+                    + 1;
+                helpers::compare_election(&api, &configs, Some(era), Some(at), 0, tolerance).await?
+            }
+            None => None,
+        };
+        checks.push(match comparison {
+            Some(comparison) => output::Check::new(
+                "election",
+                comparison.divergences.is_empty(),
+                if comparison.divergences.is_empty() {
+                    format!("election of era {} matches the offline one", comparison.era)
+                } else {
+                    comparison.divergences.join("; ")
+                },
+            ),
+            None => output::Check::new(
+                "election",
+                true,
+                "skipped, no election snapshot at --election-at".to_string(),
+            ),
+        });
+
+        if let Some(assertions) = assertions {
+            for result in helpers::check_assertions(&api, &configs, &assertions, since).await? {
+                let detail = match (result.actual, result.error) {
+                    (Some(actual), _) => format!("{} (actual {actual})", result.assertion),
+                    (None, error) => format!("{}: {}", result.assertion, error.unwrap_or_default()),
+                };
+                checks.push(output::Check::new("assertion", result.passed, detail));
+            }
+        }
+
+        let failed = checks.iter().filter(|c| !c.passed).count();
+        helpers::render(&configs, &output::Doctor { checks }).await?;
+        if failed > 0 {
+            bail!("{failed} CI check(s) failed");
+        }
+
+        Ok(configs)
+    }
+
+    /// Runs a sequential phragmen election over the snapshot read at block `at` and diffs its
+    /// winners and their backing against the exposures of `era`.
+    pub(crate) async fn verify_election(
+        era: Option<u32>,
+        at: Option<H256>,
+        balancing: usize,
+        tolerance: f64,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        println!("> Running the election offline..");
+        let Some(comparison) =
+            helpers::compare_election(&api, &configs, era, at, balancing, tolerance).await?
+        else {
+            bail!(
+                "no election snapshot found, use --at with a block where the election is ongoing"
+            );
+        };
+
+        let n_divergences = comparison.divergences.len();
+        helpers::render(
+            &configs,
+            &output::ElectionVerification {
+                era: comparison.era,
+                onchain: comparison.onchain,
+                offline: comparison.offline,
+                divergences: comparison.divergences,
+            },
+        )
+        .await?;
+        if n_divergences > 0 {
+            bail!("{n_divergences} divergence(s) between the on-chain and offline election");
+        }
+
+        Ok(configs)
+    }
+
+    /// Walks the voter list from its top bag down, from head to tail in every bag, as the
+    /// snapshot does, taking the validators and the nominators with stake and nominations until
+    /// `VoterSnapshotPerBlock * Pages` voters are taken, and the validators in storage order up
+    /// to `TargetSnapshotPerBlock`. Lists the voters and targets left out, only the CLI-created
+    /// ones with `managed`.
+    pub(crate) async fn simulate_trimming(
+        managed: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+        let constants = api.constants();
+        let epm = staking_parachain::constants().election_provider_multi_block();
+        let max_voters = constants.at(&epm.voter_snapshot_per_block())? as usize
+            * constants.at(&epm.pages())? as usize;
+        let max_targets = constants.at(&epm.target_snapshot_per_block())? as usize;
+        let thresholds =
+            constants.at(&staking_parachain::constants().voter_list().bag_thresholds())?;
+        let mine: BTreeSet<AccountId32> = State::load_for(&configs)
+            .await?
+            .accounts
+            .iter()
+            .map(|a| a.account_id())
+            .collect();
+
+        let mut validators = vec![];
+        let mut results = storage.iter(query.validators_iter()).await?;
+        while let Some(Ok((key, _))) = results.next().await {
+            validators.push(helpers::account_from_key(&key));
+        }
+        let mut nominations: BTreeMap<AccountId32, usize> = BTreeMap::new();
+        let mut results = storage.iter(query.nominators_iter()).await?;
+        while let Some(Ok((key, nominations_of))) = results.next().await {
+            nominations.insert(
+                helpers::account_from_key(&key),
+                nominations_of.targets.0.len(),
+            );
+        }
+        let is_validator: BTreeSet<&AccountId32> = validators.iter().collect();
+
+        let mut taken = 0;
+        let mut lowest_taken = None;
+        let mut excluded = vec![];
+        let bags = std::iter::once(u64::MAX)
+            .chain(thresholds.iter().rev().copied().filter(|t| *t != u64::MAX));
+        for bag in bags {
+            for (who, weight) in helpers::bag_members(&api, &configs, bag).await? {
+                let votes = if is_validator.contains(&who) {
+                    1
+                } else {
+                    nominations.get(&who).copied().unwrap_or_default()
+                };
+                if votes == 0 || weight == 0 {
+                    continue;
+                }
+                if taken < max_voters {
+                    taken += 1;
+                    lowest_taken = Some(weight);
+                } else if !managed || mine.contains(&who) {
+                    excluded.push(output::TrimmedStaker {
+                        managed: mine.contains(&who),
+                        who,
+                        weight: Some(weight),
+                    });
+                }
+            }
+        }
+        let excluded_voters = excluded.len();
+        for who in validators.into_iter().skip(max_targets) {
+            if !managed || mine.contains(&who) {
+                excluded.push(output::TrimmedStaker {
+                    managed: mine.contains(&who),
+                    who,
+                    weight: None,
+                });
+            }
+        }
+
+        helpers::render(
+            &configs,
+            &output::TrimmingSimulation {
+                max_voters,
+                voters: taken,
+                lowest_taken,
+                max_targets,
+                excluded_targets: excluded.split_off(excluded_voters),
+                excluded_voters: excluded,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Reports the nominations quota of every bond in `bonds` and of the active bond of every
+    /// account in `accounts`, with the minimum bonds it is checked against and, for accounts,
+    /// their current nominations and unlocking chunks against the runtime limits.
+    pub(crate) async fn quota(
+        bonds: Vec<Balance>,
+        accounts: Vec<AccountId32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let query = staking_parachain::storage().staking();
+        let constants = staking_parachain::constants().staking();
+
+        let min_nominator_bond = storage
+            .fetch_or_default(&query.min_nominator_bond())
+            .await?;
+        let min_validator_bond = storage
+            .fetch_or_default(&query.min_validator_bond())
+            .await?;
+        let mut quotas = std::collections::BTreeMap::new();
+
+        let mut subjects: Vec<(Option<AccountId32>, Balance)> =
+            bonds.into_iter().map(|bond| (None, bond)).collect();
+        for account in accounts {
+            let controller = storage
+                .fetch(&query.bonded(&account))
+                .await?
+                .ok_or_else(|| eyre!("{account} is not bonded"))?;
+            let bond = helpers::active_bond(&api, &configs, &controller).await?;
+            subjects.push((Some(account), bond));
+        }
+
+        let mut limits = vec![];
+        for (account, bond) in subjects {
+            let (nominations, unlocking_chunks) = match account.as_ref() {
+                Some(account) => {
+                    let nominations = storage
+                        .fetch(&query.nominators(account))
+                        .await?
+                        .map_or(0, |n| n.targets.0.len());
+                    let controller = storage.fetch(&query.bonded(account)).await?;
+                    let chunks = match controller {
+                        Some(controller) => storage
+                            .fetch(&query.ledger(controller))
+                            .await?
+                            .map_or(0, |ledger| ledger.unlocking.0.len()),
+                        None => 0,
+                    };
+                    (Some(nominations), Some(chunks))
+                }
+                None => (None, None),
+            };
+            limits.push(output::BondLimits {
+                account,
+                bond,
+                nominations_quota: helpers::nominations_quota(&api, &configs, &mut quotas, bond)
+                    .await?,
+                can_nominate: bond >= min_nominator_bond,
+                can_validate: bond >= min_validator_bond,
+                nominations,
+                unlocking_chunks,
+            });
+        }
+
+        helpers::render(
+            &configs,
+            &output::Quota {
+                min_nominator_bond,
+                min_validator_bond,
+                max_unlocking_chunks: api.constants().at(&constants.max_unlocking_chunks())?,
+                max_exposure_page_size: api.constants().at(&constants.max_exposure_page_size())?,
+                bonding_duration: api.constants().at(&constants.bonding_duration())?,
+                limits,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Prints the runtime version of the chain and the version of the metadata it serves.
+    pub(crate) async fn runtime(configs: Configs) -> color_eyre::Result<Configs> {
+        use subxt::backend::rpc::{rpc_params, RpcClient};
+
+        let version = helpers::legacy_rpc(&configs)
+            .await?
+            .state_get_runtime_version(None)
+            .await?;
+        let other = |key: &str| version.other.get(key).cloned().unwrap_or_default();
+
+        // the metadata is prefixed with the `meta` magic number and its version.
+        let metadata: String = RpcClient::from_insecure_url(&configs.url)
+            .await?
+            .request("state_getMetadata", rpc_params![])
+            .await?;
+        let metadata = hex::decode(metadata.trim_start_matches("0x"))?;
+        let metadata_version = match metadata.get(..5) {
+            Some([b'm', b'e', b't', b'a', version]) => *version,
+            _ => bail!("metadata without the meta prefix"),
+        };
+
+        helpers::render(
+            &configs,
+            &output::RuntimeInfo {
+                spec_name: other("specName").as_str().unwrap_or_default().to_string(),
+                impl_name: other("implName").as_str().unwrap_or_default().to_string(),
+                spec_version: version.spec_version,
+                impl_version: other("implVersion").as_u64().unwrap_or_default() as u32,
+                transaction_version: version.transaction_version,
+                metadata_version,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Runs the preflight checks of a run and fails if any does. The sudo key is only required
+    /// with `--sudo` or `--auto-topup`.
+    pub(crate) async fn doctor(
+        finality_timeout: u64,
+        min_balance: Balance,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        const PALLETS: [&str; 7] = [
+            "System",
+            "Balances",
+            "Staking",
+            "VoterList",
+            "Session",
+            "Utility",
+            "ElectionProviderMultiBlock",
+        ];
+
+        let mut checks = vec![];
+
+        let api = match OnlineClient::<SubstrateConfig>::from_url(&configs.url).await {
+            Ok(api) => api,
+            Err(e) => {
+                checks.push(output::Check::new("connectivity", false, e.to_string()));
+                output::render(configs.output, &output::Doctor { checks })?;
+                bail!("cannot connect to {}", configs.url);
+            }
+        };
+        let rpc = helpers::legacy_rpc(&configs).await?;
+        let version = api.runtime_version();
+        checks.push(output::Check::new(
+            "connectivity",
+            true,
+            format!(
+                "{} spec version {}",
+                rpc.system_chain().await?,
+                version.spec_version
+            ),
+        ));
+
+        let health = rpc.system_health().await?;
+        checks.push(output::Check::new(
+            "peers",
+            health.peers > 0 || !health.should_have_peers,
+            format!("{} peer(s), syncing: {}", health.peers, health.is_syncing),
+        ));
+
+        let rpc = &rpc;
+        let finalized = || async move {
+            let hash = rpc.chain_get_finalized_head().await?;
+            color_eyre::Result::<_>::Ok(
+                rpc.chain_get_header(Some(hash))
+                    .await?
+                    .ok_or(eyre!("no finalized header"))?
+                    .number,
+            )
+        };
+        let start = finalized().await?;
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(finality_timeout);
+        let mut last = start;
+        while last <= start && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            last = finalized().await?;
+        }
+        checks.push(output::Check::new(
+            "finality",
+            last > start,
+            format!("finalized #{start} -> #{last} in at most {finality_timeout}s"),
+        ));
+
+        let metadata = api.metadata();
+        let missing: Vec<_> = PALLETS
+            .iter()
+            .filter(|p| metadata.pallet_by_name(p).is_none())
+            .collect();
+        checks.push(output::Check::new(
+            "pallets",
+            missing.is_empty(),
+            if missing.is_empty() {
+                format!("{} required pallet(s) present", PALLETS.len())
+            } else {
+                format!("missing {missing:?}")
+            },
+        ));
+
+        let signer: AccountId32 = configs.signer()?.public_key().into();
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let sudo_required = configs.sudo || configs.auto_topup;
+        let (is_sudo, detail) = match metadata.pallet_by_name("Sudo") {
+            None => (false, "no sudo pallet".to_string()),
+            Some(_) => match storage
+                .fetch(&staking_parachain::storage().sudo().key())
+                .await?
+            {
+                Some(key) if key == signer => (true, format!("signer {signer} is the sudo key")),
+                Some(key) => (false, format!("sudo key is {key}, not the signer")),
+                None => (false, "no sudo key".to_string()),
+            },
+        };
+        let detail = if sudo_required {
+            detail
+        } else {
+            format!("{detail} (not required)")
+        };
+        checks.push(output::Check::new(
+            "sudo key",
+            is_sudo || !sudo_required,
+            detail,
+        ));
+
+        let free = helpers::staker_status(&api, &configs, &signer).await?.free;
+        checks.push(output::Check::new(
+            "funder balance",
+            free > min_balance,
+            format!("{signer} has {free} free, minimum {min_balance}"),
+        ));
+
+        let failed = checks.iter().filter(|c| !c.passed).count();
+        helpers::render(&configs, &output::Doctor { checks }).await?;
+        if failed > 0 {
+            bail!("{failed} check(s) failed");
+        }
+
+        Ok(configs)
+    }
+
+    /// Decodes hex encoded calls, optionally wraps them in a batch (see `--batch-mode`), and
+    /// submits the result signed by the configured signer.
+    pub(crate) async fn submit_raw(
+        calls: Vec<String>,
+        batch: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let mut calls = calls
+            .iter()
+            .map(|call| helpers::decode_call(call))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+        let n_calls = calls.len();
+        let call = match (n_calls, batch) {
+            (_, true) => helpers::batch(&configs, calls),
+            (1, false) => calls.remove(0),
+            _ => bail!("more than one call given, use --batch to submit them together"),
+        };
+
+        println!("> Submitting call 0x{}..", hex::encode(call.encode()));
+        let progress = helpers::submit(&api, &configs, call, &configs.signer()?).await?;
+        let events = helpers::wait_for_success(&configs, progress).await?;
+        let mut submitted = output::RawSubmission {
+            extrinsic: events.extrinsic_hash(),
+            block: events.block_hash(),
+            events: vec![],
+        };
+        for event in events.iter() {
+            let event = event?;
+            submitted
+                .events
+                .push(format!("{}::{}", event.pallet_name(), event.variant_name()));
+        }
+        helpers::render(&configs, &submitted).await?;
+
+        if let Some(Sudid {
+            sudo_result: Err(error),
+        }) = events.find_first::<Sudid>()?
+        {
+            bail!("sudo call failed: {error:?}");
+        }
+        if batch {
+            let failed = helpers::batch_failures(&events, n_calls)?;
+            if !failed.is_empty() {
+                bail!("{} of {n_calls} batched call(s) failed", failed.len());
+            }
+        }
+
+        Ok(configs)
+    }
+
+    /// Submits all the signed extrinsics of `file` first, so that consecutive nonces of a signer
+    /// can be pooled together, then waits for each. Pre-signed extrinsics cannot be re-signed, so
+    /// the ones dropped from the pool fail.
+    pub(crate) async fn submit_file(
+        file: PathBuf,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let mut submissions = vec![];
+        for (i, line) in std::fs::read_to_string(&file)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let extrinsic = hex::decode(line.trim_start_matches("0x"))
+                .map_err(|e| eyre!("line {}: invalid hex: {e}", i + 1))?;
+            let hash = H256(sp_core::hashing::blake2_256(&extrinsic));
+            let submission = helpers::submit_signed(&api, &configs, extrinsic).await;
+            submissions.push((i + 1, hash, submission));
+        }
+        println!(
+            "> Submitted {} extrinsic(s), waiting for them..",
+            submissions.len()
+        );
+
+        let mut extrinsics = vec![];
+        for (line, hash, submission) in submissions {
+            let outcome = match submission {
+                Ok(submission) => helpers::wait_for_success(&configs, submission).await,
+                Err(e) => Err(e),
+            };
+            let (block, error) = match outcome {
+                Ok(events) => (Some(events.block_hash()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            extrinsics.push(output::SubmittedExtrinsic {
+                line,
+                hash,
+                block,
+                error,
+            });
+        }
+
+        let failed = extrinsics.iter().filter(|x| x.error.is_some()).count();
+        helpers::render(&configs, &output::SubmittedFile { extrinsics }).await?;
+        if failed > 0 {
+            bail!("{failed} extrinsic(s) of {} failed", file.display());
+        }
+
+        Ok(configs)
+    }
+
+    /// Builds the staking `call`, wrapped in `sudo.sudo` with `sudo`, and prints its SCALE encoded
+    /// call data and blake2-256 hash. Works offline, with the bundled metadata.
+    pub(crate) fn encode(
+        call: EncodeCall,
+        sudo: bool,
+        output: output::OutputFormat,
+    ) -> color_eyre::Result<()> {
+        let mut call = helpers::staking_call(call)?;
+        if sudo {
+            call = RuntimeCall::Sudo(SudoCall::sudo {
+                call: Box::new(call),
+            });
+        }
+
+        let encoded = call.encode();
+        output::render(
+            output,
+            &output::EncodedCall {
+                call: helpers::call_name(&helpers::bundled_metadata()?, &call),
+                call_data: format!("0x{}", hex::encode(&encoded)),
+                hash: H256(sp_core::hashing::blake2_256(&encoded)),
+            },
+        )
+    }
+
+    /// Decodes the hex encoded `call` with the bundled metadata and prints its call tree.
+    pub(crate) fn decode(call: String, output: output::OutputFormat) -> color_eyre::Result<()> {
+        let call = helpers::decode_call(&call)?;
+
+        output::render(
+            output,
+            &helpers::call_tree(&helpers::bundled_metadata()?, &call),
+        )
+    }
+
+    /// Prints the public key of `address` and its SS58 addresses with `prefixes`. The network of
+    /// an SS58 `address` is inferred from its prefix.
+    pub(crate) fn address(
+        address: String,
+        prefixes: Vec<u16>,
+        output: output::OutputFormat,
+    ) -> color_eyre::Result<()> {
+        use sp_core::crypto::{AccountId32, Ss58AddressFormat, Ss58Codec};
+
+        let (account, format) = match hex::decode(address.trim_start_matches("0x")) {
+            Ok(bytes) => {
+                let public: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|b: Vec<u8>| eyre!("public key of {} bytes, expected 32", b.len()))?;
+                (AccountId32::new(public), None)
+            }
+            Err(_) => {
+                let (account, format) = AccountId32::from_ss58check_with_version(&address)
+                    .map_err(|e| {
+                        eyre!("{address} is neither a public key nor an SS58 address: {e:?}")
+                    })?;
+                (account, Some(format))
+            }
+        };
+
+        let addresses = prefixes
+            .into_iter()
+            .map(|prefix| {
+                let format = Ss58AddressFormat::custom(prefix);
+                output::Ss58Address {
+                    prefix,
+                    network: format.to_string(),
+                    address: account.to_ss58check_with_version(format),
+                }
+            })
+            .collect();
+        output::render(
+            output,
+            &output::Address {
+                public_key: format!("0x{}", hex::encode(<[u8; 32]>::from(account))),
+                network: format.map(|f| (f.prefix(), f.to_string())),
+                addresses,
+            },
+        )
+    }
+
+    /// Builds the `set_staking_configs` call applying `changes`, notes it as a preimage and
+    /// submits a referendum for it from `origin` if `submit`, or only prints the calls otherwise.
+    /// The governance calls are built against the metadata of the chain, which must have the
+    /// `Preimage` and `Referenda` pallets.
+    pub(crate) async fn propose(
+        changes: StakingConfigChanges,
+        origin: String,
+        enactment_after: u32,
+        submit: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+
+        let call = helpers::set_staking_configs(changes)?;
+        let preimage = call.encode();
+        let preimage_hash = H256(sp_core::hashing::blake2_256(&preimage));
+        let preimage_len = preimage.len() as u32;
+
+        let governance = helpers::has_governance(&api);
+        let (note_preimage, referendum_submit) = if governance {
+            let (note, referendum) =
+                helpers::referendum_calls(&api, &preimage, &origin, enactment_after)?;
+            (Some(note), Some(referendum))
+        } else if submit {
+            bail!("{} has no Preimage and Referenda pallets", configs.url);
+        } else {
+            (None, None)
+        };
+
+        let mut referendum = None;
+        if let (true, Some(note), Some(referendum_submit)) =
+            (submit, note_preimage.clone(), referendum_submit.clone())
+        {
+            let signer = configs.signer()?;
+            println!("> Noting the preimage {preimage_hash:?}..");
+            let submission =
+                helpers::submit_encoded(&api, &configs, helpers::RawCall(note), &signer).await?;
+            helpers::wait_for_success(&configs, submission).await?;
+
+            println!("> Submitting the referendum..");
+            let submission = helpers::submit_encoded(
+                &api,
+                &configs,
+                helpers::RawCall(referendum_submit),
+                &signer,
+            )
+            .await?;
+            let events = helpers::wait_for_success(&configs, submission).await?;
+            referendum = helpers::referendum_event(events.iter(), "Submitted")?;
+        }
+
+        helpers::render(
+            &configs,
+            &output::Proposal {
+                call: helpers::call_name(&helpers::bundled_metadata()?, &call),
+                call_data: format!("0x{}", hex::encode(&preimage)),
+                preimage_hash,
+                preimage_len,
+                note_preimage: note_preimage.map(|c| format!("0x{}", hex::encode(c))),
+                referendum_submit: referendum_submit.map(|c| format!("0x{}", hex::encode(c))),
+                referendum,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Evaluates the `;` separated `assertions` against the chain state, the failed extrinsics
+    /// being counted from block `since`, and fails if any of them does not hold.
+    pub(crate) async fn assert(
+        assertions: String,
+        since: Option<u32>,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let results = helpers::check_assertions(&api, &configs, &assertions, since).await?;
+
+        let failed = results.iter().filter(|r| !r.passed).count();
+        helpers::render(&configs, &output::Assertions(results)).await?;
+        if failed > 0 {
+            bail!("{failed} assertion(s) failed");
+        }
+
+        Ok(configs)
+    }
+
+    /// Changes the staking configs end-to-end through OpenGov: notes the `set_staking_configs`
+    /// preimage, submits the referendum from `origin`, places its decision deposit, votes it aye
+    /// with each of `voters` and follows the referendum for up to `max_blocks` blocks until it
+    /// is confirmed and the new configs are enacted. Fails if it is rejected, timed out,
+    /// cancelled, killed or not enacted by then.
+    pub(crate) async fn open_gov(
+        changes: StakingConfigChanges,
+        origin: String,
+        enactment_after: u32,
+        voters: Vec<String>,
+        conviction: u8,
+        max_blocks: u32,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use subxt::dynamic::Value;
+
+        if conviction > 6 {
+            bail!("invalid conviction {conviction}, expected 0 to 6");
+        }
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        if !helpers::has_governance(&api) {
+            bail!("{} has no Preimage and Referenda pallets", configs.url);
+        }
+        if !voters.is_empty() && api.metadata().pallet_by_name("ConvictionVoting").is_none() {
+            bail!("{} has no ConvictionVoting pallet", configs.url);
+        }
+        let voters = voters
+            .iter()
+            .map(|uri| Ok(Keypair::from_uri(&SecretUri::from_str(uri)?)?))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        let call = helpers::set_staking_configs(changes.clone())?;
+        // the chain runs another runtime than the bundled one, the preimage is encoded with its
+        // pallet and call indices.
+        let preimage = helpers::chain_call(&api.metadata(), &call)?;
+        let preimage_hash = H256(sp_core::hashing::blake2_256(&preimage));
+        let (note, submit) = helpers::referendum_calls(&api, &preimage, &origin, enactment_after)?;
+        let signer = configs.signer()?;
+
+        println!("> Noting the preimage {preimage_hash:?}..");
+        let submission =
+            helpers::submit_encoded(&api, &configs, helpers::RawCall(note), &signer).await?;
+        helpers::wait_for_success(&configs, submission).await?;
+
+        println!("> Submitting the referendum from {origin}..");
+        let submission =
+            helpers::submit_encoded(&api, &configs, helpers::RawCall(submit), &signer).await?;
+        let events = helpers::wait_for_success(&configs, submission).await?;
+        let Some(referendum) = helpers::referendum_event(events.iter(), "Submitted")? else {
+            bail!("the referendum was submitted but no Referenda.Submitted event was found");
+        };
+        let track = events
+            .iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.pallet_name() == "Referenda" && e.variant_name() == "Submitted")
+            .and_then(|e| e.field_values().ok())
+            .and_then(|fields| {
+                use subxt::ext::scale_value::At;
+                fields.at("track").and_then(|t| t.as_u128())
+            })
+            .map(|t| t as u16);
+
+        println!("> Placing the decision deposit of referendum #{referendum}..");
+        let deposit = api.tx().call_data(&subxt::dynamic::tx(
+            "Referenda",
+            "place_decision_deposit",
+            vec![Value::u128(referendum as u128)],
+        ))?;
+        let submission =
+            helpers::submit_encoded(&api, &configs, helpers::RawCall(deposit), &signer).await?;
+        helpers::wait_for_success(&configs, submission).await?;
+
+        let mut votes = vec![];
+        for voter in voters.iter() {
+            let who = AccountId32::from(voter.public_key());
+            let balance = helpers::staker_status(&api, &configs, &who).await?.free / 10 * 9;
+            println!("> Voting aye with {balance} from {who}..");
+            let vote = api.tx().call_data(&subxt::dynamic::tx(
+                "ConvictionVoting",
+                "vote",
+                vec![
+                    Value::u128(referendum as u128),
+                    Value::named_variant(
+                        "Standard",
+                        [
+                            (
+                                "vote",
+                                Value::unnamed_composite([Value::u128(0x80 | conviction as u128)]),
+                            ),
+                            ("balance", Value::u128(balance)),
+                        ],
+                    ),
+                ],
+            ))?;
+            let submission =
+                helpers::submit_encoded(&api, &configs, helpers::RawCall(vote), voter).await?;
+            helpers::wait_for_success(&configs, submission).await?;
+            votes.push(output::GovVote {
+                voter: who,
+                balance,
+            });
+        }
+
+        let outcomes = ["Confirmed", "Rejected", "TimedOut", "Cancelled", "Killed"];
+        let mut outcome = None;
+        let mut blocks = helpers::subscribe_blocks(&api, &configs).await?;
+        let mut followed = 0;
+        while outcome.is_none() && followed < max_blocks {
+            let Some(block) = blocks.next().await else {
+                break;
+            };
+            let events = block?.events().await?;
+            for name in outcomes {
+                if helpers::referendum_event(events.iter(), name)? == Some(referendum) {
+                    outcome = Some(name.to_string());
+                }
+            }
+            followed += 1;
+        }
+
+        let mut enacted = None;
+        if outcome.as_deref() == Some("Confirmed") {
+            println!("> Referendum #{referendum} confirmed, waiting for the enactment..");
+            while enacted.is_none() && followed < max_blocks {
+                let Some(block) = blocks.next().await else {
+                    break;
+                };
+                let block = block?;
+                if helpers::staking_configs_applied(&block.storage(), &changes).await? {
+                    enacted = Some(block.number());
+                }
+                followed += 1;
+            }
+        }
+
+        helpers::render(
+            &configs,
+            &output::OpenGov {
+                call: helpers::call_name(&helpers::bundled_metadata()?, &call),
+                preimage_hash,
+                referendum,
+                origin,
+                track,
+                votes,
+                outcome: outcome.clone(),
+                enacted,
+            },
+        )
+        .await?;
+        match (outcome, enacted) {
+            (Some(_), Some(_)) => {}
+            (Some(outcome), None) if outcome == "Confirmed" => {
+                bail!("referendum #{referendum} confirmed but not enacted in {max_blocks} blocks")
+            }
+            (Some(outcome), None) => bail!("referendum #{referendum} {outcome}"),
+            (None, _) => bail!("referendum #{referendum} still ongoing after {max_blocks} blocks"),
+        }
+
+        Ok(configs)
+    }
+
+    /// Schedules `call` at the block `at` resolves to with `scheduler.schedule_named`, wrapped
+    /// in `sudo.sudo`, once per origin: Root, `as_account`, or with `managed` the controllers of
+    /// the bonded CLI-created stakers, through `utility.dispatch_as`. The runtime this CLI is
+    /// built for has no scheduler, so the scheduler calls are encoded from the chain metadata.
+    pub(crate) async fn schedule(
+        at: Schedule,
+        managed: bool,
+        as_account: Option<AccountId32>,
+        priority: u8,
+        call: EncodeCall,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use staking_parachain::runtime_types::frame_support::dispatch::RawOrigin;
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let metadata = api.metadata();
+        if metadata.pallet_by_name("Scheduler").is_none() {
+            bail!("{} has no Scheduler pallet", configs.url);
+        }
+        let now = api.blocks().at_latest().await?.number();
+        let when = helpers::schedule_target(&configs, &at, now)?;
+        if when <= now {
+            bail!("block {when} is not in the future, the chain is at block {now}");
+        }
+
+        let call = helpers::staking_call(call)?;
+        let call_name = helpers::call_name(&helpers::bundled_metadata()?, &call);
+        // the chain runs another runtime than the bundled one, the task is encoded with its
+        // pallet and call indices.
+        let call = helpers::chain_call(&metadata, &call)?;
+        let system = metadata
+            .pallet_by_name("System")
+            .ok_or(eyre!("the runtime has no System pallet"))?
+            .index();
+        let origins = if managed {
+            let mut controllers = vec![];
+            for account in State::load_for(&configs).await?.accounts.iter() {
+                if helpers::staker_status(&api, &configs, &account.account_id())
+                    .await?
+                    .bonded
+                {
+                    controllers.push(Some(account.controller_id()));
+                }
+            }
+            controllers
+        } else {
+            vec![as_account]
+        };
+        if origins.is_empty() {
+            bail!("no bonded CLI-created staker to schedule the call for");
+        }
+
+        let mut tasks = vec![];
+        let mut schedule_calls = vec![];
+        for origin in origins {
+            let task = match origin.clone() {
+                Some(who) => {
+                    // `OriginCaller::system` is indexed by the System pallet.
+                    let mut args = vec![system];
+                    args.extend(RawOrigin::Signed(who).encode());
+                    args.extend(call.clone());
+                    helpers::raw_call(&metadata, "Utility", "dispatch_as", args)?
+                }
+                None => call.clone(),
+            };
+            let id = sp_core::hashing::blake2_256(&(*b"staking-paras-cli", when, &task).encode());
+            let mut args = (id, when, None::<(u32, u32)>, priority).encode();
+            args.extend(task);
+            schedule_calls.push(helpers::raw_call(
+                &metadata,
+                "Scheduler",
+                "schedule_named",
+                args,
+            )?);
+            tasks.push(output::ScheduledTask {
+                id: H256(id),
+                origin: origin.map_or("Root".to_string(), |who| who.to_string()),
+            });
+        }
+
+        let call = match schedule_calls.len() {
+            1 => schedule_calls.remove(0),
+            n => {
+                let mut calls = subxt::ext::codec::Compact(n as u32).encode();
+                calls.extend(schedule_calls.concat());
+                helpers::raw_call(&metadata, "Utility", "batch_all", calls)?
+            }
+        };
+        let sudo = helpers::raw_call(&metadata, "Sudo", "sudo", call)?;
+        println!("> Scheduling {} task(s) at block {when}..", tasks.len());
+        let submission =
+            helpers::submit_encoded(&api, &configs, helpers::RawCall(sudo), &configs.signer()?)
+                .await?;
+        let events = helpers::wait_for_success(&configs, submission).await?;
+        if let Some(Sudid {
+            sudo_result: Err(e),
+        }) = events.find_first::<Sudid>()?
+        {
+            bail!("sudo call failed: {e:?}");
+        }
+
+        helpers::render(
+            &configs,
+            &output::Scheduled {
+                when,
+                call: call_name,
+                tasks,
+            },
+        )
+        .await?;
+
+        Ok(configs)
+    }
+
+    /// Generates `n_validators` validators and `n_nominators` nominators and writes the balances,
+    /// staking and (optionally) session genesis config patch with them to `out`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn genesis(
+        n_validators: usize,
+        n_nominators: usize,
+        bond_amount: Balance,
+        nominations: usize,
+        validator_count: Option<u32>,
+        invulnerables: usize,
+        session_keys: bool,
+        out: Option<PathBuf>,
+        state_path: PathBuf,
+    ) -> color_eyre::Result<()> {
+        use serde_json::json;
+
+        let validators: Vec<state::ManagedAccount> = (0..n_validators)
+            .map(|_| state::ManagedAccount::generate(Role::Validator))
+            .collect();
+        let nominators: Vec<state::ManagedAccount> = (0..n_nominators)
+            .map(|_| state::ManagedAccount::generate(Role::Nominator))
+            .collect();
+        let validator_ids: Vec<AccountId32> = validators.iter().map(|v| v.account_id()).collect();
+
+        let balances: Vec<_> = validator_ids
+            .iter()
+            .cloned()
+            .chain(nominators.iter().map(|n| n.account_id()))
+            .map(|who| json!([who, bond_amount * 2]))
+            .collect();
+
+        let mut rng = rand::thread_rng();
+        let stakers: Vec<_> = validator_ids
+            .iter()
+            .map(|v| json!([v, bond_amount, "Validator"]))
+            .chain(nominators.iter().map(|n| {
+                let targets: Vec<&AccountId32> = validator_ids
+                    .choose_multiple(&mut rng, nominations)
+                    .collect();
+                json!([n.account_id(), bond_amount, { "Nominator": targets }])
+            }))
+            .collect();
+
+        let mut patch = json!({
+            "balances": { "balances": balances },
+            "staking": {
+                "validatorCount": validator_count.unwrap_or(n_validators as u32),
+                "invulnerables": validator_ids.iter().take(invulnerables).collect::<Vec<_>>(),
+                "stakers": stakers,
+            },
+        });
+        if session_keys {
+            let keys: Vec<_> = validator_ids
+                .iter()
+                .map(|v| json!([v, v, { "aura": v }]))
+                .collect();
+            patch["session"] = json!({ "keys": keys });
+        }
+
+        let patch = serde_json::to_string_pretty(&patch)?;
+        match out {
+            Some(path) => {
+                std::fs::write(&path, patch)?;
+                println!(
+                    "> Genesis patch with {n_validators} validators and {n_nominators} nominators written to {}.",
+                    path.display()
+                );
+            }
+            None => println!("{patch}"),
+        }
+
+        let mut state = State::load(&state_path)?;
+        state
+            .accounts
+            .extend(validators.into_iter().chain(nominators));
+        state.save(&state_path)?;
+
+        Ok(())
+    }
+
+    /// Generates `n_validators` validators and `n_nominators` nominators, each bonding
+    /// `bond_amount` and with a distinct controller if `separate_controller`, and injects them
+    /// through sudo `system.set_storage`.
+    pub(crate) async fn inject(
+        n_validators: usize,
+        n_nominators: usize,
+        bond_amount: Balance,
+        nominations: usize,
+        chunk_size: usize,
+        separate_controller: bool,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let generate = match separate_controller {
+            true => state::ManagedAccount::generate_with_controller,
+            false => state::ManagedAccount::generate,
+        };
+
+        let validators: Vec<helpers::PlannedStaker> = (0..n_validators)
+            .map(|_| helpers::PlannedStaker {
+                account: generate(Role::Validator),
+                bond: bond_amount,
+                targets: vec![],
+            })
+            .collect();
+        let mut targets: Vec<AccountId32> = helpers::get_validators(&api, &configs)
+            .await?
+            .into_iter()
+            .filter_map(|t| match t {
+                Id(who) => Some(who),
+                _ => None,
+            })
+            .collect();
+        targets.extend(validators.iter().map(|v| v.account.account_id()));
+
+        let mut rng = rand::thread_rng();
+        let nominators = (0..n_nominators).map(|_| helpers::PlannedStaker {
+            account: generate(Role::Nominator),
+            bond: bond_amount,
+            targets: targets
+                .choose_multiple(&mut rng, nominations)
+                .cloned()
+                .collect(),
+        });
+        let stakers: Vec<helpers::PlannedStaker> =
+            validators.into_iter().chain(nominators).collect();
+
+        helpers::inject_stakers(&api, &configs, stakers, chunk_size).await?;
+
+        Ok(configs)
+    }
+
+    /// Scrapes the validators and nominators of the network at `from`, samples `n_validators`
+    /// and `n_nominators` of them and injects an equivalent population: bonds are scaled by
+    /// `scale` (and raised to the minimum bonds) and nominations point to the clones of the
+    /// nominated validators.
+    pub(crate) async fn clone_stakers(
+        from: String,
+        n_validators: usize,
+        n_nominators: usize,
+        scale: f64,
+        chunk_size: usize,
+        configs: Configs,
+    ) -> color_eyre::Result<Configs> {
+        use std::collections::BTreeMap;
+        use subxt::{dynamic::Value, PolkadotConfig};
+
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let source = OnlineClient::<PolkadotConfig>::from_url(&from).await?;
+        let source_storage = source.storage().at_latest().await?;
+
+        println!("> Scraping stakers from {from}..");
+        let (source_validators, source_nominators) =
+            helpers::scrape_stakers(&source_storage).await?;
+        println!(
+            " {} validators and {} nominators found, sampling {n_validators} and {n_nominators}.",
+            source_validators.len(),
+            source_nominators.len()
+        );
+
+        let mut rng = rand::thread_rng();
+        let sampled_validators: Vec<&AccountId32> = source_validators
+            .choose_multiple(&mut rng, n_validators)
+            .collect();
+        let sampled_nominators: Vec<&(AccountId32, Vec<AccountId32>)> = source_nominators
+            .choose_multiple(&mut rng, n_nominators)
+            .collect();
+
+        let storage = helpers::storage_at(&api, &configs).await?;
+        let staking = staking_parachain::storage().staking();
+        let min_validator_bond = storage
+            .fetch_or_default(&staking.min_validator_bond())
+            .await?;
+        let min_nominator_bond = storage
+            .fetch_or_default(&staking.min_nominator_bond())
+            .await?;
+        let scaled_bond = |stash: &AccountId32, min_bond: Balance| {
+            let source_storage = &source_storage;
+            let stash = stash.clone();
+            async move {
+                // the ledger is keyed by the controller of the stash.
+                let controller = source_storage
+                    .fetch(&subxt::dynamic::storage(
+                        "Staking",
+                        "Bonded",
+                        vec![Value::from_bytes(stash.0)],
+                    ))
+                    .await?;
+                let ledger = match controller {
+                    Some(controller) => {
+                        let controller = controller.as_type::<AccountId32>()?;
+                        source_storage
+                            .fetch(&subxt::dynamic::storage(
+                                "Staking",
+                                "Ledger",
+                                vec![Value::from_bytes(controller.0)],
+                            ))
+                            .await?
+                    }
+                    None => None,
+                };
+                let active = match ledger {
+                    Some(ledger) => ledger.as_type::<helpers::SourceLedger>()?.active,
+                    None => 0,
+                };
+                color_eyre::Result::<Balance>::Ok(
+                    ((active as f64 * scale) as Balance).max(min_bond),
+                )
+            }
+        };
+
+        let mut stakers = vec![];
+        let mut clones = BTreeMap::new();
+        for source_validator in sampled_validators {
+            let account = state::ManagedAccount::generate(Role::Validator);
+            clones.insert(source_validator.clone(), account.account_id());
+            stakers.push(helpers::PlannedStaker {
+                bond: scaled_bond(source_validator, min_validator_bond).await?,
+                account,
+                targets: vec![],
+            });
+        }
+        let cloned_validators: Vec<AccountId32> = clones.values().cloned().collect();
+        for (source_nominator, source_targets) in sampled_nominators {
+            let mut targets: Vec<AccountId32> = source_targets
+                .iter()
+                .filter_map(|t| clones.get(t).cloned())
+                .collect();
+            // none of the nominated validators was sampled, nominate as many random clones.
+            if targets.is_empty() {
+                targets = cloned_validators
+                    .choose_multiple(&mut rng, source_targets.len().max(1))
+                    .cloned()
+                    .collect();
+            }
+            stakers.push(helpers::PlannedStaker {
+                account: state::ManagedAccount::generate(Role::Nominator),
+                bond: scaled_bond(source_nominator, min_nominator_bond).await?,
+                targets,
+            });
+        }
+
+        helpers::inject_stakers(&api, &configs, stakers, chunk_size).await?;
+
+        Ok(configs)
+    }
+
+    /// Diffs the staking state of the chains at `a` and `b` and reports the mismatches. Stake
+    /// statistics are mismatched when they differ by more than `tolerance` %.
+    pub(crate) async fn compare(
+        a: String,
+        b: String,
+        tolerance: f64,
+        format: output::OutputFormat,
+    ) -> color_eyre::Result<()> {
+        use std::collections::BTreeSet;
+
+        let mut summaries = vec![];
+        let mut validator_sets = vec![];
+        let mut nominator_sets = vec![];
+        for url in [&a, &b] {
+            eprintln!("> Scraping stakers from {url}..");
+            let storage = OnlineClient::<subxt::PolkadotConfig>::from_url(url)
+                .await?
+                .storage()
+                .at_latest()
+                .await?;
+            let (validators, nominators) = helpers::scrape_stakers(&storage).await?;
+            let stakes = helpers::scrape_stakes(&storage).await?;
+
+            summaries.push(output::ChainSummary::new(
+                url.clone(),
+                validators.len(),
+                nominators.len(),
+                stakes,
+            ));
+            validator_sets.push(validators.into_iter().collect::<BTreeSet<_>>());
+            nominator_sets.push(
+                nominators
+                    .into_iter()
+                    .map(|(who, _)| who)
+                    .collect::<BTreeSet<_>>(),
+            );
+        }
+
+        let validators_only_in_a: Vec<AccountId32> = validator_sets[0]
+            .difference(&validator_sets[1])
+            .cloned()
+            .collect();
+        let validators_only_in_b: Vec<AccountId32> = validator_sets[1]
+            .difference(&validator_sets[0])
+            .cloned()
+            .collect();
+        let nominators_only_in_a = nominator_sets[0].difference(&nominator_sets[1]).count();
+        let nominators_only_in_b = nominator_sets[1].difference(&nominator_sets[0]).count();
+
+        let (sa, sb) = (&summaries[0], &summaries[1]);
+        let mut mismatches = vec![];
+        if !validators_only_in_a.is_empty() || !validators_only_in_b.is_empty() {
+            mismatches.push(format!(
+                "validator sets differ: {} only in a, {} only in b",
+                validators_only_in_a.len(),
+                validators_only_in_b.len()
+            ));
+        }
+        if nominators_only_in_a > 0 || nominators_only_in_b > 0 {
+            mismatches.push(format!(
+                "nominators differ: {nominators_only_in_a} only in a, {nominators_only_in_b} only in b"
+            ));
+        }
+        let stats = [
+            ("total stake", sa.total_stake, sb.total_stake),
+            ("min stake", sa.min_stake, sb.min_stake),
+            ("median stake", sa.median_stake, sb.median_stake),
+            ("max stake", sa.max_stake, sb.max_stake),
+        ];
+        for (stat, in_a, in_b) in stats {
+            let diff = (in_a as f64 - in_b as f64).abs() / (in_a.max(1) as f64) * 100.0;
+            if diff > tolerance {
+                mismatches.push(format!(
+                    "{stat} differs by {diff:.2}%: {in_a} in a, {in_b} in b"
+                ));
+            }
+        }
+
+        output::render(
+            format,
+            &output::ChainComparison {
+                a: summaries.remove(0),
+                b: summaries.remove(0),
+                validators_only_in_a,
+                validators_only_in_b,
+                nominators_only_in_a,
+                nominators_only_in_b,
+                mismatches,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Launches a zombienet network from `config` (or the bundled one) and waits until the
+    /// parachain at `url` produced `wait_blocks` blocks.
+    pub(crate) async fn spawn(
+        zombienet: String,
+        provider: String,
+        config: Option<PathBuf>,
+        url: &str,
+        wait_blocks: u32,
+    ) -> color_eyre::Result<helpers::Network> {
+        const BUNDLED_CONFIG: &str = include_str!("../zombienet/staking-parachain.toml");
+        const CONNECT_ATTEMPTS: u32 = 120;
+
+        let config = match config {
+            Some(config) => config,
+            None => {
+                let path = std::env::temp_dir().join("staking-cli-zombienet.toml");
+                std::fs::write(&path, BUNDLED_CONFIG)?;
+                path
+            }
+        };
+
+        println!("> Spawning network from {}..", config.display());
+        let mut network = helpers::Network(
+            std::process::Command::new(&zombienet)
+                .arg("-p")
+                .arg(&provider)
+                .arg("spawn")
+                .arg(&config)
+                .spawn()?,
+        );
+
+        let mut attempts = 0;
+        let api = loop {
+            if let Some(status) = network.0.try_wait()? {
+                bail!("zombienet exited before the parachain was up: {status}");
+            }
+            match OnlineClient::<SubstrateConfig>::from_url(url).await {
+                Ok(api) => break api,
+                Err(e) if attempts >= CONNECT_ATTEMPTS => {
+                    bail!("could not connect to the parachain at {url}: {e}")
+                }
+                Err(_) => {
+                    attempts += 1;
+                    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                }
+            }
+        };
+
+        println!("> Parachain is up, waiting for {wait_blocks} block(s)..");
+        let mut blocks = api.blocks().subscribe_best().await?;
+        for _ in 0..wait_blocks {
+            match blocks.next().await {
+                Some(block) => println!(" block #{}", block?.number()),
+                None => bail!("block subscription ended"),
+            }
+        }
+
+        Ok(network)
+    }
+
+    /// Runs the commands of `scenario` against `url` and, with `rehearse`, against a chopsticks
+    /// fork of it first. The rehearsal runs from a temporary directory holding copies of the
+    /// state and index files, including the ones the commands pass, so that the accounts it
+    /// creates are not recorded.
+    pub(crate) async fn run_scenario(
+        scenario: PathBuf,
+        url: String,
+        rehearse: bool,
+        chopsticks: String,
+        fork_port: u16,
+    ) -> color_eyre::Result<()> {
+        const CONNECT_ATTEMPTS: u32 = 60;
+
+        let lines = helpers::scenario_lines(&std::fs::read_to_string(&scenario)?);
+
+        if rehearse {
+            let fork_url = format!("ws://127.0.0.1:{fork_port}");
+            println!("> Rehearsing {} on a fork of {url}..", scenario.display());
+            let _fork = helpers::Network(
+                std::process::Command::new(&chopsticks)
+                    .arg("--endpoint")
+                    .arg(&url)
+                    .arg("--port")
+                    .arg(fork_port.to_string())
+                    .spawn()?,
+            );
+            let mut attempts = 0;
+            while let Err(e) = OnlineClient::<SubstrateConfig>::from_url(&fork_url).await {
+                if attempts >= CONNECT_ATTEMPTS {
+                    bail!("could not connect to the fork at {fork_url}: {e}");
+                }
+                attempts += 1;
+                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            }
+
+            let cwd = std::env::current_dir()?;
+            let rehearsal = std::env::temp_dir().join(format!("staking-cli-rehearsal-{fork_port}"));
+            std::fs::create_dir_all(&rehearsal)?;
+            for file in ["staking-cli-state.json", "staking-cli-index.json"] {
+                if cwd.join(file).exists() {
+                    std::fs::copy(cwd.join(file), rehearsal.join(file))?;
+                }
+            }
+            let rehearsed = helpers::rehearsal_lines(&lines, &rehearsal)?;
+            std::env::set_current_dir(&rehearsal)?;
+            let result = helpers::run_lines(&rehearsed, &fork_url, true).await;
+            std::env::set_current_dir(&cwd)?;
+            result.map_err(|e| eyre!("rehearsal failed, not running against {url}: {e}"))?;
+            println!("> Rehearsal passed.");
+        }
+
+        println!("> Running {} against {url}..", scenario.display());
+        helpers::run_lines(&lines, &url, false).await
+    }
+
+    /// Runs a population command whose `--url` lists the endpoints of several chains against
+    /// all of them concurrently. Each chain gets its own state, index and offline files, suffixed
+    /// with its spec name and genesis hash, and its own status.
+    pub(crate) async fn multi_chain(mut command: Command) -> color_eyre::Result<()> {
+        use subxt::ext::futures::future::join_all;
+
+        let Some(configs) = command.population_configs_mut() else {
+            bail!("only validate, nominate and clone-stakers accept several --url endpoints");
+        };
+        let configs = configs.clone();
+        let urls: Vec<&str> = configs
+            .url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        let mut runs = vec![];
+        let mut chains = vec![];
+        let mut commands = vec![];
+        for url in urls {
+            let mut chain_configs = configs.clone();
+            chain_configs.url = url.to_string();
+            let rpc = helpers::legacy_rpc(&chain_configs).await?;
+            let genesis = rpc.genesis_hash().await?;
+            let spec = rpc
+                .state_get_runtime_version(None)
+                .await?
+                .other
+                .get("specName")
+                .and_then(|name| name.as_str().map(str::to_string))
+                .unwrap_or_else(|| "chain".to_string());
+            if let Some((other, _)) = chains.iter().find(|(_, g)| *g == genesis) {
+                bail!("{url} and {other} are endpoints of the same chain {genesis:?}");
+            }
+            chains.push((url.to_string(), genesis));
+
+            let chain = format!("{spec}-{}", &hex::encode(genesis.0)[..8]);
+            chain_configs.state = helpers::chain_path(&configs.state, &chain);
+            chain_configs.index = helpers::chain_path(&configs.index, &chain);
+            chain_configs.offline = configs
+                .offline
+                .as_ref()
+                .map(|path| helpers::chain_path(path, &chain));
+            chain_configs.status = Default::default();
+            runs.push(output::ChainRun {
+                url: url.to_string(),
+                chain,
+                state: chain_configs.state.clone(),
+                error: None,
+            });
+
+            let mut chain_command = command.clone();
+            *chain_command
+                .population_configs_mut()
+                .expect("population command has configs") = chain_configs;
+            commands.push(chain_command);
+        }
+
+        println!("> Running against {} chains..", commands.len());
+        let results = join_all(commands.into_iter().map(|command| Box::pin(run(command)))).await;
+        for (run, result) in runs.iter_mut().zip(results) {
+            run.error = result.err().map(|e| e.to_string());
+        }
+        let failed = runs.iter().filter(|run| run.error.is_some()).count();
+        output::render(configs.output, &output::ChainRuns(runs))?;
+        if failed > 0 {
+            bail!("the command failed on {failed} chain(s)");
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn playground(configs: Configs) -> color_eyre::Result<Configs> {
+        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+        let _current_validators = helpers::get_validators(&api, &configs).await?;
+        Ok(configs)
+    }
+}
+
+mod helpers {
+    use super::*;
+    use std::io::Write;
+
+    /// A network spawned by zombienet, killed on drop.
+    pub(crate) struct Network(pub(crate) std::process::Child);
+
+    impl Network {
+        /// Blocks until zombienet exits.
+        pub(crate) fn wait(&mut self) -> color_eyre::Result<()> {
+            self.0.wait()?;
+            Ok(())
+        }
+    }
+
+    impl Drop for Network {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// The scenario `lines` with the files they pass to `--state` and `--index` replaced with
+    /// copies in the rehearsal directory `dir`, so that a rehearsal does not write to them.
+    pub(crate) fn rehearsal_lines(
+        lines: &[String],
+        dir: &std::path::Path,
+    ) -> color_eyre::Result<Vec<String>> {
+        let mut copies = std::collections::BTreeMap::new();
+        let mut rehearsed = vec![];
+        for line in lines {
+            let mut words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+            for i in 0..words.len() {
+                let (flag, inline) = match words[i].split_once('=') {
+                    Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                    None => (words[i].clone(), None),
+                };
+                if flag != "--state" && flag != "--index" {
+                    continue;
+                }
+                let Some(path) = inline.clone().or_else(|| words.get(i + 1).cloned()) else {
+                    continue;
+                };
+                let original = PathBuf::from(&path);
+                let copy = match copies.get(&original) {
+                    Some(copy) => PathBuf::clone(copy),
+                    None => {
+                        let name = format!("{}-{}", copies.len(), flag.trim_start_matches('-'));
+                        let copy = dir.join(name);
+                        // drops the copy of a previous rehearsal.
+                        let _ = std::fs::remove_file(&copy);
+                        if original.exists() {
+                            std::fs::copy(&original, &copy)?;
+                        }
+                        copies.insert(original, copy.clone());
+                        copy
+                    }
+                };
+                match inline {
+                    Some(_) => words[i] = format!("{flag}={}", copy.display()),
+                    None => words[i + 1] = copy.display().to_string(),
+                }
+            }
+            rehearsed.push(words.join(" "));
+        }
+
+        Ok(rehearsed)
+    }
+
+    /// Parses and runs the scenario command `lines` in order, adding `--url` (and
+    /// `--fork-mode` with `fork_mode`) to the commands running against a chain.
+    pub(crate) async fn run_lines(
+        lines: &[String],
+        url: &str,
+        fork_mode: bool,
+    ) -> color_eyre::Result<()> {
+        let start = OnlineClient::<SubstrateConfig>::from_url(url)
+            .await?
+            .blocks()
+            .at_latest()
+            .await?
+            .number()
+            .to_string();
+        for (i, line) in lines.iter().enumerate() {
+            let words = std::iter::once("staking-cli").chain(line.split_whitespace());
+            let mut extra = vec!["--url", url];
+            if fork_mode {
+                extra.push("--fork-mode");
+            }
+            if line.starts_with("assert ") && !line.contains("--since") {
+                extra.extend(["--since", start.as_str()]);
+            }
+            let command = Command::from_iter_safe(words.clone().chain(extra))
+                .or_else(|_| Command::from_iter_safe(words))
+                .map_err(|e| eyre!("scenario command `{line}`: {e}"))?;
+
+            println!("> [{}/{}] {line}", i + 1, lines.len());
+            Box::pin(run(command))
+                .await
+                .map_err(|e| eyre!("scenario command `{line}` failed: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// The commands of a scenario file, skipping empty lines and `#` comments. `expect: <a>`
+    /// lines, and the indented lines of an `expect:` block, become `assert <a>` commands.
+    pub(crate) fn scenario_lines(scenario: &str) -> Vec<String> {
+        let mut lines = vec![];
+        let mut in_expect = false;
+        for raw in scenario.lines() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if in_expect && raw.starts_with(char::is_whitespace) {
+                lines.push(format!("assert {line}"));
+                continue;
+            }
+            in_expect = false;
+            match line.strip_prefix("expect:").map(str::trim) {
+                Some("") => in_expect = true,
+                Some(assertion) => lines.push(format!("assert {assertion}")),
+                None => lines.push(line.to_string()),
+            }
+        }
+
+        lines
+    }
+
+    /// Evaluates the `;` separated `assertions`, the failed extrinsics being counted from block
+    /// `since`. Metrics that cannot be read fail their assertion.
+    pub(crate) async fn check_assertions(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        assertions: &str,
+        since: Option<u32>,
+    ) -> color_eyre::Result<Vec<output::AssertionResult>> {
+        let assertions = assertions
+            .split(';')
+            .filter(|a| !a.trim().is_empty())
+            .map(|a| a.parse::<Assertion>().map_err(|e| eyre!(e)))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        let mut results = vec![];
+        for assertion in assertions {
+            let (actual, error) = match metric(api, configs, &assertion.metric, since).await {
+                Ok(actual) => (Some(actual), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            results.push(output::AssertionResult {
+                passed: actual.map_or(false, |a| assertion.op.holds(a, assertion.value)),
+                assertion: assertion.to_string(),
+                actual,
+                error,
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Current value of an `assert` metric. `failed_extrinsics` counts the failed extrinsics,
+    /// including the batches with failed items, signed by the CLI-created stashes or controllers
+    /// from block `since` on.
+    pub(crate) async fn metric(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        metric: &str,
+        since: Option<u32>,
+    ) -> color_eyre::Result<u128> {
+        use staking_parachain::system::events::ExtrinsicFailed;
+        use subxt::ext::codec::Decode;
+
+        let storage = storage_at(api, configs).await?;
+        let staking = staking_parachain::storage().staking();
+        let queued = if metric.starts_with("queued_score.") {
+            queued_score(api, configs).await?.map(|(_, score)| score)
+        } else {
+            None
+        };
+        let no_queued = || eyre!("no election solution is queued");
+
+        let value = match metric {
+            "validator_count" => {
+                storage.fetch_or_default(&staking.validator_count()).await? as u128
+            }
+            "validators" => {
+                storage
+                    .fetch_or_default(&staking.counter_for_validators())
+                    .await? as u128
+            }
+            "nominators" => {
+                storage
+                    .fetch_or_default(&staking.counter_for_nominators())
+                    .await? as u128
+            }
+            "active_era" => active_era(api, configs).await? as u128,
+            "current_era" => storage
+                .fetch(&staking.current_era())
+                .await?
+                .unwrap_or_default() as u128,
+            "min_validator_bond" => {
+                storage
+                    .fetch_or_default(&staking.min_validator_bond())
+                    .await?
+            }
+            "min_nominator_bond" => {
+                storage
+                    .fetch_or_default(&staking.min_nominator_bond())
+                    .await?
+            }
+            "managed_accounts" => state::State::load_for(configs).await?.accounts.len() as u128,
+            "queued_score.minimal_stake" => queued.ok_or_else(no_queued)?.minimal_stake,
+            "queued_score.sum_stake" => queued.ok_or_else(no_queued)?.sum_stake,
+            "queued_score.sum_stake_squared" => queued.ok_or_else(no_queued)?.sum_stake_squared,
+            "failed_extrinsics" => {
+                let Some(since) = since else {
+                    bail!("failed_extrinsics needs --since");
+                };
+                let managed: std::collections::BTreeSet<AccountId32> =
+                    state::State::load_for(configs)
+                        .await?
+                        .accounts
+                        .iter()
+                        .flat_map(|a| [a.account_id(), a.controller_id()])
+                        .collect();
+                let latest = api.blocks().at_latest().await?.number();
+
+                let mut failed = 0;
+                let rpc = legacy_rpc(configs).await?;
+                for number in since..=latest {
+                    let block = api.blocks().at(block_hash(&rpc, number).await?).await?;
+                    for extrinsic in block.extrinsics().await?.iter() {
+                        let extrinsic = extrinsic?;
+                        let signer = extrinsic.address_bytes().and_then(|mut address| {
+                            MultiAddress::<AccountId32, ()>::decode(&mut address).ok()
+                        });
+                        let Some(Id(signer)) = signer else {
+                            continue;
+                        };
+                        if !managed.contains(&signer) {
+                            continue;
+                        }
+                        let events = extrinsic.events().await?;
+                        if events.has::<ExtrinsicFailed>()?
+                            || !batch_failures(&events, 0)?.is_empty()
+                        {
+                            failed += 1;
+                        }
+                    }
+                }
+                failed
+            }
+            _ => bail!("unknown metric {metric}"),
+        };
+
+        Ok(value)
+    }
+
+    /// Checks that creating `n_new` stakers with `role` bonding `bond_amount` each, and funded
+    /// with `funds` in total, is feasible and prints a feasibility report. Refuses to start if
+    /// any limit would be violated, unless `--force` is given.
+    pub(crate) async fn preflight_capacity(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        funder: &Keypair,
+        role: state::Role,
+        n_new: usize,
+        bond_amount: Balance,
+        funds: Balance,
+    ) -> color_eyre::Result<()> {
+        let storage = storage_at(api, configs).await?;
+        let staking = staking_parachain::storage().staking();
+        let constants = api.constants();
+        let n_new = n_new as u32;
+
+        let ed = constants.at(&staking_parachain::constants()
+            .balances()
+            .existential_deposit())?;
+        let (count, max_count, min_bond) = match role {
+            state::Role::Validator => (
+                storage
+                    .fetch_or_default(&staking.counter_for_validators())
+                    .await?,
+                storage.fetch(&staking.max_validators_count()).await?,
+                storage
+                    .fetch_or_default(&staking.min_validator_bond())
+                    .await?,
+            ),
+            state::Role::Nominator => (
+                storage
+                    .fetch_or_default(&staking.counter_for_nominators())
+                    .await?,
+                storage.fetch(&staking.max_nominators_count()).await?,
+                storage
+                    .fetch_or_default(&staking.min_nominator_bond())
+                    .await?,
+            ),
+        };
+        let voters = storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .voter_list()
+                    .counter_for_list_nodes(),
+            )
+            .await?;
+        let targets = storage
+            .fetch_or_default(&staking.counter_for_validators())
+            .await?;
+        let epm = staking_parachain::constants().election_provider_multi_block();
+        let max_voters =
+            constants.at(&epm.voter_snapshot_per_block())? * constants.at(&epm.pages())?;
+        let max_targets = constants.at(&epm.target_snapshot_per_block())?;
+        let free = staker_status(api, configs, &funder.public_key().into())
+            .await?
+            .free;
+
+        let mut checks = vec![
+            (
+                format!(
+                    "{role:?} count {} <= {}",
+                    count + n_new,
+                    max_count.map_or("unbounded".to_string(), |m| m.to_string())
+                ),
+                max_count.is_none_or(|m| count + n_new <= m),
+            ),
+            (
+                format!("bond {bond_amount} >= min bond {min_bond}"),
+                bond_amount >= min_bond,
+            ),
+            (format!("bond {bond_amount} >= ED {ed}"), bond_amount >= ed),
+            (
+                format!("election voters {} <= {max_voters}", voters + n_new),
+                voters + n_new <= max_voters,
+            ),
+            (
+                format!("funder balance {free} >= {}", funds.saturating_add(ed)),
+                configs.auto_topup || free >= funds.saturating_add(ed),
+            ),
+        ];
+        if role == state::Role::Validator {
+            checks.push((
+                format!("election targets {} <= {max_targets}", targets + n_new),
+                targets + n_new <= max_targets,
+            ));
+        }
+
+        println!("> Preflight check for {n_new} new {role:?}(s):");
+        for (check, ok) in checks.iter() {
+            println!("  [{}] {check}", if *ok { "ok" } else { "VIOLATED" });
+        }
+
+        let violated = checks.iter().filter(|(_, ok)| !ok).count();
+        if violated > 0 {
+            if !configs.force {
+                bail!("{violated} preflight check(s) failed, use --force to start anyway");
+            }
+            println!("{violated} preflight check(s) failed, starting anyway (--force).");
+        }
+
+        Ok(())
+    }
+
+    /// On-chain staking status of an account.
+    pub(crate) struct StakerStatus {
+        pub(crate) free: Balance,
+        pub(crate) bonded: bool,
+        pub(crate) validating: bool,
+        pub(crate) nominating: bool,
+    }
+
+    /// Fetches the free balance and staking status of `who`.
+    pub(crate) async fn staker_status(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        who: &AccountId32,
+    ) -> color_eyre::Result<StakerStatus> {
+        let storage = storage_at(api, configs).await?;
+        let staking = staking_parachain::storage().staking();
+
+        let free = storage
+            .fetch(&staking_parachain::storage().system().account(who))
+            .await?
+            .map(|info| info.data.free)
+            .unwrap_or_default();
+
+        Ok(StakerStatus {
+            free,
+            bonded: storage.fetch(&staking.bonded(who)).await?.is_some(),
+            validating: storage.fetch(&staking.validators(who)).await?.is_some(),
+            nominating: storage.fetch(&staking.nominators(who)).await?.is_some(),
+        })
+    }
+
+    /// Returns the stash and controller key pairs of `n` CLI-created stakers with `role`. Up to `n`
+    /// of the accounts already in the state file are reused, topping up the unbonded ones which are
+    /// short of funds. Missing accounts are taken first from the idle ones of the other role,
+    /// funded but unbonded e.g. after a `full-exit`, and only the remaining ones are generated and
+    /// funded.
+    pub(crate) async fn managed_stakers(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        role: state::Role,
+        n: usize,
+        bond_amount: Balance,
+    ) -> color_eyre::Result<Vec<(Keypair, Keypair)>> {
+        let funder = configs.signer()?;
+        let mut state = state::State::load_for(&configs).await?;
+
+        let mut idle = vec![];
+        let n_missing = n.saturating_sub(state.accounts_with_role(role).count());
+        for (i, account) in state.accounts.iter().enumerate() {
+            if idle.len() >= n_missing {
+                break;
+            }
+            if account.role == role {
+                continue;
+            }
+            let status = staker_status(api, configs, &account.account_id()).await?;
+            if !status.bonded && status.free > 0 {
+                idle.push(i);
+            }
+        }
+        if !idle.is_empty() {
+            println!(
+                " {} idle account(s) found in {}, reusing them as {role:?}.",
+                idle.len(),
+                configs.state.display()
+            );
+            for i in idle {
+                state.accounts[i].role = role;
+            }
+            state.save(&configs.state)?;
+        }
+
+        let existing: Vec<(Keypair, Keypair)> = state
+            .accounts_with_role(role)
+            .take(n)
+            .map(|a| (a.keypair(), a.controller_keypair()))
+            .collect();
+        if !existing.is_empty() {
+            println!(
+                " {} {role:?} account(s) found in {}, reusing them.",
+                existing.len(),
+                configs.state.display()
+            );
+        }
+
+        let mut to_top_up = vec![];
+        for (pair, _) in existing.iter() {
+            let who: AccountId32 = pair.public_key().into();
+            let status = staker_status(api, configs, &who).await?;
+            if !status.bonded {
+                to_top_up.push((who, status.free));
+            }
+        }
+        let n_new = n.saturating_sub(existing.len());
+        let top_up_needed = to_top_up
+            .iter()
+            .map(|(_, free)| (bond_amount * 2).saturating_sub(*free))
+            .sum::<Balance>();
+        preflight_capacity(
+            api,
+            configs,
+            &funder,
+            role,
+            n_new,
+            bond_amount,
+            (bond_amount * 2).saturating_mul(n_new as Balance) + top_up_needed,
+        )
+        .await?;
+
+        let topped_up = top_up(api, configs, &funder, &to_top_up, bond_amount * 2).await?;
+        if topped_up > 0 {
+            println!("Topping up done for {topped_up} staker(s).");
+        }
+
+        let created =
+            fund_accounts(api, configs, &funder, n_new, Some(bond_amount * 2), role).await?;
+        println!("Minting done for {} stakers.", created.len());
+
+        state.accounts.extend(created.clone());
+        state.save(&configs.state)?;
+
+        Ok(existing
+            .into_iter()
+            .chain(
+                created
+                    .iter()
+                    .map(|a| (a.keypair(), a.controller_keypair())),
+            )
+            .collect())
+    }
+
+    /// Transfers from `funder` to every account whose free balance is below `amount` the
+    /// difference. Returns how many accounts were topped up.
+    pub(crate) async fn top_up(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        funder: &Keypair,
+        accounts: &[(AccountId32, Balance)],
+        amount: Balance,
+    ) -> color_eyre::Result<usize> {
+        let calls: Vec<RuntimeCall> = accounts
+            .iter()
+            .filter(|(_, free)| *free < amount)
+            .map(|(who, free)| {
+                RuntimeCall::Balances(BalancesCall::transfer_allow_death {
+                    dest: Id(who.clone()),
+                    value: amount - free,
+                })
+            })
+            .collect();
+        if calls.is_empty() {
+            return Ok(0);
+        }
+
+        let needed = accounts
+            .iter()
+            .map(|(_, free)| amount.saturating_sub(*free))
+            .sum();
+        ensure_funds(api, configs, funder, needed).await?;
+
+        let n_calls = calls.len();
+        let progress = submit(api, configs, batch(configs, calls), funder).await?;
+        let failed = match wait_for_outcome(configs, progress).await? {
+            Some(events) => batch_failures(&events, n_calls)?.len(),
+            None => 0,
+        };
+
+        Ok(n_calls - failed)
+    }
+
+    /// Makes sure `funder` can transfer `needed` plus the existential deposit. With
+    /// `--auto-topup`, a funder running low is minted twice the missing funds through
+    /// `balances.force_set_balance`, as long as the signer is the sudo key.
+    pub(crate) async fn ensure_funds(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        funder: &Keypair,
+        needed: Balance,
+    ) -> color_eyre::Result<()> {
+        let who: AccountId32 = funder.public_key().into();
+        let ed = api.constants().at(&staking_parachain::constants()
+            .balances()
+            .existential_deposit())?;
+        let free = staker_status(api, configs, &who).await?.free;
+        let required = needed.saturating_add(ed);
+        if free >= required {
+            return Ok(());
+        }
+
+        if !configs.auto_topup {
+            bail!(
+                "funder {who} has {free} free but {required} are required, use --auto-topup to mint more"
+            );
+        }
+
+        let sudo_key = storage_at(api, configs)
+            .await?
+            .fetch(&staking_parachain::storage().sudo().key())
+            .await?;
+        let signer = configs.signer()?;
+        let signer_id: AccountId32 = signer.public_key().into();
+        if sudo_key.as_ref() != Some(&signer_id) {
+            bail!(
+                "funder {who} is low on funds and the signer is not the sudo key, cannot top it up"
+            );
+        }
+
+        let new_free = free.saturating_add((required - free).saturating_mul(2));
+        println!("> Funder {who} is low on funds ({free}), minting up to {new_free}..");
+        submit_sudo(
+            api,
+            configs,
+            RuntimeCall::Balances(BalancesCall::force_set_balance {
+                who: Id(who),
+                new_free,
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Id of the balance lock of the staked funds.
+    pub(crate) const STAKING_LOCK: [u8; 8] = *b"staking ";
+
+    /// Storage of a chain whose metadata is not known at compile time.
+    pub(crate) type DynamicStorage =
+        Storage<subxt::PolkadotConfig, OnlineClient<subxt::PolkadotConfig>>;
+
+    /// The part of a ledger which is read from chains with unknown metadata.
+    #[derive(subxt::ext::scale_decode::DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub(crate) struct SourceLedger {
+        pub(crate) active: u128,
+    }
+
+    /// A delegation of `DelegatedStaking`, read without static metadata.
+    #[derive(subxt::ext::scale_decode::DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub(crate) struct Delegation {
+        pub(crate) agent: AccountId32,
+        pub(crate) amount: u128,
+    }
+
+    /// The ledger of an agent of `DelegatedStaking`, read without static metadata.
+    #[derive(subxt::ext::scale_decode::DecodeAsType)]
+    #[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+    pub(crate) struct AgentLedger {
+        pub(crate) payee: AccountId32,
+        pub(crate) total_delegated: u128,
+        pub(crate) unclaimed_withdrawals: u128,
+        pub(crate) pending_slash: u128,
+    }
+
+    /// Fails unless the runtime has the `DelegatedStaking` pallet, which the bundled metadata
+    /// does not know about.
+    fn ensure_delegated_staking(api: &OnlineClient<SubstrateConfig>) -> color_eyre::Result<()> {
+        if api.metadata().pallet_by_name("DelegatedStaking").is_none() {
+            bail!("the runtime has no DelegatedStaking pallet");
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the agents of `DelegatedStaking` with their ledger.
+    pub(crate) async fn agents(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<Vec<(AccountId32, AgentLedger)>> {
+        ensure_delegated_staking(api)?;
+
+        let mut agents = vec![];
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "DelegatedStaking",
+                "Agents",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((key, ledger))) = results.next().await {
+            agents.push((account_from_key(&key), ledger.as_type()?));
+        }
+
+        Ok(agents)
+    }
+
+    /// Fetches the delegators of `DelegatedStaking` with their delegation.
+    pub(crate) async fn delegations(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<Vec<(AccountId32, Delegation)>> {
+        ensure_delegated_staking(api)?;
+
+        let mut delegations = vec![];
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "DelegatedStaking",
+                "Delegators",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((key, delegation))) = results.next().await {
+            delegations.push((account_from_key(&key), delegation.as_type()?));
+        }
+
+        Ok(delegations)
+    }
+
+    /// The balance of `who` on hold for `reason`, the pallet variant of the runtime hold reason.
+    pub(crate) async fn held(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        who: &AccountId32,
+        reason: &str,
+    ) -> color_eyre::Result<Balance> {
+        let key = vec![subxt::dynamic::Value::from_bytes(who.0)];
+        let Some(holds) = storage
+            .fetch(&subxt::dynamic::storage("Balances", "Holds", key))
+            .await?
+        else {
+            return Ok(0);
+        };
+
+        Ok(hold_amount(&holds.to_value()?, reason))
+    }
+
+    /// Sums the amounts of the `IdAmount` holds in `value` whose id is a `reason` variant.
+    fn hold_amount(value: &subxt::ext::scale_value::Value<u32>, reason: &str) -> Balance {
+        use subxt::ext::scale_value::{At, ValueDef};
+
+        let ValueDef::Composite(composite) = &value.value else {
+            return 0;
+        };
+        match (value.at("id"), value.at("amount")) {
+            (Some(id), Some(amount)) => match &id.value {
+                ValueDef::Variant(variant) if variant.name == reason => {
+                    amount.as_u128().unwrap_or_default()
+                }
+                _ => 0,
+            },
+            _ => composite.values().map(|v| hold_amount(v, reason)).sum(),
+        }
+    }
+
+    /// Whether the runtime has the storage `entry` in `pallet`.
+    pub(crate) fn dynamic_entry_exists(
+        api: &OnlineClient<SubstrateConfig>,
+        pallet: &str,
+        entry: &str,
+    ) -> bool {
+        api.metadata()
+            .pallet_by_name(pallet)
+            .and_then(|pallet| pallet.storage())
+            .is_some_and(|storage| storage.entry_by_name(entry).is_some())
+    }
+
+    /// Fetches the value of `entry` in `pallet` under `keys` without static metadata. `None`
+    /// if the runtime has no such entry or it is not set.
+    pub(crate) async fn dynamic_entry(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        pallet: &str,
+        entry: &str,
+        keys: Vec<subxt::dynamic::Value>,
+    ) -> color_eyre::Result<Option<subxt::ext::scale_value::Value<u32>>> {
+        if !dynamic_entry_exists(api, pallet, entry) {
+            return Ok(None);
+        }
+
+        Ok(storage
+            .fetch(&subxt::dynamic::storage(pallet, entry, keys))
+            .await?
+            .map(|value| value.to_value())
+            .transpose()?)
+    }
+
+    /// The virtual stakers of staking, whose funds are not held by staking. Empty for runtimes
+    /// without virtual stakers.
+    pub(crate) async fn virtual_stakers(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<std::collections::BTreeSet<AccountId32>> {
+        let mut virtual_stakers = std::collections::BTreeSet::new();
+        if !dynamic_entry_exists(api, "Staking", "VirtualStakers") {
+            return Ok(virtual_stakers);
+        }
+
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "Staking",
+                "VirtualStakers",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((key, _))) = results.next().await {
+            virtual_stakers.insert(account_from_key(&key));
+        }
+
+        Ok(virtual_stakers)
+    }
+
+    /// The bonded accounts of all the nomination pools, with their pool id.
+    pub(crate) async fn pool_accounts(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<std::collections::BTreeMap<AccountId32, u32>> {
+        let last = storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .nomination_pools()
+                    .last_pool_id(),
+            )
+            .await?;
+
+        (1..=last)
+            .map(|pool_id| Ok((pool_account(api, pool_id)?, pool_id)))
+            .collect()
+    }
+
+    /// Fetches the validators and the nominators, with their targets, of a chain with unknown
+    /// metadata.
+    pub(crate) async fn scrape_stakers(
+        storage: &DynamicStorage,
+    ) -> color_eyre::Result<(Vec<AccountId32>, Vec<(AccountId32, Vec<AccountId32>)>)> {
+        use staking_parachain::runtime_types::pallet_staking::Nominations;
+
+        let mut validators = vec![];
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "Staking",
+                "Validators",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((key, _))) = results.next().await {
+            validators.push(account_from_key(&key));
+        }
+
+        let mut nominators = vec![];
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "Staking",
+                "Nominators",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((key, nominations))) = results.next().await {
+            let nominations: Nominations = nominations.as_type()?;
+            nominators.push((account_from_key(&key), nominations.targets.0));
+        }
+
+        Ok((validators, nominators))
+    }
+
+    /// Fetches the active stake of every ledger of a chain with unknown metadata.
+    pub(crate) async fn scrape_stakes(
+        storage: &DynamicStorage,
+    ) -> color_eyre::Result<Vec<Balance>> {
+        let mut stakes = vec![];
+        let mut results = storage
+            .iter(subxt::dynamic::storage(
+                "Staking",
+                "Ledger",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .await?;
+        while let Some(Ok((_, ledger))) = results.next().await {
+            stakes.push(ledger.as_type::<SourceLedger>()?.active);
+        }
+
+        Ok(stakes)
+    }
+
+    /// A staker to be injected into storage.
+    pub(crate) struct PlannedStaker {
+        pub(crate) account: state::ManagedAccount,
+        pub(crate) bond: Balance,
+        /// Nominated validators, empty for validators.
+        pub(crate) targets: Vec<AccountId32>,
+    }
+
+    /// Writes the accounts, balance locks, ledgers, intentions and bags-list nodes of `stakers` to
+    /// storage in chunks of `chunk_size` items through sudo `system.set_storage`, records them in
+    /// the state file once written and checks the staking invariants afterwards. Every staker is
+    /// endowed with twice its bond, and its distinct controller (if any) with the bond.
+    pub(crate) async fn inject_stakers(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        stakers: Vec<PlannedStaker>,
+        chunk_size: usize,
+    ) -> color_eyre::Result<()> {
+        use staking_parachain::runtime_types::{
+            bounded_collections::{bounded_vec::BoundedVec, weak_bounded_vec::WeakBoundedVec},
+            frame_system::AccountInfo,
+            pallet_bags_list::list::{Bag, Node},
+            pallet_balances::types::{AccountData, BalanceLock, ExtraFlags, Reasons},
+            pallet_staking::{Nominations, StakingLedger, ValidatorPrefs},
+        };
+        use std::collections::BTreeMap;
+
+        const IS_NEW_LOGIC: u128 = 1u128 << 127;
+
+        let storage = storage_at(api, configs).await?;
+        let query = staking_parachain::storage();
+
+        let n_validators = stakers
+            .iter()
+            .filter(|s| s.account.role == state::Role::Validator)
+            .count() as u32;
+        let n_nominators = stakers.len() as u32 - n_validators;
+        let issuance = storage
+            .fetch_or_default(&query.balances().total_issuance())
+            .await?
+            .saturating_add(
+                stakers
+                    .iter()
+                    .map(|s| match s.account.controller_seed {
+                        Some(_) => s.bond * 3,
+                        None => s.bond * 2,
+                    })
+                    .sum(),
+            );
+        let current_era = storage
+            .fetch(&query.staking().current_era())
+            .await?
+            .unwrap_or_default();
+        let thresholds = api
+            .constants()
+            .at(&staking_parachain::constants().voter_list().bag_thresholds())?;
+
+        let mut items: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+        let mut bags: BTreeMap<u64, Vec<(AccountId32, u64)>> = BTreeMap::new();
+        for staker in stakers.iter() {
+            let stash = staker.account.account_id();
+            let controller = staker.account.controller_id();
+            let bond = staker.bond;
+            let account_info = AccountInfo {
+                nonce: 0u32,
+                // one for the staking bond and one for the balance lock.
+                consumers: 2,
+                providers: 1,
+                sufficients: 0,
+                data: AccountData {
+                    free: bond * 2,
+                    reserved: 0,
+                    frozen: bond,
+                    flags: ExtraFlags(IS_NEW_LOGIC),
+                },
+            };
+            let locks = WeakBoundedVec(vec![BalanceLock {
+                id: STAKING_LOCK,
+                amount: bond,
+                reasons: Reasons::All,
+            }]);
+            let ledger = StakingLedger {
+                stash: stash.clone(),
+                total: bond,
+                active: bond,
+                unlocking: BoundedVec(vec![]),
+                legacy_claimed_rewards: BoundedVec(vec![]),
+            };
+
+            items.push((
+                api.storage()
+                    .address_bytes(&query.system().account(&stash))?,
+                account_info.encode(),
+            ));
+            items.push((
+                api.storage()
+                    .address_bytes(&query.balances().locks(&stash))?,
+                locks.encode(),
+            ));
+            items.push((
+                api.storage()
+                    .address_bytes(&query.staking().bonded(&stash))?,
+                controller.encode(),
+            ));
+            items.push((
+                api.storage()
+                    .address_bytes(&query.staking().ledger(&controller))?,
+                ledger.encode(),
+            ));
+            // a distinct controller is endowed with the bond, to pay for the fees of its calls.
+            if controller != stash {
+                let controller_info = AccountInfo {
+                    nonce: 0u32,
+                    consumers: 0,
+                    providers: 1,
+                    sufficients: 0,
+                    data: AccountData {
+                        free: bond,
+                        reserved: 0,
+                        frozen: 0,
+                        flags: ExtraFlags(IS_NEW_LOGIC),
+                    },
+                };
+                items.push((
+                    api.storage()
+                        .address_bytes(&query.system().account(&controller))?,
+                    controller_info.encode(),
+                ));
+            }
+            items.push((
+                api.storage()
+                    .address_bytes(&query.staking().payee(&stash))?,
+                RewardDestination::<AccountId32>::Staked.encode(),
+            ));
+            match staker.account.role {
+                state::Role::Validator => items.push((
+                    api.storage()
+                        .address_bytes(&query.staking().validators(&stash))?,
+                    ValidatorPrefs {
+                        commission: Perbill(10),
+                        blocked: false,
+                    }
+                    .encode(),
+                )),
+                state::Role::Nominator => items.push((
+                    api.storage()
+                        .address_bytes(&query.staking().nominators(&stash))?,
+                    Nominations {
+                        targets: BoundedVec(staker.targets.clone()),
+                        submitted_in: current_era,
+                        suppressed: false,
+                    }
+                    .encode(),
+                )),
+            }
+
+            let score = to_vote(bond, issuance);
+            let bag_upper = notional_bag(&thresholds, score);
+            bags.entry(bag_upper).or_default().push((stash, score));
+        }
+
+        // the new nodes are appended to the tail of their bags.
+        for (bag_upper, nodes) in bags.iter() {
+            let bag = storage
+                .fetch(&query.voter_list().list_bags(*bag_upper))
+                .await?;
+            let (head, old_tail) = match bag {
+                Some(Bag { head, tail }) => (head, tail),
+                None => (None, None),
+            };
+            let ids: Vec<&AccountId32> = nodes.iter().map(|(id, _)| id).collect();
+            if let Some(old_tail) = old_tail.as_ref() {
+                let mut node = storage
+                    .fetch(&query.voter_list().list_nodes(old_tail))
+                    .await?
+                    .ok_or(eyre!("bag tail {old_tail} has no node"))?;
+                node.next = ids.first().cloned().cloned();
+                items.push((
+                    api.storage()
+                        .address_bytes(&query.voter_list().list_nodes(old_tail))?,
+                    node.encode(),
+                ));
+            }
+            for (i, (id, score)) in nodes.iter().enumerate() {
+                let prev = match i {
+                    0 => old_tail.clone(),
+                    _ => Some(ids[i - 1].clone()),
+                };
+                let node = Node {
+                    id: id.clone(),
+                    prev,
+                    next: ids.get(i + 1).cloned().cloned(),
+                    bag_upper: *bag_upper,
+                    score: *score,
+                };
+                items.push((
+                    api.storage()
+                        .address_bytes(&query.voter_list().list_nodes(id))?,
+                    node.encode(),
+                ));
+            }
+            let bag = Bag {
+                head: head.or(ids.first().cloned().cloned()),
+                tail: ids.last().cloned().cloned(),
+            };
+            items.push((
+                api.storage()
+                    .address_bytes(&query.voter_list().list_bags(*bag_upper))?,
+                bag.encode(),
+            ));
+        }
+
+        // counters and total issuance.
+        let counters = [
+            (
+                api.storage()
+                    .address_bytes(&query.staking().counter_for_validators())?,
+                storage
+                    .fetch_or_default(&query.staking().counter_for_validators())
+                    .await?
+                    + n_validators,
+            ),
+            (
+                api.storage()
+                    .address_bytes(&query.staking().counter_for_nominators())?,
+                storage
+                    .fetch_or_default(&query.staking().counter_for_nominators())
+                    .await?
+                    + n_nominators,
+            ),
+            (
+                api.storage()
+                    .address_bytes(&query.voter_list().counter_for_list_nodes())?,
+                storage
+                    .fetch_or_default(&query.voter_list().counter_for_list_nodes())
+                    .await?
+                    + n_validators
+                    + n_nominators,
+            ),
+        ];
+        items.extend(counters.into_iter().map(|(k, v)| (k, v.encode())));
+        items.push((
+            api.storage()
+                .address_bytes(&query.balances().total_issuance())?,
+            issuance.encode(),
+        ));
+
+        println!(
+            "> Injecting {n_validators} validators and {n_nominators} nominators ({} storage items)..",
+            items.len()
+        );
+        set_storage_chunked(api, configs, items, chunk_size).await?;
+
+        let mut state = state::State::load_for(&configs).await?;
+        state
+            .accounts
+            .extend(stakers.into_iter().map(|s| s.account));
+        state.save(&configs.state)?;
+
+        let violations = staking_invariants(&storage_at(api, configs).await?).await?;
+        if !violations.is_empty() {
+            for violation in violations.iter() {
+                println!(" ! {violation}");
+            }
+            bail!(
+                "{} staking invariant(s) violated after injection",
+                violations.len()
+            );
+        }
+        println!("Injection done, staking invariants hold.");
+
+        Ok(())
+    }
+
+    /// Fetches all the raw storage entries of `pallet`.
+    pub(crate) async fn pallet_entries(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        pallet: &str,
+    ) -> color_eyre::Result<Vec<dump::DumpEntry>> {
+        let prefix = sp_core::hashing::twox_128(pallet.as_bytes());
+
+        let mut entries = vec![];
+        let mut keys = storage.fetch_raw_keys(prefix.to_vec()).await?;
+        while let Some(key) = keys.next().await {
+            let key = key?;
+            if let Some(value) = storage.fetch_raw(key.clone()).await? {
+                entries.push(dump::DumpEntry { key, value });
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Counts the keys under `prefix` in the latest block.
+    pub(crate) async fn count_keys(
+        api: &OnlineClient<SubstrateConfig>,
+        prefix: &[u8],
+    ) -> color_eyre::Result<usize> {
+        let mut keys = api
+            .storage()
+            .at_latest()
+            .await?
+            .fetch_raw_keys(prefix.to_vec())
+            .await?;
+        let mut count = 0;
+        while let Some(key) = keys.next().await {
+            key?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Writes `items` with sudo `set_storage` calls of up to `chunk_size` items each.
+    pub(crate) async fn set_storage_chunked(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        items: Vec<(Vec<u8>, Vec<u8>)>,
+        chunk_size: usize,
+    ) -> color_eyre::Result<()> {
+        use staking_parachain::runtime_types::frame_system::pallet::Call as SystemCall;
+
+        let chunks = items.chunks(chunk_size.max(1));
+        let n_chunks = chunks.len();
+        for (i, chunk) in chunks.enumerate() {
+            let call = RuntimeCall::System(SystemCall::set_storage {
+                items: chunk.to_vec(),
+            });
+            submit_sudo(api, configs, call).await?;
+            println!(" chunk {}/{n_chunks} written.", i + 1);
+        }
+
+        Ok(())
+    }
+
+    /// The on-chain staking configs, as compared by `check config`.
+    pub(crate) async fn staking_configs(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<output::StakingConfigs> {
+        let storage = storage_at(api, configs).await?;
+        let query = staking_parachain::storage().staking();
+        let constants = staking_parachain::constants().staking();
+
+        Ok(output::StakingConfigs {
+            min_nominator_bond: Some(
+                storage
+                    .fetch_or_default(&query.min_nominator_bond())
+                    .await?,
+            ),
+            min_validator_bond: Some(
+                storage
+                    .fetch_or_default(&query.min_validator_bond())
+                    .await?,
+            ),
+            max_nominators_count: storage.fetch(&query.max_nominators_count()).await?,
+            max_validators_count: storage.fetch(&query.max_validators_count()).await?,
+            validator_count: Some(storage.fetch_or_default(&query.validator_count()).await?),
+            minimum_validator_count: Some(
+                storage
+                    .fetch_or_default(&query.minimum_validator_count())
+                    .await?,
+            ),
+            min_commission: Some(storage.fetch_or_default(&query.min_commission()).await?.0),
+            chill_threshold: storage.fetch(&query.chill_threshold()).await?.map(|p| p.0),
+            max_staked_rewards: storage
+                .fetch(&query.max_staked_rewards())
+                .await?
+                .map(|p| p.0),
+            slash_reward_fraction: Some(
+                storage
+                    .fetch_or_default(&query.slash_reward_fraction())
+                    .await?
+                    .0,
+            ),
+            slash_defer_duration: Some(api.constants().at(&constants.slash_defer_duration())?),
+            sessions_per_era: Some(api.constants().at(&constants.sessions_per_era())?),
+            invulnerables: Some(storage.fetch_or_default(&query.invulnerables()).await?),
+        })
+    }
+
+    /// Winners of the on-chain election of an era compared with an offline one.
+    pub(crate) struct ElectionComparison {
+        pub(crate) era: u32,
+        /// Number of winners on-chain.
+        pub(crate) onchain: usize,
+        /// Number of winners offline.
+        pub(crate) offline: usize,
+        pub(crate) divergences: Vec<String>,
+    }
+
+    /// Runs a sequential phragmen election with `balancing` iterations over the snapshot read at
+    /// block `at` and diffs its winners and their backing, up to `tolerance` percent, against the
+    /// exposures of `era`, the current one by default. `None` if there is no snapshot at `at`.
+    pub(crate) async fn compare_election(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        era: Option<u32>,
+        at: Option<H256>,
+        balancing: usize,
+        tolerance: f64,
+    ) -> color_eyre::Result<Option<ElectionComparison>> {
+        use std::collections::BTreeMap;
+
+        let latest = storage_at(api, configs).await?;
+        let snapshot_storage = match at {
+            Some(hash) => api.storage().at(hash),
+            None => latest.clone(),
+        };
+
+        let era = match era {
+            Some(era) => era,
+            None => latest
+                .fetch(&staking_parachain::storage().staking().current_era())
+                .await?
+                .unwrap_or_default(),
+        };
+
+        let (targets, voters) = get_snapshot(api, &snapshot_storage).await?;
+        if targets.is_empty() || voters.is_empty() {
+            return Ok(None);
+        }
+        let round = snapshot_storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .election_provider_multi_block()
+                    .round(),
+            )
+            .await?;
+        let desired_targets = desired_targets(api, &snapshot_storage, round).await?;
+
+        let offline: BTreeMap<AccountId32, u128> =
+            mine_solution(desired_targets, targets, voters, balancing)?
+                .into_iter()
+                .map(|(winner, support)| (winner, support.total))
+                .collect();
+
+        // exposures are denominated in balance, the snapshot in vote weight.
+        let issuance = latest
+            .fetch_or_default(&staking_parachain::storage().balances().total_issuance())
+            .await?;
+        let onchain: BTreeMap<AccountId32, u128> = get_era_exposures(&latest, era)
+            .await?
+            .into_iter()
+            .map(|(winner, exposure)| (winner, to_vote(exposure.total, issuance) as u128))
+            .collect();
+
+        let mut divergences = vec![];
+        for (winner, backing) in offline.iter() {
+            let Some(onchain_backing) = onchain.get(winner) else {
+                divergences.push(format!(
+                    "{winner} elected offline (backing {backing}) but not on-chain."
+                ));
+                continue;
+            };
+            let difference = backing.abs_diff(*onchain_backing) as f64
+                / (*backing.max(onchain_backing)).max(1) as f64
+                * 100.0;
+            if difference > tolerance {
+                divergences.push(format!(
+                    "{winner} backing differs by {difference:.2}% (on-chain {onchain_backing}, offline {backing})."
+                ));
+            }
+        }
+        for winner in onchain.keys().filter(|w| !offline.contains_key(*w)) {
+            divergences.push(format!("{winner} elected on-chain but not offline."));
+        }
+
+        Ok(Some(ElectionComparison {
+            era,
+            onchain: onchain.len(),
+            offline: offline.len(),
+            divergences,
+        }))
+    }
+
+    /// Checks the consistency of the staking storage and returns the violated invariants: the
+    /// counters match the counted maps, every validator and nominator is bonded and in the
+    /// voter list, and every ledger is bonded to its stash.
+    pub(crate) async fn staking_invariants(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<Vec<String>> {
+        let query = staking_parachain::storage();
+        let mut violations = vec![];
+
+        let mut stakers = vec![];
+        let mut validators = storage.iter(query.staking().validators_iter()).await?;
+        while let Some(Ok(kv)) = validators.next().await {
+            stakers.push(account_from_key(&kv.0));
+        }
+        let n_validators = stakers.len() as u32;
+        let mut nominators = storage.iter(query.staking().nominators_iter()).await?;
+        while let Some(Ok(kv)) = nominators.next().await {
+            stakers.push(account_from_key(&kv.0));
+        }
+        let n_nominators = stakers.len() as u32 - n_validators;
+
+        let counters = [
+            (
+                "CounterForValidators",
+                storage
+                    .fetch_or_default(&query.staking().counter_for_validators())
+                    .await?,
+                n_validators,
+            ),
+            (
+                "CounterForNominators",
+                storage
+                    .fetch_or_default(&query.staking().counter_for_nominators())
+                    .await?,
+                n_nominators,
+            ),
+        ];
+        for (counter, value, count) in counters {
+            if value != count {
+                violations.push(format!(
+                    "{counter} is {value} but there are {count} entries"
+                ));
+            }
+        }
+
+        let mut n_nodes = 0u32;
+        let mut nodes = storage.iter(query.voter_list().list_nodes_iter()).await?;
+        while let Some(Ok(_)) = nodes.next().await {
+            n_nodes += 1;
+        }
+        let counter_for_nodes = storage
+            .fetch_or_default(&query.voter_list().counter_for_list_nodes())
+            .await?;
+        if counter_for_nodes != n_nodes {
+            violations.push(format!(
+                "CounterForListNodes is {counter_for_nodes} but there are {n_nodes} nodes"
+            ));
+        }
+        if n_nodes != stakers.len() as u32 {
+            violations.push(format!(
+                "{n_nodes} voter list nodes for {} validators and nominators",
+                stakers.len()
+            ));
+        }
+
+        for stash in stakers.iter() {
+            let Some(controller) = storage.fetch(&query.staking().bonded(stash)).await? else {
+                violations.push(format!("staker {stash} is not bonded"));
+                continue;
+            };
+            match storage.fetch(&query.staking().ledger(&controller)).await? {
+                Some(ledger) if &ledger.stash == stash => (),
+                Some(ledger) => violations.push(format!(
+                    "ledger of {controller} belongs to {} instead of {stash}",
+                    ledger.stash
+                )),
+                None => violations.push(format!("staker {stash} has no ledger")),
+            }
+            if storage
+                .fetch(&query.voter_list().list_nodes(stash))
+                .await?
+                .is_none()
+            {
+                violations.push(format!("staker {stash} is not in the voter list"));
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Randomly generates `n` accounts with a given `role` and funds them from `funder`. The
+    /// generated accounts are returned.
+    pub(crate) async fn fund_accounts(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        funder: &Keypair,
+        n: usize,
+        amount: Option<Balance>,
+        role: state::Role,
+    ) -> color_eyre::Result<Vec<state::ManagedAccount>> {
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        let ed = staking_parachain::constants()
+            .balances()
+            .existential_deposit();
+        let fund_with = amount.unwrap_or(api.constants().at(&ed)? * 1000);
+
+        let mut accounts: Vec<state::ManagedAccount> = vec![];
+        let mut mint_calls: Vec<RuntimeCall> = vec![];
+
+        // generate and fund new accounts:
+        // - generate random keypair
+        // - funds account
+        for _n in 0..n {
+            let account = state::ManagedAccount::generate(role);
+            let pair = account.keypair();
+
+            accounts.push(account);
+
+            let mint_call = BalancesCall::transfer_allow_death {
+                dest: pair.public_key().into(),
+                value: fund_with,
+            };
+            mint_calls.push(RuntimeCall::Balances(mint_call));
+        }
+
+        ensure_funds(api, configs, funder, fund_with * n as Balance).await?;
+
+        let n_calls = mint_calls.len();
+        // make sure all mints went through before progressing.
+        let progress = submit(api, configs, batch(configs, mint_calls), funder).await?;
+        let failed = match wait_for_outcome(configs, progress).await? {
+            Some(events) => batch_failures(&events, n_calls)?,
+            None => vec![],
+        };
+        if !failed.is_empty() {
+            println!(
+                "Funding failed for {} account(s), skipping them.",
+                failed.len()
+            );
+        }
+        let accounts = accounts
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !failed.contains(i))
+            .map(|(_, account)| account)
+            .collect();
+
+        Ok(accounts)
+    }
+
+    /// Fetches the pool membership of `who`, if any.
+    pub(crate) async fn pool_member(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        who: &AccountId32,
+    ) -> color_eyre::Result<Option<PoolMember>> {
+        Ok(storage_at(api, configs)
+            .await?
+            .fetch(
+                &staking_parachain::storage()
+                    .nomination_pools()
+                    .pool_members(who),
+            )
+            .await?)
+    }
+
+    /// The bonded account of the nomination pool `pool_id`, the sub account of the pallet id
+    /// as derived by the pallet.
+    pub(crate) fn pool_account(
+        api: &OnlineClient<SubstrateConfig>,
+        pool_id: u32,
+    ) -> color_eyre::Result<AccountId32> {
+        let pallet_id = api.constants().at(&staking_parachain::constants()
+            .nomination_pools()
+            .pallet_id())?;
+        // `AccountType::Bonded` is encoded as 0.
+        let encoded = (*b"modl", pallet_id.0, 0u8, pool_id).encode();
+        let mut raw = [0u8; 32];
+        raw[..encoded.len()].copy_from_slice(&encoded);
+        Ok(AccountId32(raw))
+    }
+
+    /// The ids of `pool`, or of all the CLI-created pools, with the key pair of their root:
+    /// the CLI-created depositor if it still is the root, the signer otherwise.
+    pub(crate) async fn pool_roots(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        pool: Option<u32>,
+    ) -> color_eyre::Result<Vec<(u32, Keypair)>> {
+        let managed = state::State::load_for(&configs).await?.pools;
+        let ids: Vec<u32> = match pool {
+            Some(pool) => vec![pool],
+            None if managed.is_empty() => bail!(
+                "no CLI-created pool in {}, give the pool with --pool",
+                configs.state.display()
+            ),
+            None => managed.iter().map(|p| p.id).collect(),
+        };
+
+        let storage = storage_at(api, configs).await?;
+        let signer = configs.signer()?;
+        let mut roots = vec![];
+        for pool_id in ids {
+            let Some(bonded) = storage
+                .fetch(
+                    &staking_parachain::storage()
+                        .nomination_pools()
+                        .bonded_pools(pool_id),
+                )
+                .await?
+            else {
+                bail!("pool {pool_id} does not exist");
+            };
+            let depositor = managed
+                .iter()
+                .find(|p| p.id == pool_id)
+                .map(|p| signer_from_seed(&p.depositor));
+            let root = bonded.roles.root;
+            let key = [depositor, Some(signer.clone())]
+                .into_iter()
+                .flatten()
+                .find(|pair| root.as_ref() == Some(&AccountId32::from(pair.public_key())));
+            let Some(key) = key else {
+                bail!("pool {pool_id} root {root:?} is neither its CLI-created depositor nor the signer");
+            };
+            roots.push((pool_id, key));
+        }
+
+        Ok(roots)
+    }
+
+    /// Submits the admin `calls` of `pool_id`, batched, signed by its `root` and waits for
+    /// them to succeed.
+    pub(crate) async fn submit_pool_admin(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        pool_id: u32,
+        root: &Keypair,
+        calls: Vec<PoolsCall>,
+    ) -> color_eyre::Result<()> {
+        let n_calls = calls.len();
+        let calls = calls
+            .into_iter()
+            .map(RuntimeCall::NominationPools)
+            .collect();
+        let progress = submit(api, configs, batch(configs, calls), root).await?;
+        let events = wait_for_success(configs, progress).await?;
+        let failed = batch_failures(&events, n_calls)?;
+        if !failed.is_empty() {
+            bail!(
+                "{} of the {n_calls} call(s) to pool {pool_id} failed",
+                failed.len()
+            );
+        }
+        println!("Pool {pool_id} updated with {n_calls} call(s).");
+
+        Ok(())
+    }
+
+    /// Samples the points, members and bonded balance of the CLI-created `pools` in `era`.
+    pub(crate) async fn pools_sample(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        era: u32,
+        pools: &[state::ManagedPool],
+    ) -> color_eyre::Result<output::PoolsEra> {
+        let storage = storage_at(api, configs).await?;
+        let query = staking_parachain::storage();
+
+        let mut samples = vec![];
+        for pool in pools {
+            let Some(bonded) = storage
+                .fetch(&query.nomination_pools().bonded_pools(pool.id))
+                .await?
+            else {
+                continue;
+            };
+            let balance = storage
+                .fetch(&query.staking().ledger(pool_account(api, pool.id)?))
+                .await?
+                .map(|ledger| ledger.active)
+                .unwrap_or_default();
+            samples.push(output::PoolSample {
+                pool_id: pool.id,
+                members: bonded.member_counter,
+                points: bonded.points,
+                balance,
+            });
+        }
+
+        Ok(output::PoolsEra {
+            era,
+            pools: samples,
+        })
+    }
+
+    /// Fetches all validators registered in the system.
+    pub(crate) async fn get_validators(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<Targets> {
+        let mut validators = vec![];
+        let storage_query = staking_parachain::storage().staking().validators_iter();
+
+        let mut results = storage_at(api, configs).await?.iter(storage_query).await?;
+        while let Some(Ok(kv)) = results.next().await {
+            let (k, _) = kv;
+            let account: Vec<u8> = k.into_iter().rev().take(32).rev().collect();
+            let account: [u8; 32] = account.try_into().expect("32 bytes should fit");
+            let maddress = Id(AccountId32(account));
+
+            validators.push(maddress);
+        }
+
+        Ok(validators)
+    }
+
+    /// The eras whose rewards can still be paid out: the last `HistoryDepth` eras before the
+    /// active one, none during the first era.
+    pub(crate) async fn payable_eras(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<std::ops::RangeInclusive<u32>> {
+        let history_depth = api
+            .constants()
+            .at(&staking_parachain::constants().staking().history_depth())?;
+        let active_era = active_era(api, configs).await?;
+        if active_era == 0 {
+            // an empty range, no era has ended yet.
+            return Ok(std::ops::RangeInclusive::new(1, 0));
+        }
+
+        Ok(active_era.saturating_sub(history_depth)..=active_era - 1)
+    }
+
+    /// Returns the eras of `eras` in which `who` was exposed and all its pages were paid out,
+    /// and the pages still to be paid out. Validators are checked against their own pages and
+    /// their legacy claimed rewards, nominators against the pages of their era exposures.
+    pub(crate) async fn staker_rewards(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        who: &AccountId32,
+        role: state::Role,
+        eras: impl IntoIterator<Item = u32>,
+    ) -> color_eyre::Result<(Vec<u32>, Vec<output::OutstandingPayout>)> {
+        let staking = staking_parachain::storage().staking();
+
+        let (validators, legacy_claimed) = match role {
+            state::Role::Validator => {
+                let legacy = storage
+                    .fetch(&staking.ledger(who))
+                    .await?
+                    .map(|ledger| ledger.legacy_claimed_rewards.0)
+                    .unwrap_or_default();
+                (vec![who.clone()], legacy)
+            }
+            state::Role::Nominator => {
+                let targets = storage
+                    .fetch(&staking.nominators(who))
+                    .await?
+                    .map(|n| n.targets.0)
+                    .unwrap_or_default();
+                (targets, vec![])
+            }
+        };
+
+        let mut claimed_eras = vec![];
+        let mut outstanding = vec![];
+        for era in eras {
+            let mut exposed = false;
+            let mut paid = true;
+            for validator in validators.iter() {
+                let Some(overview) = storage
+                    .fetch(&staking.eras_stakers_overview(era, validator))
+                    .await?
+                else {
+                    continue;
+                };
+                let claimed = storage
+                    .fetch(&staking.claimed_rewards(era, validator))
+                    .await?
+                    .unwrap_or_default();
+
+                for page in 0..overview.page_count {
+                    if role == state::Role::Nominator {
+                        let in_page = exposure_page(storage, era, validator, page)
+                            .await?
+                            .is_some_and(|p| p.iter().any(|(o, _)| o == who));
+                        if !in_page {
+                            continue;
+                        }
+                    }
+                    exposed = true;
+                    if !claimed.contains(&page) && !legacy_claimed.contains(&era) {
+                        paid = false;
+                        outstanding.push(output::OutstandingPayout {
+                            era,
+                            validator: validator.clone(),
+                            page,
+                        });
+                    }
+                }
+            }
+            if exposed && paid {
+                claimed_eras.push(era);
+            }
+        }
+
+        Ok((claimed_eras, outstanding))
+    }
+
+    /// Fetches the `(era, validator, page)` payouts outstanding for the CLI-created stakers
+    /// over the payable eras. Validators and their nominators may share pages.
+    pub(crate) async fn outstanding_payouts(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<std::collections::BTreeSet<(u32, AccountId32, u32)>> {
+        let storage = storage_at(api, configs).await?;
+        let eras = payable_eras(api, configs).await?;
+
+        let mut outstanding = std::collections::BTreeSet::new();
+        for account in state::State::load_for(&configs).await?.accounts.iter() {
+            let (_, pages) =
+                staker_rewards(&storage, &account.account_id(), account.role, eras.clone()).await?;
+            outstanding.extend(pages.into_iter().map(|p| (p.era, p.validator, p.page)));
+        }
+
+        Ok(outstanding)
+    }
+
+    /// Fetches the index of the active era, 0 if there is none yet.
+    pub(crate) async fn active_era(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<u32> {
+        let active_era = storage_at(api, configs)
+            .await?
+            .fetch(&staking_parachain::storage().staking().active_era())
+            .await?
+            .map(|info| info.index)
+            .unwrap_or_default();
+
+        Ok(active_era)
+    }
+
+    /// Fetches the score of the currently queued election solution, if any, together with the
+    /// era it elects.
+    pub(crate) async fn queued_score(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<Option<(u32, index::ScoreRecord)>> {
+        let storage = storage_at(api, configs).await?;
+
+        let Some(score) = storage
+            .fetch(
+                &staking_parachain::storage()
+                    .election_verifier_pallet()
+                    .queued_solution_score(),
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let round = storage
+            .fetch_or_default(
+                &staking_parachain::storage()
+                    .election_provider_multi_block()
+                    .round(),
+            )
+            .await?;
+        let current_era = storage
+            .fetch(&staking_parachain::storage().staking().current_era())
+            .await?
+            .unwrap_or_default();
+
+        let record = index::ScoreRecord {
+            round,
+            minimal_stake: score.minimal_stake,
+            sum_stake: score.sum_stake,
+            sum_stake_squared: score.sum_stake_squared,
+        };
+
+        Ok(Some((current_era + 1, record)))
+    }
+
+    /// Splits the registered validators into the ones exposed in the active era, together with
+    /// the era points they earned so far, and the waiting ones. Returns the active era index too.
+    pub(crate) async fn classify_validators(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<(u32, Vec<(AccountId32, u32)>, Vec<AccountId32>)> {
+        let active_era = active_era(api, configs).await?;
+        let storage = storage_at(api, configs).await?;
+
+        let exposed: Vec<AccountId32> = get_era_exposures(&storage, active_era)
+            .await?
+            .into_iter()
+            .map(|(validator, _)| validator)
+            .collect();
+
+        let points = storage
+            .fetch(
+                &staking_parachain::storage()
+                    .staking()
+                    .eras_reward_points(active_era),
+            )
+            .await?
+            .map(|p| p.individual)
+            .unwrap_or_default();
+
+        let mut active = vec![];
+        let mut waiting = vec![];
+        for validator in get_validators(api, configs).await?.into_iter() {
+            let Id(account) = validator else { continue };
+            if exposed.contains(&account) {
+                let era_points = points
+                    .iter()
+                    .find(|(who, _)| who == &account)
+                    .map(|(_, p)| *p)
+                    .unwrap_or_default();
+                active.push((account, era_points));
+            } else {
+                waiting.push(account);
+            }
+        }
+
+        Ok((active_era, active, waiting))
+    }
+
+    /// Fetches the exposure metadata of all the validators elected for `era`.
+    pub(crate) async fn get_era_exposures(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        era: u32,
+    ) -> color_eyre::Result<Vec<(AccountId32, PagedExposureMetadata<Balance>)>> {
+        let mut exposures = vec![];
+        let storage_query = staking_parachain::storage()
+            .staking()
+            .eras_stakers_overview_iter1(era);
+
+        let mut results = storage.iter(storage_query).await?;
+        while let Some(Ok(kv)) = results.next().await {
+            let (k, exposure) = kv;
+            exposures.push((account_from_key(&k), exposure));
+        }
+
+        Ok(exposures)
+    }
+
+    /// Fetches page `page` of the exposure of `validator` in `era`, as the backing of each
+    /// nominator. Falls back to the legacy `ErasStakers` exposure as page 0 when the era has no
+    /// paged exposure for the validator.
+    pub(crate) async fn exposure_page(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        era: u32,
+        validator: &AccountId32,
+        page: u32,
+    ) -> color_eyre::Result<Option<Vec<(AccountId32, Balance)>>> {
+        let query = staking_parachain::storage().staking();
+
+        if let Some(paged) = storage
+            .fetch(&query.eras_stakers_paged(era, validator, page))
+            .await?
+        {
+            return Ok(Some(
+                paged.others.into_iter().map(|o| (o.who, o.value)).collect(),
+            ));
+        }
+        let has_overview = storage
+            .fetch(&query.eras_stakers_overview(era, validator))
+            .await?
+            .is_some();
+        if page > 0 || has_overview {
+            return Ok(None);
+        }
+        let legacy = storage
+            .fetch(&query.eras_stakers(era, validator))
+            .await?
+            .map(|e| e.others.into_iter().map(|o| (o.who, o.value)).collect());
+
+        Ok(legacy)
+    }
+
+    /// Fetches the `page_count` exposure pages of `validator` in `era`. A missing page is read
+    /// as empty.
+    pub(crate) async fn exposure_pages(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        era: u32,
+        validator: &AccountId32,
+        page_count: u32,
+    ) -> color_eyre::Result<Vec<Vec<(AccountId32, Balance)>>> {
+        let mut pages = vec![];
+        for page in 0..page_count {
+            pages.push(
+                exposure_page(storage, era, validator, page)
+                    .await?
+                    .unwrap_or_default(),
+            );
+        }
+
+        Ok(pages)
+    }
+
+    /// Estimates the encoded size of a snapshot of `voters` voters casting `votes` votes over
+    /// `targets` targets, and of a solution page electing `desired_targets` winners, and checks
+    /// them, and `most_backers` backers of a winner, against the snapshot bounds, the max proof
+    /// size and length of a block and the bounds of `verifier_pallet` if the runtime has it. The
+    /// solution size assumes every voter backs its share of winners.
+    pub(crate) fn estimate_election(
+        api: &OnlineClient<SubstrateConfig>,
+        verifier_pallet: &str,
+        voters: u64,
+        votes: u64,
+        targets: u32,
+        desired_targets: u32,
+        most_backers: u32,
+    ) -> color_eyre::Result<output::ElectionEstimate> {
+        // account, vote weight and the length prefix of the votes.
+        const VOTER_BYTES: u64 = 32 + 8 + 1;
+
+        let constants = api.constants();
+        let epm = staking_parachain::constants().election_provider_multi_block();
+        let pages = constants.at(&epm.pages())?;
+        let voters_per_page = constants.at(&epm.voter_snapshot_per_block())?;
+        let max_targets = constants.at(&epm.target_snapshot_per_block())?;
+        let block_weights =
+            constants.at(&staking_parachain::constants().system().block_weights())?;
+        let block_length = constants.at(&staking_parachain::constants().system().block_length())?;
+
+        let max_voters = voters_per_page as u64 * pages as u64;
+        let snapshot_voters = voters.min(max_voters);
+        let votes_per_voter = votes as f64 / voters.max(1) as f64;
+        let voter_bytes = VOTER_BYTES as f64 + 32.0 * votes_per_voter;
+        let voter_page_bytes =
+            (voter_bytes * snapshot_voters.min(voters_per_page as u64) as f64) as u64;
+        let snapshot_bytes =
+            (voter_bytes * snapshot_voters as f64) as u64 + 32 * targets.min(max_targets) as u64;
+
+        // compact solution: voter index, then a target index and a PerU16 share per extra vote.
+        let index_bytes = if snapshot_voters < 1 << 14 { 2 } else { 4 };
+        let backed = (votes_per_voter * desired_targets as f64 / targets.max(1) as f64).max(1.0);
+        let solution_voter_bytes = index_bytes as f64 + 2.0 + (backed - 1.0) * 4.0;
+        let solution_page_bytes =
+            (solution_voter_bytes * snapshot_voters.min(voters_per_page as u64) as f64) as u64;
+
+        let verifier_bound = |name: &str| {
+            constants
+                .at(&subxt::dynamic::constant(verifier_pallet, name))
+                .ok()
+                .and_then(|value| value.to_value().ok())
+                .and_then(|value| value.as_u128())
+                .map(|bound| bound as u32)
+        };
+        let max_backers = verifier_bound("MaxBackersPerWinner");
+        let max_winners_per_page = verifier_bound("MaxWinnersPerPage");
+
+        let max_proof_size = block_weights.max_block.proof_size;
+        let max_length = block_length.max.normal;
+        let mut warnings = vec![];
+        if voters > max_voters {
+            warnings.push(format!(
+                "{} voters over the snapshot bound of {max_voters} ({pages} pages of {voters_per_page}) will be left out",
+                voters - max_voters
+            ));
+        }
+        if targets > max_targets {
+            warnings.push(format!(
+                "{} targets over the snapshot bound of {max_targets} will be left out",
+                targets - max_targets
+            ));
+        }
+        if voter_page_bytes > max_proof_size {
+            warnings.push(format!(
+                "a voter snapshot page of ~{voter_page_bytes} bytes exceeds the max proof size of {max_proof_size}"
+            ));
+        }
+        if solution_page_bytes > max_length as u64 {
+            warnings.push(format!(
+                "a solution page of ~{solution_page_bytes} bytes exceeds the max block length of {max_length}"
+            ));
+        }
+        if let Some(max_backers) = max_backers.filter(|max| most_backers > *max) {
+            warnings.push(format!(
+                "a target nominated by {most_backers} voters may exceed the {max_backers} backers per winner, trimming them"
+            ));
+        }
+        if let Some(max_winners) = max_winners_per_page.filter(|max| desired_targets > *max) {
+            warnings.push(format!(
+                "{desired_targets} desired targets exceed the {max_winners} winners per page"
+            ));
+        }
+
+        Ok(output::ElectionEstimate {
+            voters,
+            max_voters,
+            targets,
+            max_targets,
+            desired_targets,
+            pages,
+            votes_per_voter,
+            voter_page_bytes,
+            snapshot_bytes,
+            solution_page_bytes,
+            max_proof_size,
+            max_length,
+            most_backers,
+            max_backers,
+            max_winners_per_page,
+            warnings,
+        })
+    }
+
+    /// Fetches all the pages of the election snapshot, returning its targets and voters.
+    pub(crate) async fn get_snapshot(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<(Vec<AccountId32>, Vec<Voter>)> {
+        let pages = api.constants().at(&staking_parachain::constants()
+            .election_provider_multi_block()
+            .pages())?;
+
+        let mut targets = vec![];
+        let mut voters = vec![];
+        for page in 0..pages {
+            let epm = staking_parachain::storage().election_provider_multi_block();
+            if let Some(page_targets) = storage.fetch(&epm.paged_target_snapshot(page)).await? {
+                targets.extend(page_targets.0);
+            }
+            if let Some(page_voters) = storage.fetch(&epm.paged_voter_snapshot(page)).await? {
+                voters.extend(
+                    page_voters
+                        .0
+                        .into_iter()
+                        .map(|(who, weight, votes)| (who, weight, votes.0)),
+                );
+            }
+        }
+        targets.sort();
+        targets.dedup();
+
+        Ok((targets, voters))
+    }
+
+    /// The number of winners of the election of `round`, its `DesiredTargets` in the election
+    /// provider. Runtimes which do not record it elect staking's `ValidatorCount` winners.
+    pub(crate) async fn desired_targets(
+        api: &OnlineClient<SubstrateConfig>,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        round: u32,
+    ) -> color_eyre::Result<u32> {
+        const PALLET: &str = "ElectionProviderMultiBlock";
+
+        if !dynamic_entry_exists(api, PALLET, "DesiredTargets") {
+            return Ok(storage
+                .fetch_or_default(&staking_parachain::storage().staking().validator_count())
+                .await?);
+        }
+        let keys = vec![subxt::dynamic::Value::u128(round as u128)];
+        dynamic_entry(api, storage, PALLET, "DesiredTargets", keys)
+            .await?
+            .and_then(|value| value.as_u128())
+            .map(|desired| desired as u32)
+            .ok_or(eyre!("no desired targets recorded for round {round}"))
+    }
+
+    /// Mines a solution for `desired_targets` seats with sequential phragmen, optionally followed
+    /// by `balancing` iterations, and returns the supports of the winners.
+    pub(crate) fn mine_solution(
+        desired_targets: u32,
+        targets: Vec<AccountId32>,
+        voters: Vec<Voter>,
+        balancing: usize,
+    ) -> color_eyre::Result<sp_npos_elections::Supports<AccountId32>> {
+        let params = MiningParams {
+            balancing,
+            reduce: false,
+            max_voters: None,
+            voter_trimming: VoterTrimming::Stake,
+            max_backers: None,
+        };
+
+        Ok(mine(desired_targets, targets, voters, &params)?.supports)
+    }
+
+    /// Parameters of the offline miner, trading the quality of a solution for its size.
+    #[derive(Debug, Clone)]
+    pub(crate) struct MiningParams {
+        pub(crate) balancing: usize,
+        pub(crate) reduce: bool,
+        pub(crate) max_voters: Option<usize>,
+        pub(crate) voter_trimming: VoterTrimming,
+        pub(crate) max_backers: Option<usize>,
+    }
+
+    impl std::fmt::Display for MiningParams {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "balancing={}", self.balancing)?;
+            if self.reduce {
+                write!(f, " reduce")?;
+            }
+            if let Some(max) = self.max_voters {
+                write!(f, " max-voters={max} ({:?})", self.voter_trimming)?;
+            }
+            if let Some(max) = self.max_backers {
+                write!(f, " max-backers={max}")?;
+            }
+            Ok(())
+        }
+    }
+
+    /// A solution mined by `mine`.
+    pub(crate) struct Mined {
+        pub(crate) supports: sp_npos_elections::Supports<AccountId32>,
+        /// Edges between voters and winners left in the assignments.
+        pub(crate) edges: usize,
+        /// Edges removed by `reduce`.
+        pub(crate) reduced_edges: usize,
+        pub(crate) trimmed_voters: usize,
+        /// Edges removed from the winners over `max_backers` backers.
+        pub(crate) trimmed_backers: usize,
+    }
+
+    /// Mines a solution for `desired_targets` seats with sequential phragmen and `params`: the
+    /// voters are first trimmed to `max_voters`, the assignments are then balanced, reduced and
+    /// trimmed to `max_backers` backers per winner, keeping those with the most stake behind it.
+    pub(crate) fn mine(
+        desired_targets: u32,
+        targets: Vec<AccountId32>,
+        mut voters: Vec<Voter>,
+        params: &MiningParams,
+    ) -> color_eyre::Result<Mined> {
+        use sp_npos_elections::{
+            assignment_ratio_to_staked_normalized, reduce, seq_phragmen, to_supports,
+            BalancingConfig,
+        };
+        use std::collections::BTreeMap;
+
+        let mut trimmed_voters = 0;
+        if let Some(max) = params.max_voters.filter(|max| voters.len() > *max) {
+            match params.voter_trimming {
+                VoterTrimming::Stake => voters.sort_by(|a, b| b.1.cmp(&a.1)),
+                VoterTrimming::Votes => {
+                    voters.sort_by(|a, b| a.2.len().cmp(&b.2.len()).then(b.1.cmp(&a.1)))
+                }
+            }
+            trimmed_voters = voters.len() - max;
+            voters.truncate(max);
+        }
+
+        let stake_of: BTreeMap<AccountId32, u64> = voters
+            .iter()
+            .map(|(who, weight, _)| (who.clone(), *weight))
+            .collect();
+        let balancing = (params.balancing > 0).then_some(BalancingConfig {
+            iterations: params.balancing,
+            tolerance: 0,
+        });
+
+        let result = seq_phragmen::<AccountId32, sp_runtime::Perbill>(
+            desired_targets as usize,
+            targets,
+            voters,
+            balancing,
+        )
+        .map_err(|e| eyre!("offline election failed: {e:?}"))?;
+        let mut staked = assignment_ratio_to_staked_normalized(result.assignments, |who| {
+            stake_of.get(who).copied().unwrap_or_default()
+        })
+        .map_err(|e| eyre!("offline election failed: {e:?}"))?;
+
+        let reduced_edges = if params.reduce {
+            reduce(&mut staked) as usize
+        } else {
+            0
+        };
+
+        let mut trimmed_backers = 0;
+        if let Some(max) = params.max_backers {
+            let mut backers: BTreeMap<AccountId32, Vec<(u128, AccountId32)>> = BTreeMap::new();
+            for assignment in staked.iter() {
+                for (winner, stake) in assignment.distribution.iter() {
+                    backers
+                        .entry(winner.clone())
+                        .or_default()
+                        .push((*stake, assignment.who.clone()));
+                }
+            }
+            let mut cut = std::collections::BTreeSet::new();
+            for (winner, mut backers) in backers {
+                backers.sort_by(|a, b| b.0.cmp(&a.0));
+                for (_, voter) in backers.into_iter().skip(max) {
+                    cut.insert((voter, winner.clone()));
+                }
+            }
+            trimmed_backers = cut.len();
+            for assignment in staked.iter_mut() {
+                let who = assignment.who.clone();
+                assignment
+                    .distribution
+                    .retain(|(winner, _)| !cut.contains(&(who.clone(), winner.clone())));
+            }
+            staked.retain(|assignment| !assignment.distribution.is_empty());
+        }
+
+        Ok(Mined {
+            edges: staked.iter().map(|a| a.distribution.len()).sum(),
+            supports: to_supports(&staked),
+            reduced_edges,
+            trimmed_voters,
+            trimmed_backers,
+        })
+    }
+
+    /// Converts an on-chain election score into its `sp_npos_elections` counterpart.
+    pub(crate) fn to_npos_score(score: &ElectionScore) -> sp_npos_elections::ElectionScore {
+        sp_npos_elections::ElectionScore {
+            minimal_stake: score.minimal_stake,
+            sum_stake: score.sum_stake,
+            sum_stake_squared: score.sum_stake_squared,
+        }
+    }
+
+    /// The bag of the voter list where a node with `score` belongs to.
+    pub(crate) fn notional_bag(thresholds: &[u64], score: u64) -> u64 {
+        thresholds
+            .iter()
+            .find(|t| **t >= score)
+            .copied()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Fetches the voter list nodes that are not in the bag of their current score, as
+    /// `(stash, current bag, notional bag)`.
+    pub(crate) async fn dislocated_nodes(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<Vec<(AccountId32, u64, u64)>> {
+        let query = staking_parachain::storage();
+        let storage = storage_at(api, configs).await?;
+        let thresholds = api
+            .constants()
+            .at(&staking_parachain::constants().voter_list().bag_thresholds())?;
+        let issuance = storage
+            .fetch_or_default(&query.balances().total_issuance())
+            .await?;
+
+        let mut dislocated = vec![];
+        let mut nodes = storage.iter(query.voter_list().list_nodes_iter()).await?;
+        while let Some(Ok((_, node))) = nodes.next().await {
+            let Some(score) = vote_weight(&storage, &node.id, issuance).await? else {
+                continue;
+            };
+            let bag = notional_bag(&thresholds, score);
+            if bag != node.bag_upper {
+                dislocated.push((node.id, node.bag_upper, bag));
+            }
+        }
+
+        Ok(dislocated)
+    }
+
+    /// The vote weight of the active bond of `stash`, if bonded.
+    pub(crate) async fn vote_weight(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        stash: &AccountId32,
+        issuance: Balance,
+    ) -> color_eyre::Result<Option<u64>> {
+        let query = staking_parachain::storage();
+        let Some(controller) = storage.fetch(&query.staking().bonded(stash)).await? else {
+            return Ok(None);
+        };
+        let ledger = storage.fetch(&query.staking().ledger(&controller)).await?;
+
+        Ok(ledger.map(|l| to_vote(l.active, issuance)))
+    }
+
+    /// Fetches the nodes of the bag `who` is in, from head to tail, with their scores.
+    pub(crate) async fn bag_of(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        who: &AccountId32,
+    ) -> color_eyre::Result<Vec<(AccountId32, u64)>> {
+        let node = storage_at(api, configs)
+            .await?
+            .fetch(&staking_parachain::storage().voter_list().list_nodes(who))
+            .await?
+            .ok_or(eyre!("{who} is not in the voter list"))?;
+
+        bag_members(api, configs, node.bag_upper).await
+    }
+
+    /// Fetches the nodes of the bag with upper threshold `bag_upper`, from head to tail, with
+    /// their scores. An empty bag has no members.
+    pub(crate) async fn bag_members(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        bag_upper: u64,
+    ) -> color_eyre::Result<Vec<(AccountId32, u64)>> {
+        let query = staking_parachain::storage();
+        let storage = storage_at(api, configs).await?;
+        let issuance = storage
+            .fetch_or_default(&query.balances().total_issuance())
+            .await?;
+
+        let mut nodes = vec![];
+        let mut next = storage
+            .fetch(&query.voter_list().list_bags(bag_upper))
+            .await?
+            .and_then(|bag| bag.head);
+        while let Some(id) = next {
+            let node = storage
+                .fetch(&query.voter_list().list_nodes(&id))
+                .await?
+                .ok_or(eyre!("bag node {id} has no node"))?;
+            let score = vote_weight(&storage, &id, issuance)
+                .await?
+                .unwrap_or(node.score);
+            nodes.push((id, score));
+            next = node.next;
+        }
+
+        Ok(nodes)
+    }
+
+    /// How many calls like `call` fit in one batch, given the max weight of a normal extrinsic.
+    pub(crate) async fn calls_per_batch(
+        api: &OnlineClient<SubstrateConfig>,
+        call: RuntimeCall,
+    ) -> color_eyre::Result<usize> {
+        let limits = api
+            .constants()
+            .at(&staking_parachain::constants().system().block_weights())?;
+        let max = limits
+            .per_class
+            .normal
+            .max_extrinsic
+            .unwrap_or(limits.max_block);
+        let len = call.encode().len() as u32;
+        let info = api
+            .runtime_api()
+            .at_latest()
+            .await?
+            .call(
+                staking_parachain::apis()
+                    .transaction_payment_call_api()
+                    .query_call_info(call, len),
+            )
+            .await?;
+
+        let by_ref_time = max.ref_time / info.weight.ref_time.max(1);
+        let by_proof_size = max.proof_size / info.weight.proof_size.max(1);
+        // keep some room for the batch call itself.
+        Ok(((by_ref_time.min(by_proof_size) * 9 / 10) as usize).max(1))
+    }
+
+    /// Fetches the active bond of every nominator.
+    pub(crate) async fn nominator_stakes(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<Vec<Balance>> {
+        use std::collections::BTreeSet;
+
+        let nominators: BTreeSet<AccountId32> = get_nominators(api, configs)
+            .await?
+            .into_iter()
+            .filter_map(|n| match n {
+                Id(who) => Some(who),
+                _ => None,
+            })
+            .collect();
+
+        let mut stakes = vec![];
+        let query = staking_parachain::storage().staking().ledger_iter();
+        let mut ledgers = storage.iter(query).await?;
+        while let Some(Ok((_, ledger))) = ledgers.next().await {
+            if nominators.contains(&ledger.stash) {
+                stakes.push(ledger.active);
+            }
+        }
+
+        Ok(stakes)
+    }
+
+    /// Computes the Gini coefficient, the Herfindahl-Hirschman index and the share of the top
+    /// `top_k` of a set of stakes.
+    pub(crate) fn concentration(mut stakes: Vec<Balance>, top_k: usize) -> output::Concentration {
+        stakes.sort_unstable();
+        let n = stakes.len();
+        let total: Balance = stakes.iter().sum();
+        if n == 0 || total == 0 {
+            return output::Concentration {
+                count: n,
+                total,
+                gini: 0.0,
+                hhi: 0.0,
+                top_k,
+                top_k_share: 0.0,
+            };
+        }
+
+        let total_f = total as f64;
+        let weighted: f64 = stakes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i + 1) as f64 * *s as f64)
+            .sum();
+        let gini = 2.0 * weighted / (n as f64 * total_f) - (n as f64 + 1.0) / n as f64;
+        let hhi = stakes.iter().map(|s| (*s as f64 / total_f).powi(2)).sum();
+        let top: Balance = stakes.iter().rev().take(top_k).sum();
+
+        output::Concentration {
+            count: n,
+            total,
+            gini,
+            hhi,
+            top_k,
+            top_k_share: top as f64 / total_f,
+        }
+    }
+
+    /// Splits a set of stakes in `buckets` equally wide buckets between the lowest and highest
+    /// stake, together with their percentiles. `None` if there are no stakes.
+    pub(crate) fn bond_distribution(
+        mut stakes: Vec<Balance>,
+        buckets: usize,
+    ) -> Option<output::BondDistribution> {
+        stakes.sort_unstable();
+        let (&min, &max) = (stakes.first()?, stakes.last()?);
+        let percentile = |p: f64| {
+            let rank = ((stakes.len() as f64 * p).ceil() as usize).clamp(1, stakes.len());
+            stakes[rank - 1]
+        };
+
+        let buckets = buckets.max(1);
+        let width = ((max - min) / buckets as Balance).max(1);
+        let mut counts: Vec<output::BondBucket> = (0..buckets)
+            .map(|i| output::BondBucket {
+                from: min + width * i as Balance,
+                nominators: 0,
+            })
+            .collect();
+        for stake in stakes.iter() {
+            let i = (((stake - min) / width) as usize).min(buckets - 1);
+            counts[i].nominators += 1;
+        }
+
+        Some(output::BondDistribution {
+            nominators: stakes.len(),
+            min,
+            max,
+            mean: stakes.iter().sum::<Balance>() / stakes.len() as Balance,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            buckets: counts,
+        })
+    }
+
+    /// Converts a balance into vote weight, the same way the runtime's `CurrencyToVote` does.
+    pub(crate) fn to_vote(balance: Balance, issuance: Balance) -> u64 {
+        let factor = (issuance / u64::MAX as Balance).max(1);
+        (balance / factor).try_into().unwrap_or(u64::MAX)
+    }
+
+    /// Fetches all the nominators registered in the systen.
+    pub(crate) async fn get_nominators(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<Targets> {
+        let mut nominators = vec![];
+        let storage_query = staking_parachain::storage().staking().nominators_iter();
+
+        let mut results = storage_at(api, configs).await?.iter(storage_query).await?;
+        while let Some(Ok(kv)) = results.next().await {
+            let (k, _) = kv;
+            nominators.push(Id(account_from_key(&k)));
+        }
+
+        Ok(nominators)
+    }
+
+    /// Nominates a new random set of targets with a random `fraction` of the CLI-created
+    /// nominators which are currently nominating.
+    pub(crate) async fn rotate_nominations(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        fraction: f64,
+        nominations: usize,
+    ) -> color_eyre::Result<()> {
+        let mut nominating = vec![];
+        for account in state::State::load_for(&configs)
+            .await?
+            .accounts_with_role(state::Role::Nominator)
+        {
+            if staker_status(api, configs, &account.account_id())
+                .await?
+                .nominating
+            {
+                nominating.push(account.controller_keypair());
+            }
+        }
+        let n = ((nominating.len() as f64) * fraction).ceil() as usize;
+
+        let validators = get_validators(api, configs).await?;
+        let n_validators = validators.len();
+        let eligible = preflight_targets(api, configs, validators, n_validators).await?;
+        let max_nominations = max_nominations(api, configs).await?;
+
+        let mut quotas = std::collections::BTreeMap::new();
+        let mut calls = vec![];
+        for controller in nominating.choose_multiple(&mut rand::thread_rng(), n) {
+            let bond = active_bond(api, configs, &controller.public_key().into()).await?;
+            let quota = nominations_quota(api, configs, &mut quotas, bond).await?;
+            let mut targets = select_targets(nominations, eligible.clone());
+            targets.truncate(quota.map_or(max_nominations, |q| max_nominations.min(q as usize)));
+            if targets.is_empty() {
+                continue;
+            }
+            calls.push((
+                controller.clone(),
+                RuntimeCall::Staking(StakingCall::nominate { targets }),
+            ));
+        }
+
+        println!(
+            " rotating the nominations of {n}/{} nominators..",
+            nominating.len()
+        );
+        submit_all(api, configs, calls).await?;
+        println!("Rotation done for {n} nominator(s).");
+
+        Ok(())
+    }
+
+    /// The active bond of the ledger controlled by `controller`, 0 if it is not bonded.
+    pub(crate) async fn active_bond(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        controller: &AccountId32,
+    ) -> color_eyre::Result<Balance> {
+        Ok(storage_at(api, configs)
+            .await?
+            .fetch(&staking_parachain::storage().staking().ledger(controller))
+            .await?
+            .map(|ledger| ledger.active)
+            .unwrap_or_default())
+    }
+
+    /// The number of nominations allowed to a nominator bonding `bond`, from the
+    /// `StakingApi_nominations_quota` runtime API at the block of the reads, cached in `quotas`
+    /// by bond. `None` if the runtime does not expose the API.
+    pub(crate) async fn nominations_quota(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        quotas: &mut std::collections::BTreeMap<Balance, Option<u32>>,
+        bond: Balance,
+    ) -> color_eyre::Result<Option<u32>> {
+        if let Some(quota) = quotas.get(&bond) {
+            return Ok(*quota);
+        }
+        let quota = if api
+            .metadata()
+            .runtime_api_trait_by_name("StakingApi")
+            .is_some()
+        {
+            let call = subxt::dynamic::runtime_api_call(
+                "StakingApi",
+                "nominations_quota",
+                vec![subxt::dynamic::Value::u128(bond)],
+            );
+            let quota = api
+                .runtime_api()
+                .at(read_hash(api, configs).await?)
+                .call(call)
+                .await?;
+            quota.to_value()?.as_u128().map(|quota| quota as u32)
+        } else {
+            None
+        };
+        quotas.insert(bond, quota);
+
+        Ok(quota)
+    }
+
+    /// Nominations a nominator can have at most when the runtime does not expose its bound.
+    pub(crate) const DEFAULT_MAX_NOMINATIONS: usize = 16;
+
+    /// The `MaxNominations` bound of the runtime, the nominations quota of the largest bond, or
+    /// `DEFAULT_MAX_NOMINATIONS` if the runtime does not expose the quotas.
+    pub(crate) async fn max_nominations(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<usize> {
+        let quota = nominations_quota(api, configs, &mut Default::default(), Balance::MAX).await?;
+        if quota.is_none() {
+            println!(
+                " the runtime does not expose MaxNominations, assuming {DEFAULT_MAX_NOMINATIONS}."
+            );
+        }
+
+        Ok(quota.map_or(DEFAULT_MAX_NOMINATIONS, |quota| quota as usize))
+    }
+
+    /// Bonds `step` extra for every bonded CLI-created staker, topping up the ones without the
+    /// funds first, or with `decrease` unbonds it, skipping the stakers it would take below the
+    /// minimum bond of their role.
+    pub(crate) async fn ramp_bonds(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        step: Balance,
+        decrease: bool,
+    ) -> color_eyre::Result<()> {
+        let storage = storage_at(api, configs).await?;
+        let query = staking_parachain::storage().staking();
+        let min_nominator_bond = storage
+            .fetch_or_default(&query.min_nominator_bond())
+            .await?;
+        let min_validator_bond = storage
+            .fetch_or_default(&query.min_validator_bond())
+            .await?;
+
+        let mut calls = vec![];
+        let mut spendable = vec![];
+        let mut skipped = 0;
+        for account in state::State::load_for(&configs).await?.accounts.iter() {
+            let stash = account.account_id();
+            let status = staker_status(api, configs, &stash).await?;
+            if !status.bonded {
+                continue;
+            }
+            let Some(ledger) = storage
+                .fetch(&query.ledger(account.controller_id()))
+                .await?
+            else {
+                continue;
+            };
+            if decrease {
+                let min = if status.validating {
+                    min_validator_bond
+                } else {
+                    min_nominator_bond
+                };
+                if ledger.active < min.saturating_add(step) {
+                    skipped += 1;
+                    continue;
+                }
+                calls.push((
+                    account.controller_keypair(),
+                    RuntimeCall::Staking(StakingCall::unbond { value: step }),
+                ));
+            } else {
+                spendable.push((stash, status.free.saturating_sub(ledger.total)));
+                calls.push((
+                    account.keypair(),
+                    RuntimeCall::Staking(StakingCall::bond_extra {
+                        max_additional: step,
+                    }),
+                ));
+            }
+        }
+
+        if !decrease {
+            // keep the fees of the bond extra on top of the step.
+            let topped_up = top_up(api, configs, &configs.signer()?, &spendable, step * 2).await?;
+            if topped_up > 0 {
+                println!(" topped up {topped_up} staker(s).");
+            }
+        }
+        let n_calls = calls.len();
+        submit_all(api, configs, calls).await?;
+        println!(
+            " {} {step} for {n_calls} staker(s){}.",
+            if decrease { "unbonded" } else { "bonded extra" },
+            if skipped > 0 {
+                format!(", {skipped} at their minimum bond skipped")
+            } else {
+                String::new()
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Checks that all `targets` are registered, non-blocked validators and that there are at most
+    /// `max_nominations` of them. Invalid targets are replaced by random valid ones when possible,
+    /// and dropped otherwise.
+    pub(crate) async fn preflight_targets(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        targets: Targets,
+        max_nominations: usize,
+    ) -> color_eyre::Result<Targets> {
+        let mut eligible = vec![];
+        let storage_query = staking_parachain::storage().staking().validators_iter();
+        let mut results = storage_at(api, configs).await?.iter(storage_query).await?;
+        while let Some(Ok(kv)) = results.next().await {
+            let (k, prefs) = kv;
+            if !prefs.blocked {
+                let target: Target = Id(account_from_key(&k));
+                eligible.push(target);
+            }
+        }
+
+        let mut checked: Targets = vec![];
+        for target in targets.into_iter() {
+            if eligible.contains(&target) {
+                if !checked.contains(&target) {
+                    checked.push(target);
+                }
+                continue;
+            }
+            let replacement = eligible
+                .iter()
+                .filter(|t| !checked.contains(t))
+                .cloned()
+                .collect::<Vec<_>>()
+                .choose(&mut rand::thread_rng())
+                .cloned();
+            match replacement {
+                Some(replacement) => {
+                    println!(
+                        " target {} is not a valid nomination target, replaced by {}.",
+                        display_target(&target),
+                        display_target(&replacement)
+                    );
+                    checked.push(replacement);
+                }
+                None => println!(
+                    " target {} is not a valid nomination target, dropped.",
+                    display_target(&target)
+                ),
+            }
+        }
+        if checked.len() > max_nominations {
+            println!(
+                " {} targets selected, only nominating the first {max_nominations}.",
+                checked.len()
+            );
+            checked.truncate(max_nominations);
+        }
+        if checked.is_empty() {
+            bail!("no valid nomination targets left");
+        }
+
+        Ok(checked)
+    }
+
+    /// Formats a target as its SS58 address when possible.
+    pub(crate) fn display_target(target: &Target) -> String {
+        match target {
+            Id(account) => account.to_string(),
+            other => format!("{other:?}"),
+        }
+    }
+
+    /// Extracts the account from a storage key that ends with it, e.g. a `Twox64Concat` hashed key.
+    pub(crate) fn account_from_key(key: &[u8]) -> AccountId32 {
+        let account: [u8; 32] = key[key.len() - 32..]
+            .try_into()
+            .expect("32 bytes should fit");
+
+        AccountId32(account)
+    }
+
+    /// Selects a random `n` number of targets from a vec of validators.
+    pub(crate) fn select_targets(n: usize, validators: Targets) -> Targets {
+        validators
+            .choose_multiple(&mut rand::thread_rng(), n)
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    /// Call data that is submitted as is, already SCALE encoded.
+    pub(crate) struct RawCall(pub(crate) Vec<u8>);
+
+    impl subxt::tx::TxPayload for RawCall {
+        fn encode_call_data_to(
+            &self,
+            _metadata: &subxt::Metadata,
+            out: &mut Vec<u8>,
+        ) -> Result<(), subxt::Error> {
+            out.extend_from_slice(&self.0);
+            Ok(())
+        }
+    }
+
+    /// Whether the chain has the `Preimage` and `Referenda` pallets.
+    pub(crate) fn has_governance(api: &OnlineClient<SubstrateConfig>) -> bool {
+        let metadata = api.metadata();
+        ["Preimage", "Referenda"]
+            .iter()
+            .all(|p| metadata.pallet_by_name(p).is_some())
+    }
+
+    /// The `Preimage.note_preimage` and `Referenda.submit` call data of a referendum enacting
+    /// `preimage` `enactment_after` blocks after its approval, from `origin` given as
+    /// `<caller>:<origin>`. Built against the metadata of the chain.
+    pub(crate) fn referendum_calls(
+        api: &OnlineClient<SubstrateConfig>,
+        preimage: &[u8],
+        origin: &str,
+        enactment_after: u32,
+    ) -> color_eyre::Result<(Vec<u8>, Vec<u8>)> {
+        use subxt::dynamic::Value;
+
+        let Some((caller, origin)) = origin.split_once(':') else {
+            bail!("invalid origin {origin}, expected <caller>:<origin>");
+        };
+        let note = api.tx().call_data(&subxt::dynamic::tx(
+            "Preimage",
+            "note_preimage",
+            vec![Value::from_bytes(preimage)],
+        ))?;
+        let submit = api.tx().call_data(&subxt::dynamic::tx(
+            "Referenda",
+            "submit",
+            vec![
+                Value::unnamed_variant(caller, [Value::unnamed_variant(origin, [])]),
+                Value::named_variant(
+                    "Lookup",
+                    [
+                        (
+                            "hash",
+                            Value::from_bytes(sp_core::hashing::blake2_256(preimage)),
+                        ),
+                        ("len", Value::u128(preimage.len() as u128)),
+                    ],
+                ),
+                Value::unnamed_variant("After", [Value::u128(enactment_after as u128)]),
+            ],
+        ))?;
+
+        Ok((note, submit))
+    }
+
+    /// Index of the referendum of the first `Referenda.<name>` event of `events`, whose first
+    /// field is the referendum index.
+    pub(crate) fn referendum_event(
+        events: impl Iterator<Item = Result<subxt::events::EventDetails<SubstrateConfig>, subxt::Error>>,
+        name: &str,
+    ) -> color_eyre::Result<Option<u32>> {
+        for event in events {
+            let event = event?;
+            if event.pallet_name() == "Referenda" && event.variant_name() == name {
+                return Ok(Some(<u32 as subxt::ext::codec::Decode>::decode(
+                    &mut event.field_bytes(),
+                )?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Call data of the call `call` of `pallet` with its SCALE encoded `args`, for the calls of
+    /// pallets the bundled metadata does not know about.
+    pub(crate) fn raw_call(
+        metadata: &subxt::Metadata,
+        pallet: &str,
+        call: &str,
+        args: Vec<u8>,
+    ) -> color_eyre::Result<Vec<u8>> {
+        let pallet_metadata = metadata
+            .pallet_by_name(pallet)
+            .ok_or_else(|| eyre!("the runtime has no {pallet} pallet"))?;
+        let variant = pallet_metadata
+            .call_variant_by_name(call)
+            .ok_or_else(|| eyre!("the runtime has no {pallet}.{call} call"))?;
+
+        let mut data = vec![pallet_metadata.index(), variant.index];
+        data.extend(args);
+        Ok(data)
+    }
+
+    /// Call data of `call` for the chain of `metadata`: the pallet and call indices of the
+    /// bundled runtime are replaced with the chain's ones, matched by name. The arguments are
+    /// kept as they are, so `call` must not wrap other calls.
+    pub(crate) fn chain_call(
+        metadata: &subxt::Metadata,
+        call: &RuntimeCall,
+    ) -> color_eyre::Result<Vec<u8>> {
+        let bundled = bundled_metadata()?;
+        let encoded = call.encode();
+        let pallet = bundled
+            .pallet_by_index(encoded[0])
+            .ok_or_else(|| eyre!("no pallet {} in the bundled runtime", encoded[0]))?;
+        let variant = pallet
+            .call_variant_by_index(encoded[1])
+            .ok_or_else(|| eyre!("no call {} in the bundled {}", encoded[1], pallet.name()))?;
+
+        raw_call(
+            metadata,
+            pallet.name(),
+            &variant.name,
+            encoded[2..].to_vec(),
+        )
+    }
+
+    /// Decodes a hex encoded call, with or without the `0x` prefix.
+    pub(crate) fn decode_call(call: &str) -> color_eyre::Result<RuntimeCall> {
+        let bytes = hex::decode(call.trim_start_matches("0x"))?;
+
+        RuntimeCall::decode_all(&mut &bytes[..])
+            .map_err(|e| eyre!("{call} is not a valid runtime call: {e}"))
+    }
+
+    /// Builds the staking call described by the `encode` arguments.
+    pub(crate) fn staking_call(call: EncodeCall) -> color_eyre::Result<RuntimeCall> {
+        let destination = |payee: Payee| match payee {
+            Payee::Staked => Ok(RewardDestination::Staked),
+            Payee::Stash => Ok(RewardDestination::Stash),
+            Payee::Account(who) => Ok(RewardDestination::Account(who)),
+            Payee::None => Ok(RewardDestination::None),
+            Payee::Random => Err(eyre!("a random payee cannot be encoded")),
+        };
+        let call = match call {
+            EncodeCall::Bond { value, payee } => StakingCall::bond {
+                value,
+                payee: destination(payee)?,
+            },
+            EncodeCall::BondExtra { max_additional } => StakingCall::bond_extra { max_additional },
+            EncodeCall::Unbond { value } => StakingCall::unbond { value },
+            EncodeCall::Rebond { value } => StakingCall::rebond { value },
+            EncodeCall::WithdrawUnbonded { num_slashing_spans } => {
+                StakingCall::withdraw_unbonded { num_slashing_spans }
+            }
+            EncodeCall::Validate {
+                commission,
+                blocked,
+            } => StakingCall::validate {
+                prefs: staking_parachain::runtime_types::pallet_staking::ValidatorPrefs {
+                    commission: perbill(commission)?,
+                    blocked,
+                },
+            },
+            EncodeCall::Nominate { targets } => StakingCall::nominate {
+                targets: targets.into_iter().map(Id).collect(),
+            },
+            EncodeCall::Chill => StakingCall::chill,
+            EncodeCall::SetPayee { payee } => StakingCall::set_payee {
+                payee: destination(payee)?,
+            },
+            EncodeCall::PayoutStakers {
+                validator_stash,
+                era,
+                page: Some(page),
+            } => StakingCall::payout_stakers_by_page {
+                validator_stash,
+                era,
+                page,
+            },
+            EncodeCall::PayoutStakers {
+                validator_stash,
+                era,
+                page: None,
+            } => StakingCall::payout_stakers {
+                validator_stash,
+                era,
+            },
+            EncodeCall::ChillOther { stash } => StakingCall::chill_other { stash },
+            EncodeCall::Kick { who } => StakingCall::kick {
+                who: who.into_iter().map(Id).collect(),
+            },
+            EncodeCall::ReapStash {
+                stash,
+                num_slashing_spans,
+            } => StakingCall::reap_stash {
+                stash,
+                num_slashing_spans,
+            },
+            EncodeCall::ForceUnstake {
+                stash,
+                num_slashing_spans,
+            } => StakingCall::force_unstake {
+                stash,
+                num_slashing_spans,
+            },
+            EncodeCall::SetValidatorCount { new } => StakingCall::set_validator_count { new },
+            EncodeCall::SetMinCommission { percent } => StakingCall::set_min_commission {
+                new: perbill(percent)?,
+            },
+            EncodeCall::ForceApplyMinCommission { validator_stash } => {
+                StakingCall::force_apply_min_commission { validator_stash }
+            }
+            EncodeCall::ForceNoEras => StakingCall::force_no_eras,
+            EncodeCall::ForceNewEra => StakingCall::force_new_era,
+            EncodeCall::ForceNewEraAlways => StakingCall::force_new_era_always,
+        };
+
+        Ok(RuntimeCall::Staking(call))
+    }
+
+    /// A commission in `percent` as a `Perbill`.
+    pub(crate) fn perbill(percent: f64) -> color_eyre::Result<Perbill> {
+        if !(0.0..=100.0).contains(&percent) {
+            bail!("commission must be within [0, 100], got {percent}");
+        }
+
+        Ok(Perbill((percent * 10_000_000.0).round() as u32))
+    }
+
+    /// The `set_staking_configs` call applying `changes`. Configs without a change are left
+    /// untouched.
+    pub(crate) fn set_staking_configs(
+        changes: StakingConfigChanges,
+    ) -> color_eyre::Result<RuntimeCall> {
+        use staking_parachain::runtime_types::{
+            pallet_staking::pallet::pallet::ConfigOp, sp_arithmetic::per_things::Percent,
+        };
+
+        fn op<T, U>(
+            change: Option<ConfigChange<T>>,
+            f: impl FnOnce(T) -> color_eyre::Result<U>,
+        ) -> color_eyre::Result<ConfigOp<U>> {
+            Ok(match change {
+                None => ConfigOp::Noop,
+                Some(ConfigChange::Set(value)) => ConfigOp::Set(f(value)?),
+                Some(ConfigChange::Remove) => ConfigOp::Remove,
+            })
+        }
+
+        Ok(RuntimeCall::Staking(StakingCall::set_staking_configs {
+            min_nominator_bond: op(changes.min_nominator_bond, Ok)?,
+            min_validator_bond: op(changes.min_validator_bond, Ok)?,
+            max_nominator_count: op(changes.max_nominator_count, Ok)?,
+            max_validator_count: op(changes.max_validator_count, Ok)?,
+            chill_threshold: op(changes.chill_threshold, |p| Ok(Percent(p)))?,
+            min_commission: op(changes.min_commission, perbill)?,
+            max_staked_rewards: op(changes.max_staked_rewards, |p| Ok(Percent(p)))?,
+        }))
+    }
+
+    /// Whether the staking configs in `storage` are the ones `changes` sets or removes.
+    pub(crate) async fn staking_configs_applied(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        changes: &StakingConfigChanges,
+    ) -> color_eyre::Result<bool> {
+        fn applied<T: PartialEq>(change: &Option<ConfigChange<T>>, on_chain: Option<T>) -> bool {
+            match change {
+                None => true,
+                Some(ConfigChange::Set(value)) => on_chain.as_ref() == Some(value),
+                Some(ConfigChange::Remove) => on_chain.is_none(),
+            }
+        }
+
+        let query = staking_parachain::storage().staking();
+        // removed bonds and commission are zeroed rather than killed.
+        let min_nominator_bond = storage
+            .fetch_or_default(&query.min_nominator_bond())
+            .await?;
+        let min_validator_bond = storage
+            .fetch_or_default(&query.min_validator_bond())
+            .await?;
+        let min_commission = storage.fetch_or_default(&query.min_commission()).await?.0;
+        let commission_change = match &changes.min_commission {
+            Some(ConfigChange::Set(percent)) => Some(ConfigChange::Set(perbill(*percent)?.0)),
+            Some(ConfigChange::Remove) => Some(ConfigChange::Remove),
+            None => None,
+        };
+
+        Ok(applied(
+            &changes.min_nominator_bond,
+            (min_nominator_bond > 0).then_some(min_nominator_bond),
+        ) && applied(
+            &changes.min_validator_bond,
+            (min_validator_bond > 0).then_some(min_validator_bond),
+        ) && applied(
+            &changes.max_nominator_count,
+            storage.fetch(&query.max_nominators_count()).await?,
+        ) && applied(
+            &changes.max_validator_count,
+            storage.fetch(&query.max_validators_count()).await?,
+        ) && applied(
+            &changes.chill_threshold,
+            storage.fetch(&query.chill_threshold()).await?.map(|p| p.0),
+        ) && applied(
+            &commission_change,
+            (min_commission > 0).then_some(min_commission),
+        ) && applied(
+            &changes.max_staked_rewards,
+            storage
+                .fetch(&query.max_staked_rewards())
+                .await?
+                .map(|p| p.0),
+        ))
+    }
+
+    /// The metadata the runtime types are generated from, to name calls without a node.
+    pub(crate) fn bundled_metadata() -> color_eyre::Result<subxt::Metadata> {
+        let bytes = include_bytes!("../artifacts/staking-parachain.scale");
+
+        Ok(<subxt::Metadata as subxt::ext::codec::Decode>::decode(
+            &mut &bytes[..],
+        )?)
+    }
+
+    /// Wraps `calls` in the utility batch call selected with `--batch-mode`.
+    pub(crate) fn batch(configs: &Configs, calls: Vec<RuntimeCall>) -> RuntimeCall {
+        let call = match configs.batch_mode {
+            BatchMode::Batch => UtilityCall::batch { calls },
+            BatchMode::BatchAll => UtilityCall::batch_all { calls },
+            BatchMode::ForceBatch => UtilityCall::force_batch { calls },
+        };
+
+        RuntimeCall::Utility(call)
+    }
+
+    /// Prints the outcome of the failed items of a batch of `n` calls and returns their indices.
+    /// Items that were never dispatched because the batch was interrupted count as failed, and
+    /// with an unknown `n` of 0 only the item interrupting it does.
+    pub(crate) fn batch_failures(
+        events: &ExtrinsicEvents<SubstrateConfig>,
+        n: usize,
+    ) -> color_eyre::Result<Vec<usize>> {
+        let mut items = vec![];
+        for event in events.iter() {
+            let event = event?;
+            if event.as_event::<ItemCompleted>()?.is_some() {
+                items.push(BatchItem::Completed);
+            } else if let Some(ItemFailed { error }) = event.as_event::<ItemFailed>()? {
+                println!(" batch item {} failed: {error:?}", items.len());
+                items.push(BatchItem::Failed);
+            } else if let Some(BatchInterrupted { index, error }) =
+                event.as_event::<BatchInterrupted>()?
+            {
+                println!(" batch interrupted at item {index}: {error:?}");
+                items.push(BatchItem::Interrupted(index as usize));
+            }
+        }
+
+        Ok(failed_items(&items, n))
+    }
+
+    /// The outcome of a batch item, as reported by the utility pallet events.
+    #[derive(Clone, Copy, Debug)]
+    pub(crate) enum BatchItem {
+        Completed,
+        Failed,
+        /// The batch stopped at this item.
+        Interrupted(usize),
+    }
+
+    /// Indices of the failed items of a batch of `n` calls, given the outcomes of its items in
+    /// order. See `batch_failures`.
+    pub(crate) fn failed_items(items: &[BatchItem], n: usize) -> Vec<usize> {
+        let mut item = 0;
+        let mut failed = vec![];
+        for outcome in items {
+            match *outcome {
+                BatchItem::Completed => item += 1,
+                BatchItem::Failed => {
+                    failed.push(item);
+                    item += 1;
+                }
+                BatchItem::Interrupted(index) => failed.extend(index..n.max(index + 1)),
+            }
+        }
+
+        failed
+    }
+
+    /// Signs and submits `call` with `signer`. With `--sudo`, a call that needs the Root origin is
+    /// wrapped in `sudo.sudo` (or `sudo.sudo_unchecked_weight`) and signed by the configured sudo
+    /// key instead. Submissions rejected for a stale or already pooled nonce are re-signed with the
+    /// nonce the pool expects, up to `--max-resubmits` times. With `--max-tip`, submissions
+    /// rejected for their priority or a full pool are retried with a doubling tip, from an eighth
+    /// of the cap up to the cap.
+    pub(crate) async fn submit(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        call: RuntimeCall,
+        signer: &Keypair,
+    ) -> color_eyre::Result<Submission> {
+        let sudo_signer;
+        let (call, signer) = if configs.sudo && needs_root(&call) {
+            let call = Box::new(call);
+            let call = match configs.sudo_unchecked_weight.as_deref() {
+                Some([ref_time, proof_size]) => {
+                    RuntimeCall::Sudo(SudoCall::sudo_unchecked_weight {
+                        call,
+                        weight: Weight {
+                            ref_time: *ref_time,
+                            proof_size: *proof_size,
+                        },
+                    })
+                }
+                _ => RuntimeCall::Sudo(SudoCall::sudo { call }),
+            };
+            sudo_signer = configs.signer()?;
+            (call, &sudo_signer)
+        } else {
+            (call, signer)
+        };
+
+        submit_encoded(api, configs, RawCall(call.encode()), signer).await
+    }
+
+    /// Whether `call` needs the Root origin, which `--sudo` provides. Batches need it if any of
+    /// their calls does.
+    pub(crate) fn needs_root(call: &RuntimeCall) -> bool {
+        use staking_parachain::runtime_types::frame_system::pallet::Call as SystemCall;
+
+        match call {
+            RuntimeCall::Utility(
+                UtilityCall::batch { calls }
+                | UtilityCall::batch_all { calls }
+                | UtilityCall::force_batch { calls },
+            ) => calls.iter().any(needs_root),
+            RuntimeCall::Utility(
+                UtilityCall::dispatch_as { .. } | UtilityCall::with_weight { .. },
+            ) => true,
+            RuntimeCall::Balances(call) => matches!(
+                call,
+                BalancesCall::force_set_balance { .. }
+                    | BalancesCall::force_transfer { .. }
+                    | BalancesCall::force_unreserve { .. }
+            ),
+            RuntimeCall::Staking(call) => matches!(
+                call,
+                StakingCall::set_validator_count { .. }
+                    | StakingCall::increase_validator_count { .. }
+                    | StakingCall::scale_validator_count { .. }
+                    | StakingCall::force_no_eras
+                    | StakingCall::force_new_era
+                    | StakingCall::force_new_era_always
+                    | StakingCall::set_invulnerables { .. }
+                    | StakingCall::force_unstake { .. }
+                    | StakingCall::cancel_deferred_slash { .. }
+                    | StakingCall::set_staking_configs { .. }
+                    | StakingCall::set_min_commission { .. }
+            ),
+            RuntimeCall::System(call) => matches!(
+                call,
+                SystemCall::set_storage { .. }
+                    | SystemCall::kill_storage { .. }
+                    | SystemCall::kill_prefix { .. }
+            ),
+            _ => false,
+        }
+    }
+
+    /// The signed extension parameters of the extrinsics, with `tip` and the `--fee-asset` to
+    /// pay in, if any.
+    pub(crate) fn extrinsic_params(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        tip: u128,
+    ) -> color_eyre::Result<
+        <DefaultExtrinsicParams<SubstrateConfig> as ExtrinsicParams<SubstrateConfig>>::OtherParams,
+    > {
+        let builder = DefaultExtrinsicParamsBuilder::new();
+        let Some(asset) = configs.fee_asset else {
+            return Ok(builder.tip(tip).build());
+        };
+        let metadata = api.metadata();
+        if !metadata
+            .extrinsic()
+            .signed_extensions()
+            .iter()
+            .any(|extension| extension.identifier() == "ChargeAssetTxPayment")
+        {
+            bail!("--fee-asset {asset}: the runtime does not pay fees with ChargeAssetTxPayment");
+        }
+        Ok(builder.tip_of(tip, asset).build())
+    }
+
+    /// Signs and submits the already encoded `call` with `signer`, as [`submit`] but without
+    /// the sudo wrapping.
+    pub(crate) async fn submit_encoded(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        call: RawCall,
+        signer: &Keypair,
+    ) -> color_eyre::Result<Submission> {
+        wait_for_schedule(api, configs).await?;
+
+        if let Some(path) = configs.offline.as_ref() {
+            write_offline(api, configs, path, &call, signer).await?;
+            return Ok(Submission {
+                progress: None,
+                api: api.clone(),
+                resign: Some((call.0, signer.clone())),
+            });
+        }
+
+        let mut nonce = None;
+        let mut tip = 0;
+        let mut next_tip = configs.max_tip.map(|max_tip| (max_tip / 8).max(1));
+        let mut resubmits = 0;
+        let progress = loop {
+            let params = extrinsic_params(api, configs, tip)?;
+            let progress = match nonce {
+                None => {
+                    api.tx()
+                        .sign_and_submit_then_watch(&call, signer, params)
+                        .await
+                }
+                Some(nonce) => match api
+                    .tx()
+                    .create_signed_with_nonce(&call, signer, nonce, params)
+                {
+                    Ok(extrinsic) => extrinsic.submit_and_watch().await,
+                    Err(e) => Err(e),
+                },
+            };
+            let Err(e) = &progress else {
+                break progress;
+            };
+
+            // a nonce already used in the pool: re-sign with the next nonce the pool expects.
+            if (is_stale(e) || is_low_priority(e)) && resubmits < configs.max_resubmits {
+                let fresh = legacy_rpc(configs)
+                    .await?
+                    .system_account_next_index(&signer.public_key().into())
+                    .await?;
+                if nonce != Some(fresh) {
+                    println!(" submission rejected for its nonce, re-signing with nonce {fresh}..");
+                    configs.status.update(|s| s.nonce_resigns += 1);
+                    resubmits += 1;
+                    nonce = Some(fresh);
+                    continue;
+                }
+            }
+            // a pool too busy for the priority of the submission: retry with a higher tip.
+            match (next_tip, configs.max_tip) {
+                (Some(next), Some(max_tip)) if is_low_priority(e) => {
+                    println!(
+                        " submission rejected for its priority, retrying with a tip of {next}.."
+                    );
+                    configs.status.update(|s| {
+                        s.tip_escalations += 1;
+                        s.max_tip_paid = s.max_tip_paid.max(next);
+                    });
+                    tip = next;
+                    next_tip = (next < max_tip).then(|| next.saturating_mul(2).min(max_tip));
+                }
+                _ => break progress,
+            }
+        };
+        configs.status.update(|s| match progress {
+            Ok(_) => {
+                s.submitted += 1;
+                s.in_flight += 1;
+            }
+            Err(_) => s.errors += 1,
+        });
+
+        Ok(Submission {
+            progress: Some(progress?),
+            api: api.clone(),
+            resign: Some((call.0, signer.clone())),
+        })
+    }
+
+    /// Signs `call` with the next local nonce of `signer` and appends the hex encoded
+    /// extrinsic to `path`.
+    pub(crate) async fn write_offline(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        path: &std::path::Path,
+        call: &RawCall,
+        signer: &Keypair,
+    ) -> color_eyre::Result<()> {
+        use std::io::Write;
+
+        let who: AccountId32 = signer.public_key().into();
+        let known = configs
+            .offline_nonces
+            .lock()
+            .expect("nonces lock is never poisoned")
+            .get(&who)
+            .copied();
+        let nonce = match (known, configs.offline_nonce) {
+            (Some(nonce), _) | (None, Some(nonce)) => nonce,
+            (None, None) => {
+                legacy_rpc(configs)
+                    .await?
+                    .system_account_next_index(&who)
+                    .await?
+            }
+        };
+        let params = extrinsic_params(api, configs, 0)?;
+        let extrinsic = api
+            .tx()
+            .create_signed_with_nonce(call, signer, nonce, params)?;
+        configs
+            .offline_nonces
+            .lock()
+            .expect("nonces lock is never poisoned")
+            .insert(who, nonce + 1);
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "0x{}", hex::encode(extrinsic.encoded()))?;
+
+        Ok(())
+    }
+
+    /// A submitted extrinsic, with the call and key pair to re-sign it unless it was submitted
+    /// pre-signed. Extrinsics written offline have no progress.
+    pub(crate) struct Submission {
+        pub(crate) progress: Option<TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>>,
+        api: OnlineClient<SubstrateConfig>,
+        resign: Option<(Vec<u8>, Keypair)>,
+    }
+
+    /// Submits an already signed, SCALE encoded `extrinsic`.
+    pub(crate) async fn submit_signed(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        extrinsic: Vec<u8>,
+    ) -> color_eyre::Result<Submission> {
+        let progress = subxt::tx::SubmittableExtrinsic::from_bytes(api.clone(), extrinsic)
+            .submit_and_watch()
+            .await;
+        configs.status.update(|s| match progress {
+            Ok(_) => {
+                s.submitted += 1;
+                s.in_flight += 1;
+            }
+            Err(_) => s.errors += 1,
+        });
+
+        Ok(Submission {
+            progress: Some(progress?),
+            api: api.clone(),
+            resign: None,
+        })
+    }
+
+    /// JSON-RPC error code of the transaction pool for an invalid transaction, with the
+    /// `InvalidTransaction` description as data.
+    pub(crate) const POOL_INVALID_TX: i32 = 1010;
+    /// JSON-RPC error code of the transaction pool for a priority too low to replace a
+    /// transaction.
+    pub(crate) const POOL_TOO_LOW_PRIORITY: i32 = 1014;
+
+    /// The code and data of the JSON-RPC error a submission was rejected with by the node.
+    pub(crate) fn rpc_error(error: &subxt::Error) -> Option<(i32, Option<String>)> {
+        use subxt::{error::RpcError, ext::jsonrpsee::core::ClientError};
+
+        let subxt::Error::Rpc(RpcError::ClientError(e)) = error else {
+            return None;
+        };
+        match e.downcast_ref::<ClientError>()? {
+            ClientError::Call(e) => Some((
+                e.code(),
+                e.data().and_then(|d| serde_json::from_str(d.get()).ok()),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Whether a submission was rejected because its priority is too low to enter (or replace a
+    /// transaction in) the pool.
+    pub(crate) fn is_low_priority(error: &subxt::Error) -> bool {
+        rpc_error(error).is_some_and(|(code, _)| code == POOL_TOO_LOW_PRIORITY)
+    }
+
+    /// Whether a submission was rejected because its nonce was already used.
+    pub(crate) fn is_stale(error: &subxt::Error) -> bool {
+        // the description of `InvalidTransaction::Stale`.
+        const STALE: &str = "Transaction is outdated";
+
+        rpc_error(error).is_some_and(|(code, data)| {
+            code == POOL_INVALID_TX && data.is_some_and(|data| data == STALE)
+        })
+    }
+
+    /// Waits until the block the submissions are scheduled at, if any. The target is resolved
+    /// once per run, so that all the submissions of a command are released together.
+    pub(crate) async fn wait_for_schedule(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<()> {
+        let Some(schedule) = configs.submit_at.as_ref() else {
+            return Ok(());
+        };
+        let status = configs.status.snapshot();
+        if status.schedule_reached {
+            return Ok(());
+        }
+        let now = api.blocks().at_latest().await?.number();
+        let target = match status.scheduled_at {
+            Some(target) => target,
+            None => {
+                let target = schedule_target(configs, schedule, now)?;
+                configs.status.update(|s| s.scheduled_at = Some(target));
+                target
+            }
+        };
+
+        if now < target {
+            println!("> Holding the submissions until block #{target} (now #{now})..");
+            let mut blocks = subscribe_blocks(api, configs).await?;
+            loop {
+                if configs.fork_mode {
+                    new_block(configs).await?;
+                }
+                match blocks.next().await {
+                    Some(block) => {
+                        if block?.number() >= target {
+                            break;
+                        }
+                    }
+                    None => bail!("block subscription ended before block #{target}"),
+                }
+            }
+        }
+        configs.status.update(|s| s.schedule_reached = true);
+
+        Ok(())
+    }
+
+    /// Resolves `schedule` into a block after `now`. Phase anchors are predicted from the
+    /// last two recorded starts of the phase, one round period apart.
+    pub(crate) fn schedule_target(
+        configs: &Configs,
+        schedule: &Schedule,
+        now: u32,
+    ) -> color_eyre::Result<u32> {
+        let (phase, offset) = match schedule {
+            Schedule::Block(block) => return Ok(*block),
+            Schedule::Phase { phase, offset } => (*phase, *offset),
+        };
+
+        let starts: Vec<u32> = index::Index::load(&configs.index)?
+            .election_phases
+            .values()
+            .filter_map(|records| records.iter().find(|r| r.phase == phase))
+            .map(|r| r.block)
+            .collect();
+        let [.., previous, last] = starts[..] else {
+            bail!(
+                "the {phase} phase must have been recorded for two rounds to predict the next one, run `monitor election` first"
+            );
+        };
+        let period = i64::from(last.saturating_sub(previous));
+        if period == 0 {
+            bail!("recorded {phase} phase starts do not advance, cannot predict the next one");
+        }
+
+        let mut target = i64::from(last) + offset;
+        while target <= i64::from(now) {
+            target += period;
+        }
+
+        Ok(target as u32)
+    }
+
+    /// Waits until the extrinsic is finalized and succeeded, including the call it wraps in
+    /// `sudo.sudo`, if any. In fork mode, a block is produced first and inclusion in the best block
+    /// is enough. Otherwise, retractions and stuck extrinsics are handled by `wait_for_finalized`.
+    pub(crate) async fn wait_for_success(
+        configs: &Configs,
+        mut submission: Submission,
+    ) -> color_eyre::Result<ExtrinsicEvents<SubstrateConfig>> {
+        let Some(progress) = submission.progress.take() else {
+            bail!("extrinsic written offline, the rest of the command needs its outcome");
+        };
+        let phase = match submission.resign.as_ref() {
+            Some((call, _)) => {
+                RuntimeCall::decode_all(&mut &call[..]).map_or("other", |call| fee_phase(&call))
+            }
+            None => "pre-signed",
+        };
+        let events = if configs.fork_mode {
+            new_block(configs).await?;
+            match wait_for_in_block(progress).await {
+                Ok(in_block) => in_block.wait_for_success().await.map_err(Into::into),
+                Err(e) => Err(e),
+            }
+        } else {
+            wait_for_finalized(configs, submission, progress).await
+        };
+        let fee = match events.as_ref() {
+            Ok(events) => events.find_first::<TransactionFeePaid>()?,
+            Err(_) => None,
+        };
+        configs.status.update(|s| {
+            s.in_flight = s.in_flight.saturating_sub(1);
+            if events.is_err() {
+                s.errors += 1;
+            }
+            if let Some(TransactionFeePaid {
+                actual_fee, tip, ..
+            }) = fee
+            {
+                let fees = s.fees.entry(phase.to_string()).or_default();
+                fees.extrinsics += 1;
+                fees.fees += actual_fee;
+                fees.tips += tip;
+            }
+        });
+
+        let events = events?;
+        if let Some(Sudid {
+            sudo_result: Err(e),
+        }) = events.find_first::<Sudid>()?
+        {
+            configs.status.update(|s| s.errors += 1);
+            bail!("sudo call failed: {e:?}");
+        }
+
+        Ok(events)
+    }
+
+    /// As [`wait_for_success`], but the outcome of an extrinsic written with `--offline` is
+    /// unknown (`None`) rather than an error, for the callers that can go on without it.
+    pub(crate) async fn wait_for_outcome(
+        configs: &Configs,
+        submission: Submission,
+    ) -> color_eyre::Result<Option<ExtrinsicEvents<SubstrateConfig>>> {
+        if submission.progress.is_none() {
+            return Ok(None);
+        }
+
+        wait_for_success(configs, submission).await.map(Some)
+    }
+
+    /// The phase of a run `call` belongs to, for the fee accounting. Batches and sudo calls
+    /// belong to the phase of the calls they wrap.
+    pub(crate) fn fee_phase(call: &RuntimeCall) -> &'static str {
+        match call {
+            RuntimeCall::Balances(_) => "funding",
+            RuntimeCall::NominationPools(_) => "pools",
+            RuntimeCall::Staking(
+                StakingCall::bond { .. }
+                | StakingCall::bond_extra { .. }
+                | StakingCall::rebond { .. },
+            ) => "bonding",
+            RuntimeCall::Staking(StakingCall::nominate { .. }) => "nominating",
+            RuntimeCall::Staking(StakingCall::validate { .. }) => "validating",
+            RuntimeCall::Staking(
+                StakingCall::chill
+                | StakingCall::unbond { .. }
+                | StakingCall::withdraw_unbonded { .. },
+            ) => "exiting",
+            RuntimeCall::Staking(
+                StakingCall::payout_stakers { .. } | StakingCall::payout_stakers_by_page { .. },
+            ) => "payouts",
+            RuntimeCall::Utility(
+                UtilityCall::batch { calls }
+                | UtilityCall::batch_all { calls }
+                | UtilityCall::force_batch { calls },
+            ) => calls.first().map_or("other", fee_phase),
+            RuntimeCall::Sudo(
+                SudoCall::sudo { call }
+                | SudoCall::sudo_unchecked_weight { call, .. }
+                | SudoCall::sudo_as { call, .. },
+            ) => fee_phase(call),
+            _ => "other",
+        }
+    }
+
+    /// Follows the extrinsic until it is finalized and succeeded, rather than trusting its first
+    /// inclusion. When its block is retracted and the pool then drops it, the canonical blocks
+    /// since the retraction are searched for it before it is resubmitted. With `--stuck-after`,
+    /// an extrinsic neither included nor in the pool for that many blocks is resubmitted too.
+    /// Resubmissions are re-signed with the next nonce of the pool, up to `--max-resubmits`
+    /// times.
+    pub(crate) async fn wait_for_finalized(
+        configs: &Configs,
+        submission: Submission,
+        mut progress: TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<ExtrinsicEvents<SubstrateConfig>> {
+        use staking_parachain::system::events::ExtrinsicFailed;
+        use subxt::tx::TxStatus;
+
+        let mut blocks = subscribe_blocks(&submission.api, configs).await?;
+        // a retraction before the first followed block is searched for from the best one.
+        let mut now = legacy_rpc(configs)
+            .await?
+            .chain_get_header(None)
+            .await?
+            .map(|header| header.number)
+            .unwrap_or_default();
+        let mut waited = 0;
+        let mut in_block = false;
+        let mut watching = true;
+        let mut retracted_at = None;
+        let mut resubmits = 0;
+        loop {
+            let hash = progress.extrinsic_hash();
+            tokio::select! {
+                status = progress.next(), if watching => match status {
+                    Some(Ok(TxStatus::InFinalizedBlock(in_block))) => {
+                        return Ok(in_block.wait_for_success().await?)
+                    }
+                    Some(Ok(TxStatus::InBestBlock(block))) => {
+                        if retracted_at.is_some() {
+                            println!(" extrinsic {hash:?} included again in {:?}.", block.block_hash());
+                        }
+                        in_block = true;
+                    }
+                    Some(Ok(TxStatus::NoLongerInBestBlock)) => {
+                        println!(" extrinsic {hash:?} retracted from the best chain.");
+                        configs.status.update(|s| s.retractions += 1);
+                        retracted_at.get_or_insert(now);
+                        in_block = false;
+                    }
+                    Some(Ok(TxStatus::Error { message } | TxStatus::Invalid { message })) => {
+                        bail!("transaction failed: {message}")
+                    }
+                    Some(Ok(TxStatus::Dropped { .. })) | None => watching = false,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                },
+                block = blocks.next() => match block {
+                    Some(block) => {
+                        now = block?.number();
+                        waited += 1;
+                    }
+                    None => bail!("block subscription ended while waiting for the extrinsic"),
+                },
+            }
+            let stuck = configs.stuck_after.is_some_and(|n| waited >= n);
+            if in_block || (watching && !stuck) {
+                continue;
+            }
+
+            if watching {
+                let pooled = pending_extrinsics(configs)
+                    .await?
+                    .iter()
+                    .any(|xt| H256(sp_core::hashing::blake2_256(xt)) == hash);
+                if pooled {
+                    waited = 0;
+                    continue;
+                }
+            } else if let Some(from) = retracted_at {
+                if let Some(events) = find_included(&submission.api, configs, hash, from).await? {
+                    println!(
+                        " extrinsic {hash:?} found on the canonical chain after its retraction."
+                    );
+                    if let Some(failed) = events.find_first::<ExtrinsicFailed>()? {
+                        bail!("extrinsic failed: {:?}", failed.dispatch_error);
+                    }
+                    return Ok(events);
+                }
+            } else if configs.stuck_after.is_none() {
+                bail!("transaction {hash:?} dropped from the pool");
+            }
+            if resubmits >= configs.max_resubmits {
+                bail!("extrinsic {hash:?} not included after {resubmits} resubmission(s)");
+            }
+            let Some((call, signer)) = submission.resign.as_ref() else {
+                bail!("pre-signed extrinsic {hash:?} not included and cannot be re-signed");
+            };
+
+            let nonce = legacy_rpc(configs)
+                .await?
+                .system_account_next_index(&signer.public_key().into())
+                .await?;
+            progress = submission
+                .api
+                .tx()
+                .create_signed_with_nonce(
+                    &RawCall(call.clone()),
+                    signer,
+                    nonce,
+                    extrinsic_params(&submission.api, configs, 0)?,
+                )?
+                .submit_and_watch()
+                .await?;
+            println!(
+                " extrinsic {hash:?} neither included nor pooled, resubmitted as {:?} with nonce {nonce}.",
+                progress.extrinsic_hash()
+            );
+            configs.status.update(|s| s.resubmissions += 1);
+            resubmits += 1;
+            waited = 0;
+            watching = true;
+            retracted_at = None;
+        }
+    }
+
+    /// Searches the canonical chain, from block `from` to the best block, for the extrinsic
+    /// `hash` and returns its events.
+    pub(crate) async fn find_included(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        hash: H256,
+        from: u32,
+    ) -> color_eyre::Result<Option<ExtrinsicEvents<SubstrateConfig>>> {
+        let rpc = legacy_rpc(configs).await?;
+        let best = rpc
+            .chain_get_header(None)
+            .await?
+            .map(|header| header.number)
+            .unwrap_or_default();
+
+        for number in from..=best {
+            let block = api.blocks().at(block_hash(&rpc, number).await?).await?;
+            for extrinsic in block.extrinsics().await?.iter() {
+                let events = extrinsic?.events().await?;
+                if events.extrinsic_hash() == hash {
+                    return Ok(Some(events));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Waits until the extrinsic is included in a best (or finalized) block.
+    pub(crate) async fn wait_for_in_block(
+        mut progress: TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    ) -> color_eyre::Result<subxt::tx::TxInBlock<SubstrateConfig, OnlineClient<SubstrateConfig>>>
+    {
+        use subxt::tx::TxStatus;
+
+        while let Some(status) = progress.next().await {
+            match status? {
+                TxStatus::InBestBlock(in_block) | TxStatus::InFinalizedBlock(in_block) => {
+                    return Ok(in_block)
+                }
+                TxStatus::Error { message }
+                | TxStatus::Invalid { message }
+                | TxStatus::Dropped { message } => bail!("transaction failed: {message}"),
+                _ => continue,
+            }
+        }
+
+        Err(eyre!("transaction status stream ended before inclusion"))
+    }
+
+    /// Asks a chopsticks fork to produce a new block.
+    pub(crate) async fn new_block(configs: &Configs) -> color_eyre::Result<()> {
+        use subxt::backend::rpc::{rpc_params, RpcClient};
+
+        RpcClient::from_insecure_url(&configs.url)
+            .await?
+            .request::<serde_json::Value>("dev_newBlock", rpc_params![])
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fetches the Aura author of block `hash`, from the slot of its pre-runtime digest, and the
+    /// session validators the author is picked from.
+    pub(crate) async fn block_author(
+        api: &OnlineClient<SubstrateConfig>,
+        hash: H256,
+    ) -> color_eyre::Result<(Option<AccountId32>, Vec<AccountId32>)> {
+        use subxt::{config::substrate::DigestItem, ext::codec::Decode};
+        const AURA_ENGINE_ID: [u8; 4] = *b"aura";
+
+        let block = api.blocks().at(hash).await?;
+        let validators = api
+            .storage()
+            .at(hash)
+            .fetch_or_default(&staking_parachain::storage().session().validators())
+            .await?;
+
+        let slot = block.header().digest.logs.iter().find_map(|log| match log {
+            DigestItem::PreRuntime(AURA_ENGINE_ID, data) => u64::decode(&mut &data[..]).ok(),
+            _ => None,
+        });
+        let author = match (slot, validators.len()) {
+            (Some(slot), n) if n > 0 => Some(validators[(slot % n as u64) as usize].clone()),
+            _ => None,
+        };
+
+        Ok((author, validators))
+    }
+
+    /// Finds the first block at which `era` is the active era, by bisecting the blocks up to the
+    /// latest one. Needs the state of past blocks, i.e. an archive node.
+    pub(crate) async fn era_activation_block(
+        api: &OnlineClient<SubstrateConfig>,
+        rpc: &subxt::backend::legacy::LegacyRpcMethods<SubstrateConfig>,
+        era: u32,
+    ) -> color_eyre::Result<u32> {
+        async fn active_era_at(
+            api: &OnlineClient<SubstrateConfig>,
+            rpc: &subxt::backend::legacy::LegacyRpcMethods<SubstrateConfig>,
+            number: u32,
+        ) -> color_eyre::Result<Option<u32>> {
+            let hash = block_hash(rpc, number).await?;
+            Ok(api
+                .storage()
+                .at(hash)
+                .fetch(&staking_parachain::storage().staking().active_era())
+                .await?
+                .map(|info| info.index))
+        }
+
+        let latest = api.blocks().at_latest().await?.number();
+        if active_era_at(api, rpc, latest).await? < Some(era) {
+            bail!("era {era} is not active yet at #{latest}");
+        }
+        let (mut low, mut high) = (0, latest);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if active_era_at(api, rpc, mid).await? >= Some(era) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    /// Fetches the encoded extrinsics pending in the transaction pool.
+    pub(crate) async fn pending_extrinsics(configs: &Configs) -> color_eyre::Result<Vec<Vec<u8>>> {
+        use subxt::backend::{
+            legacy::rpc_methods::Bytes,
+            rpc::{rpc_params, RpcClient},
+        };
+
+        let pending = RpcClient::from_insecure_url(&configs.url)
+            .await?
+            .request::<Vec<Bytes>>("author_pendingExtrinsics", rpc_params![])
+            .await?;
+
+        Ok(pending.into_iter().map(|xt| xt.0).collect())
+    }
+
+    /// Decodes the signer, nonce and call of a signed (v4) extrinsic. The signed extensions are
+    /// skipped by their metadata types. Unsigned extrinsics are `None`.
+    pub(crate) fn decode_signed_extrinsic(
+        metadata: &subxt::Metadata,
+        extrinsic: &[u8],
+    ) -> color_eyre::Result<Option<(AccountId32, u64, RuntimeCall)>> {
+        use staking_parachain::runtime_types::sp_runtime::MultiSignature;
+        use subxt::ext::{
+            codec::{Compact, Decode},
+            scale_decode::visitor::{decode_with_visitor, IgnoreVisitor},
+        };
+
+        let cursor = &mut &extrinsic[..];
+        let _len = Compact::<u32>::decode(cursor)?;
+        if u8::decode(cursor)? & 0b1000_0000 == 0 {
+            return Ok(None);
+        }
+        let Id(signer) = MultiAddress::<AccountId32, ()>::decode(cursor)? else {
+            bail!("extrinsic signed by a non-account address");
+        };
+        let _signature = MultiSignature::decode(cursor)?;
+
+        let mut nonce = 0;
+        for extension in metadata.extrinsic().signed_extensions() {
+            if extension.identifier() == "CheckNonce" {
+                nonce = Compact::<u64>::decode(cursor)?.0;
+            } else {
+                decode_with_visitor(
+                    cursor,
+                    extension.extra_ty(),
+                    metadata.types(),
+                    IgnoreVisitor,
+                )
+                .map_err(|e| eyre!("failed to decode {}: {e}", extension.identifier()))?;
+            }
+        }
+
+        Ok(Some((signer, nonce, RuntimeCall::decode(cursor)?)))
+    }
+
+    /// The staking related calls in `call`, looking into utility batches and sudo calls.
+    pub(crate) fn staking_calls(call: &RuntimeCall) -> Vec<&RuntimeCall> {
+        match call {
+            RuntimeCall::Staking(_)
+            | RuntimeCall::VoterList(_)
+            | RuntimeCall::NominationPools(_)
+            | RuntimeCall::FastUnstake(_) => vec![call],
+            RuntimeCall::Utility(
+                UtilityCall::batch { calls }
+                | UtilityCall::batch_all { calls }
+                | UtilityCall::force_batch { calls },
+            ) => calls.iter().flat_map(staking_calls).collect(),
+            RuntimeCall::Sudo(
+                SudoCall::sudo { call }
+                | SudoCall::sudo_unchecked_weight { call, .. }
+                | SudoCall::sudo_as { call, .. },
+            ) => staking_calls(call),
+            _ => vec![],
+        }
+    }
+
+    /// The `Pallet.call` name of `call`, from its pallet and call indices.
+    pub(crate) fn call_name(metadata: &subxt::Metadata, call: &RuntimeCall) -> String {
+        let encoded = call.encode();
+        let pallet = metadata.pallet_by_index(encoded[0]);
+        let variant = pallet
+            .as_ref()
+            .and_then(|p| p.call_variant_by_index(encoded[1]));
+
+        match (pallet, variant) {
+            (Some(pallet), Some(variant)) => format!("{}.{}", pallet.name(), variant.name),
+            _ => format!("{}.{}", encoded[0], encoded[1]),
+        }
+    }
+
+    /// The call tree of `call`, with the calls nested in utility and sudo wrappers as children.
+    /// Only the calls without children carry their arguments.
+    pub(crate) fn call_tree(metadata: &subxt::Metadata, call: &RuntimeCall) -> output::DecodedCall {
+        let nested: Vec<&RuntimeCall> = match call {
+            RuntimeCall::Utility(
+                UtilityCall::batch { calls }
+                | UtilityCall::batch_all { calls }
+                | UtilityCall::force_batch { calls },
+            ) => calls.iter().collect(),
+            RuntimeCall::Utility(
+                UtilityCall::as_derivative { call, .. }
+                | UtilityCall::dispatch_as { call, .. }
+                | UtilityCall::with_weight { call, .. },
+            )
+            | RuntimeCall::Sudo(
+                SudoCall::sudo { call }
+                | SudoCall::sudo_unchecked_weight { call, .. }
+                | SudoCall::sudo_as { call, .. },
+            ) => vec![call],
+            _ => vec![],
+        };
+
+        // `Pallet(call { args })`, of which only `{ args }` is kept.
+        let debug = format!("{call:?}");
+        let args = if nested.is_empty() {
+            debug
+                .split_once('(')
+                .and_then(|(_, inner)| inner.strip_suffix(')')?.split_once(' '))
+                .map(|(_, args)| args.to_string())
+        } else {
+            None
+        };
+
+        output::DecodedCall {
+            call: call_name(metadata, call),
+            args,
+            calls: nested.into_iter().map(|c| call_tree(metadata, c)).collect(),
+        }
+    }
+
+    /// Reads the value of the gauge `name` from a Prometheus text exposition, summed over its
+    /// labels.
+    pub(crate) fn prometheus_gauge(body: &str, name: &str) -> Option<u64> {
+        let values: Vec<f64> = body
+            .lines()
+            .filter(|line| {
+                line.strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with([' ', '{']))
+            })
+            .filter_map(|line| line.rsplit(' ').next()?.parse().ok())
+            .collect();
+
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() as u64)
+    }
+
+    /// The storage at the block selected by `--read-block` or `--read-at`.
+    pub(crate) async fn storage_at(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>> {
+        Ok(api.storage().at(read_hash(api, configs).await?))
+    }
+
+    /// Hash of the block the reads are made at: `--read-block`, or the latest finalized or best
+    /// block as of `--read-at`.
+    pub(crate) async fn read_hash(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<H256> {
+        let hash = match (configs.read_block, configs.read_at) {
+            (Some(hash), _) => hash,
+            (None, ReadAt::Finalized) => api.blocks().at_latest().await?.hash(),
+            (None, ReadAt::Best) => legacy_rpc(configs)
+                .await?
+                .chain_get_block_hash(None)
+                .await?
+                .ok_or(eyre!("no best block"))?,
+        };
+
+        Ok(hash)
+    }
+
+    /// Prints `result` in the format selected by `--output`, with the on-chain identities of the
+    /// accounts it shows unless `--no-identities`. The accounts are shown by their address if
+    /// the identities cannot be fetched.
+    pub(crate) async fn render<R: output::Render>(
+        configs: &Configs,
+        result: &R,
+    ) -> color_eyre::Result<()> {
+        if configs.no_identities {
+            return output::render(configs.output, result);
+        }
+
+        let mut accounts = std::collections::BTreeSet::new();
+        output::accounts(&serde_json::to_value(result)?, &mut accounts);
+        let identities = identities(configs, accounts).await.unwrap_or_else(|e| {
+            eprintln!("! identities not resolved, showing the addresses: {e}");
+            Default::default()
+        });
+
+        output::render_with_identities(configs.output, result, &identities)
+    }
+
+    /// The display names of the `accounts` with an identity, as `parent/sub` for sub-identities.
+    /// Empty if the identity chain has no identity pallet.
+    pub(crate) async fn identities(
+        configs: &Configs,
+        accounts: std::collections::BTreeSet<AccountId32>,
+    ) -> color_eyre::Result<std::collections::BTreeMap<AccountId32, String>> {
+        use subxt::ext::scale_value::At;
+
+        let url = configs.identity_url.as_ref().unwrap_or(&configs.url);
+        let api = OnlineClient::<SubstrateConfig>::from_url(url).await?;
+        let mut identities = std::collections::BTreeMap::new();
+        if api.metadata().pallet_by_name("Identity").is_none() {
+            return Ok(identities);
+        }
+        let storage = api.storage().at_latest().await?;
+
+        for account in accounts {
+            if let Some(name) = identity_display(&storage, &account).await? {
+                identities.insert(account, name);
+                continue;
+            }
+            let key = vec![subxt::dynamic::Value::from_bytes(account.0)];
+            let Some(sub) = storage
+                .fetch(&subxt::dynamic::storage("Identity", "SuperOf", key))
+                .await?
+            else {
+                continue;
+            };
+            let sub = sub.to_value()?;
+            let parent = sub.at(0).map(value_bytes).unwrap_or_default();
+            let Ok(parent) = <[u8; 32]>::try_from(parent) else {
+                continue;
+            };
+            let parent = AccountId32(parent);
+            let parent_name = identity_display(&storage, &parent)
+                .await?
+                .unwrap_or_else(|| parent.to_string());
+            let sub_name = sub.at(1).and_then(identity_data).unwrap_or_default();
+            identities.insert(account, format!("{parent_name}/{sub_name}"));
+        }
+
+        Ok(identities)
+    }
+
+    /// The display name of the identity of `account`, if it has one.
+    async fn identity_display(
+        storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        account: &AccountId32,
+    ) -> color_eyre::Result<Option<String>> {
+        use subxt::ext::scale_value::At;
+
+        let key = vec![subxt::dynamic::Value::from_bytes(account.0)];
+        let Some(registration) = storage
+            .fetch(&subxt::dynamic::storage("Identity", "IdentityOf", key))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let registration = registration.to_value()?;
+        // newer runtimes store the registration along with a username, as a tuple.
+        let info = registration
+            .at("info")
+            .or_else(|| registration.at(0).and_then(|r| r.at("info")));
+
+        Ok(info.and_then(|i| i.at("display")).and_then(identity_data))
+    }
+
+    /// The text of a raw identity `Data` field.
+    fn identity_data(data: &subxt::ext::scale_value::Value<u32>) -> Option<String> {
+        use subxt::ext::scale_value::ValueDef;
+
+        let ValueDef::Variant(variant) = &data.value else {
+            return None;
+        };
+        if !variant.name.starts_with("Raw") {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&value_bytes(data)).into_owned();
+
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// The bytes of a value made of `u8` primitives, e.g. an account or a raw identity field.
+    fn value_bytes(value: &subxt::ext::scale_value::Value<u32>) -> Vec<u8> {
+        use subxt::ext::scale_value::{Primitive, ValueDef};
+
+        match &value.value {
+            ValueDef::Composite(composite) => composite.values().flat_map(value_bytes).collect(),
+            ValueDef::Variant(variant) => variant.values.values().flat_map(value_bytes).collect(),
+            ValueDef::Primitive(Primitive::U128(byte)) => vec![*byte as u8],
+            _ => vec![],
+        }
+    }
+
+    /// Connects to the legacy RPC methods of the node, once per `--url`: later calls reuse the
+    /// connection.
+    pub(crate) async fn legacy_rpc(
+        configs: &Configs,
+    ) -> color_eyre::Result<subxt::backend::legacy::LegacyRpcMethods<SubstrateConfig>> {
+        use std::{
+            collections::BTreeMap,
+            sync::{Mutex, OnceLock},
+        };
+        use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
+
+        static CLIENTS: OnceLock<Mutex<BTreeMap<String, RpcClient>>> = OnceLock::new();
+        let clients = CLIENTS.get_or_init(Default::default);
+        let cached = clients
+            .lock()
+            .expect("lock is not poisoned")
+            .get(&configs.url)
+            .cloned();
+        let client = match cached {
+            Some(client) => client,
+            None => {
+                let client = RpcClient::from_insecure_url(&configs.url).await?;
+                clients
+                    .lock()
+                    .expect("lock is not poisoned")
+                    .insert(configs.url.clone(), client.clone());
+                client
+            }
+        };
+
+        Ok(LegacyRpcMethods::new(client))
+    }
+
+    /// Blocks until the node is reachable, reports itself synced, with peers if it should have
+    /// some, and produces a new best block, failing after `timeout`.
+    pub(crate) async fn wait_for_sync(
+        configs: &Configs,
+        timeout: std::time::Duration,
+    ) -> color_eyre::Result<()> {
+        const POLL: std::time::Duration = std::time::Duration::from_secs(2);
+
+        let deadline = std::time::Instant::now() + timeout;
+        let timed_out = |what: &'static str| async move {
+            if std::time::Instant::now() >= deadline {
+                bail!("{} {what} after {}s", configs.url, timeout.as_secs());
+            }
+            tokio::time::sleep(POLL).await;
+            color_eyre::Result::<()>::Ok(())
+        };
+
+        println!("> Waiting for {} to be synced..", configs.url);
+        let rpc = loop {
+            match legacy_rpc(configs).await {
+                Ok(rpc) => break rpc,
+                Err(_) => timed_out("not reachable").await?,
+            }
+        };
+        loop {
+            let health = rpc.system_health().await?;
+            if !health.is_syncing && (health.peers > 0 || !health.should_have_peers) {
+                break;
+            }
+            timed_out("not synced").await?;
+        }
+        let rpc = &rpc;
+        let best = || async move {
+            color_eyre::Result::<_>::Ok(
+                rpc.chain_get_header(None)
+                    .await?
+                    .ok_or(eyre!("no best block"))?
+                    .number,
+            )
+        };
+        let start = best().await?;
+        while best().await? <= start {
+            timed_out("not producing blocks").await?;
+        }
+        println!(" synced, at block #{}.", best().await?);
+
+        Ok(())
+    }
+
+    /// Fails if the connected chain does not match `--expect-spec`, `--expect-genesis` or
+    /// `--min-spec-version`.
+    pub(crate) async fn ensure_chain(configs: &Configs) -> color_eyre::Result<()> {
+        if configs.expect_spec.is_none()
+            && configs.expect_genesis.is_none()
+            && configs.min_spec_version.is_none()
+        {
+            return Ok(());
+        }
+        let rpc = legacy_rpc(configs).await?;
+
+        let version = rpc.state_get_runtime_version(None).await?;
+        if let Some(expected) = configs.expect_spec.as_ref() {
+            let spec = version
+                .other
+                .get("specName")
+                .and_then(|name| name.as_str())
+                .unwrap_or_default();
+            if spec != expected {
+                bail!("{} runs spec {spec}, expected {expected}", configs.url);
+            }
+        }
+        if let Some(min) = configs.min_spec_version {
+            if version.spec_version < min {
+                bail!(
+                    "{} runs spec version {}, this command requires at least {min}",
+                    configs.url,
+                    version.spec_version
+                );
+            }
+        }
+        if let Some(expected) = configs.expect_genesis {
+            let genesis = rpc.genesis_hash().await?;
+            if genesis != expected {
+                bail!(
+                    "{} has genesis {genesis:?}, expected {expected:?}",
+                    configs.url
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `path` with `chain` appended to its file stem, e.g. `state-<chain>.json` for `state.json`.
+    pub(crate) fn chain_path(path: &std::path::Path, chain: &str) -> PathBuf {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut name = format!("{stem}-{chain}");
+        if let Some(extension) = path.extension() {
+            name.push('.');
+            name.push_str(&extension.to_string_lossy());
+        }
+        path.with_file_name(name)
+    }
+
+    /// Fetches the hash of block `number` with the `rpc` of the command, for the commands
+    /// scanning block ranges.
+    pub(crate) async fn block_hash(
+        rpc: &subxt::backend::legacy::LegacyRpcMethods<SubstrateConfig>,
+        number: u32,
+    ) -> color_eyre::Result<H256> {
+        rpc.chain_get_block_hash(Some(number.into()))
+            .await?
+            .ok_or(eyre!("block {number} not found"))
+    }
+
+    /// Subscribes to the finalized blocks, or to the best ones in fork mode.
+    pub(crate) async fn subscribe_blocks(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+    ) -> color_eyre::Result<
+        subxt::backend::StreamOfResults<
+            subxt::blocks::Block<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+        >,
+    > {
+        use subxt::ext::futures::StreamExt;
+
+        let blocks = if configs.fork_mode {
+            api.blocks().subscribe_best().await?
+        } else {
+            api.blocks().subscribe_finalized().await?
+        };
+        let status = configs.status.clone();
+        let blocks = blocks.inspect(move |block| {
+            if let Ok(block) = block {
+                status.update(|s| s.last_block = Some(block.number()));
+            }
+        });
+
+        Ok(subxt::backend::StreamOf::new(Box::pin(blocks)))
+    }
+
+    /// Submits `call` wrapped in `sudo.sudo`, signed by the signer as the sudo key, and waits
+    /// until it succeeded. Its events are `None` when written with `--offline`.
+    pub(crate) async fn submit_sudo(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        call: RuntimeCall,
+    ) -> color_eyre::Result<Option<ExtrinsicEvents<SubstrateConfig>>> {
+        let call = RuntimeCall::Sudo(SudoCall::sudo {
+            call: Box::new(call),
+        });
+        let (call, signer) = (RawCall(call.encode()), configs.signer()?);
+        let progress = match configs.offline.as_ref() {
+            Some(path) => {
+                write_offline(api, configs, path, &call, &signer).await?;
+                None
+            }
+            None => Some(
+                api.tx()
+                    .sign_and_submit_then_watch(&call, &signer, extrinsic_params(api, configs, 0)?)
+                    .await?,
+            ),
+        };
+        let submission = Submission {
+            progress,
+            api: api.clone(),
+            resign: Some((call.0, signer)),
+        };
+        wait_for_outcome(configs, submission).await
+    }
+
+    /// Submits all calls, each signed by its key pair, and waits until the last one is finalized
+    /// (or included in a block, in fork mode). With a configured rate, at most that many calls
+    /// are submitted before waiting for the next block. The calls wrapped in `sudo.sudo` by
+    /// `--sudo` are all waited for, to check the outcome of the wrapped call.
+    pub(crate) async fn submit_all(
+        api: &OnlineClient<SubstrateConfig>,
+        configs: &Configs,
+        calls: Vec<(Keypair, RuntimeCall)>,
+    ) -> color_eyre::Result<()> {
+        let n_calls = calls.len() as u64;
+        let mut blocks = match configs.rate {
+            Some(_) => Some(subscribe_blocks(api, configs).await?),
+            None => None,
+        };
+        let mut sudo_submissions = vec![];
+        let mut it = calls.into_iter().enumerate().peekable();
+        while let Some((i, (pair, call))) = it.next() {
+            let sudo = configs.sudo && needs_root(&call);
+            let submission = submit(api, configs, call, &pair).await?;
+            // with a rate, wait for the next block once the block's share was submitted.
+            if let (Some(rate), Some(blocks)) = (configs.rate, blocks.as_mut()) {
+                if (i + 1) % rate.get() == 0 && it.peek().is_some() {
+                    if configs.fork_mode {
+                        new_block(configs).await?;
+                    }
+                    match blocks.next().await {
+                        Some(block) => println!(" {} submitted by #{}.", i + 1, block?.number()),
+                        None => bail!("block subscription ended after {} submissions", i + 1),
+                    }
+                }
+            }
+            if sudo {
+                sudo_submissions.push(submission);
+                continue;
+            }
+            // make sure all calls went through before progressing.
+            if let (None, Some(mut progress)) = (it.peek(), submission.progress) {
+                if configs.fork_mode {
+                    new_block(configs).await?;
+                    wait_for_in_block(progress).await?;
+                } else {
+                    while progress.next().await.is_some() {}
+                }
+            }
+        }
+        // waited for one by one, which already accounts for them in flight.
+        let n_calls = n_calls - sudo_submissions.len() as u64;
+        for submission in sudo_submissions {
+            wait_for_outcome(configs, submission).await?;
+        }
+        configs
+            .status
+            .update(|s| s.in_flight = s.in_flight.saturating_sub(n_calls));
+
+        Ok(())
+    }
+
+    /// The name of an election phase, without its data.
+    pub(crate) fn phase_name(phase: &Phase<u32>) -> &'static str {
+        match phase {
+            Phase::Halted => "Halted",
+            Phase::Off => "Off",
+            Phase::Signed => "Signed",
+            Phase::SignedValidation(_) => "SignedValidation",
+            Phase::Unsigned(_) => "Unsigned",
+            Phase::Snapshot(_) => "Snapshot",
+            Phase::Export(_) => "Export",
+            Phase::Emergency => "Emergency",
+        }
+    }
+
+    /// The configured duration, in blocks, of the election phases with a bounded duration. The
+    /// snapshot and export phases take one block per page.
+    pub(crate) fn phase_durations(
+        api: &OnlineClient<SubstrateConfig>,
+    ) -> color_eyre::Result<std::collections::BTreeMap<&'static str, u32>> {
+        let constants = staking_parachain::constants().election_provider_multi_block();
+        let pages = api.constants().at(&constants.pages())?;
+
+        Ok([
+            ("Snapshot", pages),
+            ("Signed", api.constants().at(&constants.signed_phase())?),
+            (
+                "SignedValidation",
+                api.constants().at(&constants.signed_validation_phase())?,
+            ),
+            ("Unsigned", api.constants().at(&constants.unsigned_phase())?),
+            ("Export", pages),
+        ]
+        .into_iter()
+        .collect())
+    }
+
+    /// Starts serving the status of the running daemon, if a status address is configured.
+    pub(crate) async fn serve_status(configs: &Configs) -> color_eyre::Result<()> {
+        let Some(addr) = configs.status_addr else {
+            return Ok(());
+        };
+        let accounts_managed = state::State::load_for(&configs).await?.accounts.len();
+        configs
+            .status
+            .update(|s| s.accounts_managed = accounts_managed);
+
+        status::serve(addr, configs.status.clone()).await
+    }
+
+    /// Raises an alert about an unexpected chain condition, also posted to the webhook.
+    pub(crate) async fn alert(configs: &Configs, message: &str) {
+        eprintln!("! ALERT: {message}");
+        configs.status.update(|s| s.alerts += 1);
+        notify(configs, "alert", message).await;
+    }
+
+    /// Time after which a webhook post is given up.
+    pub(crate) const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+    /// Posts `message` about an `event` to the webhook, if any. The payload is shaped for
+    /// Slack or Discord based on the webhook URL, and is `{"event", "message"}` otherwise.
+    /// Failures to post are only logged, and posts time out after `WEBHOOK_TIMEOUT`, so that a
+    /// webhook never stops or stalls a daemon.
+    pub(crate) async fn notify(configs: &Configs, event: &str, message: &str) {
+        let Some(url) = configs.webhook.as_ref() else {
+            return;
+        };
+        let payload = if url.contains("hooks.slack.com") {
+            serde_json::json!({ "text": format!("[{event}] {message}") })
+        } else if url.contains("discord.com") || url.contains("discordapp.com") {
+            serde_json::json!({ "content": format!("[{event}] {message}") })
+        } else {
+            serde_json::json!({ "event": event, "message": message })
+        };
+
+        let response = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+            Ok(client) => client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = response {
+            eprintln!("! failed to post to the webhook: {e}");
+        }
+    }
+
+    /// Notifies the slashes, era payouts and failed staking elections in `events`.
+    pub(crate) async fn notify_staking_events(
+        configs: &Configs,
+        events: &subxt::events::Events<SubstrateConfig>,
+    ) -> color_eyre::Result<()> {
+        use staking_parachain::staking::events::{EraPaid, Slashed, StakingElectionFailed};
+
+        for slash in events.find::<Slashed>() {
+            let slash = slash?;
+            alert(
+                configs,
+                &format!("{} slashed by {}", slash.staker, slash.amount),
+            )
+            .await;
+        }
+        for paid in events.find::<EraPaid>() {
+            let paid = paid?;
+            configs.status.update(|s| s.last_era = Some(paid.era_index));
+            notify(
+                configs,
+                "era",
+                &format!(
+                    "era {} ended, {} paid to validators",
+                    paid.era_index, paid.validator_payout
+                ),
+            )
+            .await;
+        }
+        if events.has::<StakingElectionFailed>()? {
+            alert(configs, "staking election failed").await;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a JSON file into `T`. A missing file is read as `T::default()`.
+    pub(crate) fn read_json<T: serde::de::DeserializeOwned + Default>(
+        path: &std::path::Path,
+    ) -> color_eyre::Result<T> {
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        let value = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        Ok(value)
+    }
+
+    /// Writes `value` as pretty JSON into a file, overwriting it.
+    pub(crate) fn write_json<T: serde::Serialize>(
+        path: &std::path::Path,
+        value: &T,
+    ) -> color_eyre::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(value)?)?;
+
+        Ok(())
+    }
+
+    /// Generates a key pair from an init seed.
+    pub(crate) fn signer_from_seed(init_seed: &str) -> Keypair {
+        Keypair::from_seed(seed_bytes(init_seed)).expect("generate keypair should be ok")
+    }
+
+    /// The 32 bytes secret seed of the key pair derived from `init_seed`.
+    pub(crate) fn seed_bytes(init_seed: &str) -> [u8; 32] {
+        let mut seed = [0; 32];
+        let mut buffer = &mut seed[..];
+        buffer.write_all(init_seed.as_bytes()).unwrap();
+
+        seed
+    }
+}
+
+mod state {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::{collections::BTreeSet, path::Path};
+
+    /// Role of an account created by the CLI.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub(crate) enum Role {
+        Validator,
+        Nominator,
+    }
+
+    impl FromStr for Role {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "validator" => Ok(Self::Validator),
+                "nominator" => Ok(Self::Nominator),
+                _ => Err(format!("unknown role {s}")),
+            }
+        }
+    }
+
+    /// An account created by the CLI. Its key pair is derived from the seed.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub(crate) struct ManagedAccount {
+        pub(crate) seed: String,
+        pub(crate) role: Role,
+        /// Seed of the controller, if it is not the stash.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) controller_seed: Option<String>,
+        /// Genesis hash of the chain the account was created on, unset for the accounts
+        /// created offline until they are used on a chain.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) genesis: Option<H256>,
+        #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+        pub(crate) tags: BTreeSet<String>,
+    }
+
+    impl ManagedAccount {
+        /// Generates an account with `role` from a random seed.
+        pub(crate) fn generate(role: Role) -> Self {
+            let seed: usize = rand::thread_rng().gen();
+            Self {
+                seed: seed.to_string(),
+                role,
+                controller_seed: None,
+                genesis: None,
+                tags: BTreeSet::new(),
+            }
+        }
+
+        /// Generates an account with `role` and a distinct controller from random seeds.
+        pub(crate) fn generate_with_controller(role: Role) -> Self {
+            let seed: usize = rand::thread_rng().gen();
+            Self {
+                controller_seed: Some(seed.to_string()),
+                ..Self::generate(role)
+            }
+        }
+
+        pub(crate) fn keypair(&self) -> Keypair {
+            helpers::signer_from_seed(&self.seed)
+        }
+
+        pub(crate) fn account_id(&self) -> AccountId32 {
+            self.keypair().public_key().into()
+        }
+
+        /// Secret URI of the key pair, its hex encoded secret seed, e.g. for `author_insertKey`.
+        pub(crate) fn secret_uri(&self) -> String {
+            format!("0x{}", hex::encode(helpers::seed_bytes(&self.seed)))
+        }
+
+        /// Key pair of the controller, the stash one if there is no distinct controller.
+        pub(crate) fn controller_keypair(&self) -> Keypair {
+            match self.controller_seed.as_ref() {
+                Some(seed) => helpers::signer_from_seed(seed),
+                None => self.keypair(),
+            }
+        }
+
+        pub(crate) fn controller_id(&self) -> AccountId32 {
+            self.controller_keypair().public_key().into()
+        }
+    }
+
+    /// A nomination pool created by the CLI, with the seeds of its depositor and members.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub(crate) struct ManagedPool {
+        pub(crate) id: u32,
+        pub(crate) depositor: String,
+        pub(crate) members: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) genesis: Option<H256>,
+    }
+
+    /// Chain and tags a state is loaded for.
+    #[derive(Debug, Clone)]
+    struct Scope {
+        genesis: H256,
+        tags: BTreeSet<String>,
+    }
+
+    impl Scope {
+        fn contains(&self, genesis: Option<H256>, tags: Option<&BTreeSet<String>>) -> bool {
+            genesis.map_or(true, |g| g == self.genesis)
+                && tags.map_or(true, |tags| tags.is_superset(&self.tags))
+        }
+    }
+
+    /// Registry of the accounts created by the CLI across runs and chains, keyed by the genesis
+    /// hash of their chain and tagged by the user.
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub(crate) struct State {
+        pub(crate) accounts: Vec<ManagedAccount>,
+        /// Pools of `pools loadtest`, whose accounts are not staking themselves.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) pools: Vec<ManagedPool>,
+        /// Scope the state was loaded for with `load_for`.
+        #[serde(skip)]
+        scope: Option<Scope>,
+        /// Accounts and pools out of the scope, written back untouched on save.
+        #[serde(skip)]
+        out_of_scope: (Vec<ManagedAccount>, Vec<ManagedPool>),
+        /// Seeds of the accounts in scope loaded from the file, whose tags are kept as they are.
+        #[serde(skip)]
+        loaded: BTreeSet<String>,
+    }
+
+    impl State {
+        /// Loads the whole state from `path`. A missing file is an empty state.
+        pub(crate) fn load(path: &Path) -> color_eyre::Result<Self> {
+            helpers::read_json(path)
+        }
+
+        /// Loads the accounts and pools of the chain of `configs` from its state file, the
+        /// accounts restricted to those with the `--tag` tags. On save, the accounts in scope
+        /// are stamped with the genesis hash and the accounts created since the load with the
+        /// tags, and the others are kept as they are.
+        pub(crate) async fn load_for(configs: &Configs) -> color_eyre::Result<Self> {
+            let scope = Scope {
+                genesis: helpers::legacy_rpc(configs).await?.genesis_hash().await?,
+                tags: configs.tags.iter().cloned().collect(),
+            };
+            let state = Self::load(&configs.state)?;
+
+            let (accounts, other_accounts) = state
+                .accounts
+                .into_iter()
+                .partition(|a| scope.contains(a.genesis, Some(&a.tags)));
+            let (pools, other_pools) = state
+                .pools
+                .into_iter()
+                .partition(|p| scope.contains(p.genesis, None));
+            Ok(Self {
+                loaded: accounts.iter().map(|a| a.seed.clone()).collect(),
+                accounts,
+                pools,
+                scope: Some(scope),
+                out_of_scope: (other_accounts, other_pools),
+            })
+        }
+
+        pub(crate) fn save(&self, path: &Path) -> color_eyre::Result<()> {
+            let Some(scope) = self.scope.as_ref() else {
+                return helpers::write_json(path, self);
+            };
+
+            let mut accounts = self.accounts.clone();
+            for account in accounts.iter_mut() {
+                account.genesis = Some(scope.genesis);
+                if !self.loaded.contains(&account.seed) {
+                    account.tags.extend(scope.tags.iter().cloned());
+                }
+            }
+            accounts.extend(self.out_of_scope.0.iter().cloned());
+            let mut pools = self.pools.clone();
+            for pool in pools.iter_mut() {
+                pool.genesis = Some(scope.genesis);
+            }
+            pools.extend(self.out_of_scope.1.iter().cloned());
+
+            helpers::write_json(
+                path,
+                &Self {
+                    accounts,
+                    pools,
+                    ..Default::default()
+                },
+            )
+        }
+
+        pub(crate) fn accounts_with_role(
+            &self,
+            role: Role,
+        ) -> impl Iterator<Item = &ManagedAccount> {
+            self.accounts.iter().filter(move |a| a.role == role)
+        }
+    }
+}
+
+mod index {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        path::Path,
+    };
+
+    /// The score of the election solution queued in a round.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub(crate) struct ScoreRecord {
+        pub(crate) round: u32,
+        pub(crate) minimal_stake: u128,
+        pub(crate) sum_stake: u128,
+        pub(crate) sum_stake_squared: u128,
+    }
+
+    /// A solution mined from the snapshot of a round, with the parameters of the miner.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub(crate) struct MinedRecord {
+        pub(crate) params: String,
+        pub(crate) edges: usize,
+        pub(crate) score: ScoreRecord,
+    }
+
+    /// A slash of a validator in an era, applied or still deferred.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub(crate) struct SlashRecord {
+        /// Slashed fraction in parts per billion, unknown (0) for deferred slashes.
+        pub(crate) fraction: u32,
+        pub(crate) amount: Balance,
+        pub(crate) applied: bool,
+    }
+
+    /// The start of an election phase.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub(crate) struct PhaseRecord {
+        pub(crate) phase: String,
+        pub(crate) block: u32,
+    }
+
+    /// Chain data collected by the CLI over time, keyed by era.
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub(crate) struct Index {
+        #[serde(default)]
+        pub(crate) election_scores: BTreeMap<u32, ScoreRecord>,
+        /// Eras each CLI-created staker has been fully paid for.
+        #[serde(default)]
+        pub(crate) claimed_rewards: BTreeMap<AccountId32, BTreeSet<u32>>,
+        /// Election phase transitions, per round.
+        #[serde(default)]
+        pub(crate) election_phases: BTreeMap<u32, Vec<PhaseRecord>>,
+        /// Solutions mined by `election mine`, per round.
+        #[serde(default)]
+        pub(crate) mined_solutions: BTreeMap<u32, Vec<MinedRecord>>,
+        /// Slashes of the disabled or offending validators seen by `validators disabled`, per
+        /// validator and era, kept once their era is no longer bonded.
+        #[serde(default)]
+        pub(crate) slashes: BTreeMap<AccountId32, BTreeMap<u32, SlashRecord>>,
+    }
+
+    impl Index {
+        /// Loads the index from `path`. A missing file is an empty index.
+        pub(crate) fn load(path: &Path) -> color_eyre::Result<Self> {
+            helpers::read_json(path)
+        }
+
+        pub(crate) fn save(&self, path: &Path) -> color_eyre::Result<()> {
+            helpers::write_json(path, self)
+        }
+    }
+}
+
+mod dump {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::path::Path;
+    use subxt::ext::codec::Decode;
+
+    /// Version of the dump format, bumped on incompatible changes.
+    pub(crate) const VERSION: u32 = 1;
+
+    /// Pallets dumped by default.
+    pub(crate) const PALLETS: [&str; 5] = [
+        "Staking",
+        "VoterList",
+        "ElectionProviderMultiBlock",
+        "ElectionVerifierPallet",
+        "ElectionSignedPallet",
+    ];
+
+    /// A raw storage entry.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+    #[codec(crate = subxt::ext::codec)]
+    pub(crate) struct DumpEntry {
+        #[serde(with = "hex::serde")]
+        pub(crate) key: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        pub(crate) value: Vec<u8>,
+    }
+
+    /// The raw storage of some pallets at a block.
+    #[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+    #[codec(crate = subxt::ext::codec)]
+    pub(crate) struct StorageDump {
+        pub(crate) version: u32,
+        pub(crate) block_hash: H256,
+        pub(crate) block_number: u32,
+        pub(crate) spec_version: u32,
+        pub(crate) transaction_version: u32,
+        #[serde(with = "hex::serde")]
+        pub(crate) metadata_hash: [u8; 32],
+        pub(crate) pallets: Vec<String>,
+        pub(crate) entries: Vec<DumpEntry>,
+    }
+
+    impl StorageDump {
+        /// Loads a JSON or SCALE encoded dump from `path`.
+        pub(crate) fn load(path: &Path) -> color_eyre::Result<Self> {
+            let bytes = std::fs::read(path)?;
+            let dump: Self = match bytes.first() {
+                Some(b'{') => serde_json::from_slice(&bytes)?,
+                _ => Self::decode(&mut &bytes[..])
+                    .map_err(|e| eyre!("{} is not a valid dump: {e}", path.display()))?,
+            };
+            if dump.version != VERSION {
+                bail!(
+                    "dump version {} is not supported, expected {VERSION}",
+                    dump.version
+                );
+            }
+
+            Ok(dump)
+        }
+
+        /// Writes the dump to `path`, SCALE encoded with `scale` and as JSON otherwise.
+        pub(crate) fn save(&self, path: &Path, scale: bool) -> color_eyre::Result<()> {
+            match scale {
+                true => std::fs::write(path, self.encode())?,
+                false => helpers::write_json(path, self)?,
+            }
+
+            Ok(())
+        }
+    }
+}
+
+mod status {
+    use serde::Serialize;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Status of a daemon, as served by the status endpoint.
+    #[derive(Debug, Default, Clone, Serialize)]
+    pub(crate) struct Status {
+        /// Accounts in the state file when the daemon started.
+        pub(crate) accounts_managed: usize,
+        /// Last block followed.
+        pub(crate) last_block: Option<u32>,
+        /// Last era processed.
+        pub(crate) last_era: Option<u32>,
+        /// Extrinsics submitted so far.
+        pub(crate) submitted: u64,
+        /// Submitted extrinsics not yet included.
+        pub(crate) in_flight: u64,
+        /// Failed submissions.
+        pub(crate) errors: u64,
+        /// Alerts raised.
+        pub(crate) alerts: u64,
+        /// Submissions retried with a higher tip.
+        pub(crate) tip_escalations: u64,
+        /// Submissions re-signed with a fresh nonce.
+        pub(crate) nonce_resigns: u64,
+        /// Retractions of blocks including submissions.
+        pub(crate) retractions: u64,
+        /// Stuck or dropped submissions re-signed and resubmitted.
+        pub(crate) resubmissions: u64,
+        /// Highest tip paid by an escalated submission.
+        pub(crate) max_tip_paid: u128,
+        /// Block the submissions are scheduled at.
+        pub(crate) scheduled_at: Option<u32>,
+        /// Whether the scheduled block was reached.
+        pub(crate) schedule_reached: bool,
+        /// Fees paid by the included extrinsics, by phase.
+        pub(crate) fees: std::collections::BTreeMap<String, PhaseFees>,
+    }
+
+    /// Fees paid by the extrinsics of a phase, e.g. `funding` or `bonding`.
+    #[derive(Debug, Default, Clone, Serialize)]
+    pub(crate) struct PhaseFees {
+        pub(crate) extrinsics: u64,
+        /// Total actual fees, tips included.
+        pub(crate) fees: u128,
+        pub(crate) tips: u128,
+    }
+
+    /// Shared handle to the status of the running command.
+    #[derive(Debug, Default, Clone)]
+    pub(crate) struct StatusHandle(Arc<Mutex<Status>>);
+
+    impl StatusHandle {
+        pub(crate) fn update(&self, f: impl FnOnce(&mut Status)) {
+            f(&mut self.0.lock().expect("status lock is never poisoned"))
+        }
+
+        pub(crate) fn snapshot(&self) -> Status {
+            self.0
+                .lock()
+                .expect("status lock is never poisoned")
+                .clone()
+        }
+    }
+
+    /// Serves the status as JSON to every HTTP request on `addr`, in the background.
+    pub(crate) async fn serve(
+        addr: std::net::SocketAddr,
+        handle: StatusHandle,
+    ) -> color_eyre::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        eprintln!("> Serving the status on http://{addr}.");
+
+        tokio::spawn(async move {
+            loop {
+                let mut stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        // e.g. out of file descriptors, back off instead of spinning.
+                        eprintln!("! status server failed to accept a connection: {e}");
+                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let body = serde_json::to_string(&handle.snapshot()).unwrap_or_default();
+                tokio::spawn(async move {
+                    // the request itself does not matter, every path gets the status.
+                    let mut request = [0u8; 1024];
+                    let _ = stream.read(&mut request).await;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        Ok(())
+    }
+}
+
+mod output {
+    use super::*;
+    use serde::Serialize;
+
+    /// Format in which command results are printed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) enum OutputFormat {
+        Plain,
+        Table,
+        Json,
+    }
+
+    impl FromStr for OutputFormat {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "plain" => Ok(Self::Plain),
+                "table" => Ok(Self::Table),
+                "json" => Ok(Self::Json),
+                _ => Err(format!("unknown output format {s}")),
+            }
+        }
+    }
+
+    /// A command result which can be printed in every [`OutputFormat`]. The JSON output is the
+    /// serialized result.
+    pub(crate) trait Render: Serialize {
+        /// Human readable lines.
+        fn plain(&self) -> String;
+
+        /// Aligned table, the plain output by default.
+        fn table(&self) -> String {
+            self.plain()
+        }
+    }
+
+    /// Prints `result` in the `format` selected by `--output`.
+    pub(crate) fn render<R: Render>(format: OutputFormat, result: &R) -> color_eyre::Result<()> {
+        match format {
+            OutputFormat::Plain => println!("{}", result.plain()),
+            OutputFormat::Table => println!("{}", result.table()),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        }
+        Ok(())
+    }
+
+    /// Prints `result` as [`render`], with the `identities` of the accounts it shows: next to
+    /// the accounts in the plain output, below the table output and in an `identities` object of
+    /// the JSON output.
+    pub(crate) fn render_with_identities<R: Render>(
+        format: OutputFormat,
+        result: &R,
+        identities: &std::collections::BTreeMap<AccountId32, String>,
+    ) -> color_eyre::Result<()> {
+        if identities.is_empty() {
+            return render(format, result);
+        }
+
+        match format {
+            OutputFormat::Plain => {
+                let mut plain = result.plain();
+                for (account, identity) in identities.iter() {
+                    let account = account.to_string();
+                    plain = plain.replace(&account, &format!("{account} ({identity})"));
+                }
+                println!("{plain}");
+            }
+            OutputFormat::Table => {
+                println!("{}", result.table());
+                println!("> Identities:");
+                for (account, identity) in identities.iter() {
+                    println!(" {account}: {identity}");
+                }
+            }
+            OutputFormat::Json => {
+                let mut json = serde_json::to_value(result)?;
+                if let Some(object) = json.as_object_mut() {
+                    let identities = identities
+                        .iter()
+                        .map(|(account, identity)| (account.to_string(), identity.clone().into()))
+                        .collect();
+                    object.insert(
+                        "identities".to_string(),
+                        serde_json::Value::Object(identities),
+                    );
+                }
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects the accounts of a serialized result, i.e. its strings which are SS58 addresses.
+    pub(crate) fn accounts(
+        json: &serde_json::Value,
+        accounts: &mut std::collections::BTreeSet<AccountId32>,
+    ) {
+        match json {
+            serde_json::Value::String(s) => {
+                if let Ok(account) = AccountId32::from_str(s) {
+                    accounts.insert(account);
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter().for_each(|v| self::accounts(v, accounts));
+            }
+            serde_json::Value::Object(values) => {
+                values.values().for_each(|v| self::accounts(v, accounts));
+            }
+            _ => {}
+        }
+    }
+
+    /// Formats `rows` as a table with a `headers` row and left-aligned columns.
+    pub(crate) fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+        let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let line = |cells: Vec<&str>| {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
         };
 
-        let n_nominators = keypairs.len();
-        println!("Minting done for {n_nominators} stakers.");
+        let mut lines = vec![line(headers.to_vec())];
+        lines.push(
+            widths
+                .iter()
+                .map(|w| "-".repeat(*w))
+                .collect::<Vec<_>>()
+                .join("-+-"),
+        );
+        for row in rows.iter() {
+            lines.push(line(row.iter().map(|c| c.as_str()).collect()));
+        }
+        lines.join("\n")
+    }
+
+    /// An election score.
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+    pub(crate) struct Score {
+        pub(crate) minimal_stake: u128,
+        pub(crate) sum_stake: u128,
+        pub(crate) sum_stake_squared: u128,
+    }
+
+    impl From<&ElectionScore> for Score {
+        fn from(score: &ElectionScore) -> Self {
+            Self {
+                minimal_stake: score.minimal_stake,
+                sum_stake: score.sum_stake,
+                sum_stake_squared: score.sum_stake_squared,
+            }
+        }
+    }
+
+    impl From<sp_npos_elections::ElectionScore> for Score {
+        fn from(score: sp_npos_elections::ElectionScore) -> Self {
+            Self {
+                minimal_stake: score.minimal_stake,
+                sum_stake: score.sum_stake,
+                sum_stake_squared: score.sum_stake_squared,
+            }
+        }
+    }
+
+    impl std::fmt::Display for Score {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "({}, {}, {})",
+                self.minimal_stake, self.sum_stake, self.sum_stake_squared
+            )
+        }
+    }
+
+    /// Result of `stakers_info`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct StakersInfo {
+        pub(crate) validators: usize,
+        pub(crate) active_era: u32,
+        pub(crate) active: usize,
+        pub(crate) waiting: usize,
+        pub(crate) nominators: usize,
+    }
+
+    impl Render for StakersInfo {
+        fn plain(&self) -> String {
+            format!(
+                "> Stakers info:\n {} validators registered.\n   {} active in era {}, {} waiting.\n {} nominators registered.",
+                self.validators, self.active, self.active_era, self.waiting, self.nominators
+            )
+        }
+
+        fn table(&self) -> String {
+            table(
+                &[
+                    "validators",
+                    "active era",
+                    "active",
+                    "waiting",
+                    "nominators",
+                ],
+                &[vec![
+                    self.validators.to_string(),
+                    self.active_era.to_string(),
+                    self.active.to_string(),
+                    self.waiting.to_string(),
+                    self.nominators.to_string(),
+                ]],
+            )
+        }
+    }
+
+    /// An active validator and its era points.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ActiveValidator {
+        pub(crate) validator: AccountId32,
+        pub(crate) points: u32,
+    }
+
+    /// Result of `validators list`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorsList {
+        pub(crate) active_era: u32,
+        pub(crate) active: Vec<ActiveValidator>,
+        pub(crate) total_points: u32,
+        pub(crate) waiting: Vec<AccountId32>,
+    }
+
+    impl Render for ValidatorsList {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Active validators in era {} ({}):",
+                self.active_era,
+                self.active.len()
+            )];
+            for v in self.active.iter() {
+                lines.push(format!(" {} (era points: {})", v.validator, v.points));
+            }
+            lines.push(format!(" total era points: {}", self.total_points));
+            lines.push(format!("> Waiting validators ({}):", self.waiting.len()));
+            for validator in self.waiting.iter() {
+                lines.push(format!(" {validator}"));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .active
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        "active".to_string(),
+                        v.points.to_string(),
+                    ]
+                })
+                .chain(
+                    self.waiting
+                        .iter()
+                        .map(|v| vec![v.to_string(), "waiting".to_string(), "-".to_string()]),
+                )
+                .collect();
+            table(&["validator", "status", "era points"], &rows)
+        }
+    }
+
+    /// The score recorded for an era, with its change from the previous recorded one in %.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct EraScore {
+        pub(crate) era: u32,
+        #[serde(flatten)]
+        pub(crate) score: index::ScoreRecord,
+        pub(crate) minimal_stake_change: Option<f64>,
+        pub(crate) sum_stake_change: Option<f64>,
+    }
+
+    impl EraScore {
+        pub(crate) fn new(
+            era: u32,
+            score: &index::ScoreRecord,
+            previous: Option<&index::ScoreRecord>,
+        ) -> Self {
+            let change =
+                |from: u128, to: u128| (to as f64 - from as f64) / (from.max(1) as f64) * 100.0;
+            Self {
+                era,
+                score: *score,
+                minimal_stake_change: previous
+                    .map(|p| change(p.minimal_stake, score.minimal_stake)),
+                sum_stake_change: previous.map(|p| change(p.sum_stake, score.sum_stake)),
+            }
+        }
+
+        fn row(&self) -> Vec<String> {
+            let change = |c: Option<f64>| c.map_or("-".to_string(), |c| format!("{c:+.2}%"));
+            vec![
+                self.era.to_string(),
+                self.score.round.to_string(),
+                self.score.minimal_stake.to_string(),
+                self.score.sum_stake.to_string(),
+                self.score.sum_stake_squared.to_string(),
+                change(self.minimal_stake_change),
+                change(self.sum_stake_change),
+            ]
+        }
+    }
+
+    const ERA_SCORE_HEADERS: [&str; 7] = [
+        "era",
+        "round",
+        "minimal stake",
+        "sum stake",
+        "sum stake squared",
+        "minimal stake change",
+        "sum stake change",
+    ];
+
+    impl Render for EraScore {
+        fn plain(&self) -> String {
+            let trend = match (self.minimal_stake_change, self.sum_stake_change) {
+                (Some(minimal), Some(sum)) => {
+                    format!(" [{minimal:+.2}% minimal stake, {sum:+.2}% sum stake]")
+                }
+                _ => String::new(),
+            };
+            format!(
+                " era {} (round {}): minimal stake {}, sum stake {}, sum stake squared {}{trend}",
+                self.era,
+                self.score.round,
+                self.score.minimal_stake,
+                self.score.sum_stake,
+                self.score.sum_stake_squared
+            )
+        }
+
+        fn table(&self) -> String {
+            table(&ERA_SCORE_HEADERS, &[self.row()])
+        }
+    }
+
+    /// Result of `election scores`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionScores(pub(crate) Vec<EraScore>);
+
+    impl Render for ElectionScores {
+        fn plain(&self) -> String {
+            std::iter::once("> Recorded election scores:".to_string())
+                .chain(self.0.iter().map(|s| s.plain()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self.0.iter().map(|s| s.row()).collect();
+            table(&ERA_SCORE_HEADERS, &rows)
+        }
+    }
+
+    /// A signed submission of the current round.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Submission {
+        pub(crate) who: AccountId32,
+        pub(crate) pages: u32,
+        pub(crate) deposit: Balance,
+        pub(crate) score: Score,
+    }
+
+    /// Result of `election submissions`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionSubmissions {
+        pub(crate) round: u32,
+        pub(crate) submissions: Vec<Submission>,
+        /// Score of the solution mined by the CLI, if there is a snapshot to mine it from.
+        pub(crate) mined: Option<Score>,
+        /// Whether the mined solution beats the best submission, if there are both.
+        pub(crate) mined_beats_best: Option<bool>,
+    }
+
+    impl ElectionSubmissions {
+        fn verdict(&self) -> &'static str {
+            match (self.mined, self.mined_beats_best) {
+                (None, _) => {
+                    "No election snapshot available, cannot mine a solution to compare with."
+                }
+                (Some(_), Some(true)) => {
+                    "Mined solution beats the best submission, it is worth submitting."
+                }
+                (Some(_), Some(false)) => "Mined solution does not beat the best submission.",
+                (Some(_), None) => "No submissions yet, the mined solution would be the best one.",
+            }
+        }
+    }
+
+    impl Render for ElectionSubmissions {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Signed submissions in round {} ({}):",
+                self.round,
+                self.submissions.len()
+            )];
+            for s in self.submissions.iter() {
+                lines.push(format!(
+                    " {}: {} page(s), deposit {}, claimed score {}",
+                    s.who, s.pages, s.deposit, s.score
+                ));
+            }
+            if let Some(mined) = self.mined {
+                lines.push(format!("> Mined solution score: {mined}"));
+            }
+            lines.push(self.verdict().to_string());
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let mut rows: Vec<Vec<String>> = self
+                .submissions
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.who.to_string(),
+                        s.pages.to_string(),
+                        s.deposit.to_string(),
+                        s.score.to_string(),
+                    ]
+                })
+                .collect();
+            if let Some(mined) = self.mined {
+                rows.push(vec![
+                    "(mined)".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    mined.to_string(),
+                ]);
+            }
+            format!(
+                "{}\n{}",
+                table(&["submitter", "pages", "deposit", "score"], &rows),
+                self.verdict()
+            )
+        }
+    }
+
+    /// Staking state summary of a chain.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ChainSummary {
+        pub(crate) url: String,
+        pub(crate) validators: usize,
+        pub(crate) nominators: usize,
+        pub(crate) ledgers: usize,
+        pub(crate) total_stake: Balance,
+        pub(crate) min_stake: Balance,
+        pub(crate) median_stake: Balance,
+        pub(crate) max_stake: Balance,
+    }
+
+    impl ChainSummary {
+        pub(crate) fn new(
+            url: String,
+            validators: usize,
+            nominators: usize,
+            mut stakes: Vec<Balance>,
+        ) -> Self {
+            stakes.sort();
+            Self {
+                url,
+                validators,
+                nominators,
+                ledgers: stakes.len(),
+                total_stake: stakes.iter().sum(),
+                min_stake: stakes.first().copied().unwrap_or_default(),
+                median_stake: stakes.get(stakes.len() / 2).copied().unwrap_or_default(),
+                max_stake: stakes.last().copied().unwrap_or_default(),
+            }
+        }
+
+        fn row(&self) -> Vec<String> {
+            vec![
+                self.url.clone(),
+                self.validators.to_string(),
+                self.nominators.to_string(),
+                self.ledgers.to_string(),
+                self.total_stake.to_string(),
+                self.min_stake.to_string(),
+                self.median_stake.to_string(),
+                self.max_stake.to_string(),
+            ]
+        }
+    }
+
+    /// Result of `compare`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ChainComparison {
+        pub(crate) a: ChainSummary,
+        pub(crate) b: ChainSummary,
+        pub(crate) validators_only_in_a: Vec<AccountId32>,
+        pub(crate) validators_only_in_b: Vec<AccountId32>,
+        pub(crate) nominators_only_in_a: usize,
+        pub(crate) nominators_only_in_b: usize,
+        pub(crate) mismatches: Vec<String>,
+    }
+
+    impl ChainComparison {
+        fn report(&self) -> Vec<String> {
+            let mut lines = vec![];
+            for v in self.validators_only_in_a.iter() {
+                lines.push(format!(" validator {v} only in a"));
+            }
+            for v in self.validators_only_in_b.iter() {
+                lines.push(format!(" validator {v} only in b"));
+            }
+            if self.mismatches.is_empty() {
+                lines.push("Both chains match.".to_string());
+            } else {
+                lines.push(format!("> Mismatches ({}):", self.mismatches.len()));
+                lines.extend(self.mismatches.iter().map(|m| format!(" ! {m}")));
+            }
+            lines
+        }
+    }
+
+    impl Render for ChainComparison {
+        fn plain(&self) -> String {
+            let mut lines = vec![];
+            for (name, summary) in [("a", &self.a), ("b", &self.b)] {
+                lines.push(format!(
+                    "> Chain {name} ({}): {} validators, {} nominators, {} ledgers",
+                    summary.url, summary.validators, summary.nominators, summary.ledgers
+                ));
+                lines.push(format!(
+                    " stake: total {}, min {}, median {}, max {}",
+                    summary.total_stake, summary.min_stake, summary.median_stake, summary.max_stake
+                ));
+            }
+            lines.extend(self.report());
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let summaries = table(
+                &[
+                    "chain",
+                    "validators",
+                    "nominators",
+                    "ledgers",
+                    "total stake",
+                    "min stake",
+                    "median stake",
+                    "max stake",
+                ],
+                &[self.a.row(), self.b.row()],
+            );
+            format!("{summaries}\n{}", self.report().join("\n"))
+        }
+    }
+
+    /// A page of rewards not yet paid out.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct OutstandingPayout {
+        pub(crate) era: u32,
+        pub(crate) validator: AccountId32,
+        pub(crate) page: u32,
+    }
+
+    /// Paid and outstanding rewards of a CLI-created staker.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct StakerRewards {
+        pub(crate) who: AccountId32,
+        pub(crate) role: state::Role,
+        pub(crate) claimed: Vec<u32>,
+        pub(crate) outstanding: Vec<OutstandingPayout>,
+    }
+
+    /// Result of `rewards status`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct RewardsStatus {
+        pub(crate) from_era: u32,
+        pub(crate) to_era: u32,
+        pub(crate) stakers: Vec<StakerRewards>,
+    }
+
+    impl Render for RewardsStatus {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Rewards of eras {}..={} ({} stakers):",
+                self.from_era,
+                self.to_era,
+                self.stakers.len()
+            )];
+            for staker in self.stakers.iter() {
+                lines.push(format!(
+                    " {} ({:?}): paid for eras {:?}, {} outstanding page(s)",
+                    staker.who,
+                    staker.role,
+                    staker.claimed,
+                    staker.outstanding.len()
+                ));
+                for p in staker.outstanding.iter() {
+                    lines.push(format!(
+                        "   era {} validator {} page {}",
+                        p.era, p.validator, p.page
+                    ));
+                }
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .stakers
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.who.to_string(),
+                        format!("{:?}", s.role),
+                        s.claimed.len().to_string(),
+                        s.outstanding.len().to_string(),
+                    ]
+                })
+                .collect();
+            table(&["staker", "role", "paid eras", "outstanding pages"], &rows)
+        }
+    }
+
+    /// Reward points of a validator, and whether it was created by the CLI.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorPoints {
+        pub(crate) validator: AccountId32,
+        pub(crate) points: u32,
+        pub(crate) managed: bool,
+    }
+
+    /// Result of `era-points`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct EraPoints {
+        pub(crate) era: u32,
+        pub(crate) total: u32,
+        pub(crate) validators: Vec<ValidatorPoints>,
+    }
+
+    impl Render for EraPoints {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Era {} reward points ({} validators):",
+                self.era,
+                self.validators.len()
+            )];
+            for v in self.validators.iter() {
+                let managed = if v.managed { " [managed]" } else { "" };
+                lines.push(format!(" {}: {}{managed}", v.validator, v.points));
+            }
+            lines.push(format!(" total era points: {}", self.total));
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.points.to_string(),
+                        v.managed.to_string(),
+                    ]
+                })
+                .collect();
+            format!(
+                "{}\ntotal era points: {}",
+                table(&["validator", "points", "managed"], &rows),
+                self.total
+            )
+        }
+    }
+
+    /// An inconsistent ledger, with the sudo call repairing it.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct LedgerIssue {
+        pub(crate) stash: AccountId32,
+        pub(crate) controller: AccountId32,
+        pub(crate) issue: String,
+        /// Hex encoded call to submit with `submit-raw --sudo`.
+        pub(crate) repair: String,
+    }
+
+    /// Result of `check ledgers`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct LedgerCheck {
+        pub(crate) ledgers: usize,
+        pub(crate) issues: Vec<LedgerIssue>,
+    }
+
+    impl Render for LedgerCheck {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} ledgers checked, {} issue(s):",
+                self.ledgers,
+                self.issues.len()
+            )];
+            for i in self.issues.iter() {
+                lines.push(format!(
+                    " ! {} (controller {}): {}",
+                    i.stash, i.controller, i.issue
+                ));
+                lines.push(format!("   repair: submit-raw --sudo {}", i.repair));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .issues
+                .iter()
+                .map(|i| {
+                    vec![
+                        i.stash.to_string(),
+                        i.controller.to_string(),
+                        i.issue.clone(),
+                        i.repair.clone(),
+                    ]
+                })
+                .collect();
+            table(&["stash", "controller", "issue", "repair call"], &rows)
+        }
+    }
+
+    /// A node of a voter list bag.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BagMember {
+        pub(crate) who: AccountId32,
+        pub(crate) score: u64,
+        pub(crate) managed: bool,
+    }
+
+    /// Result of `bags members`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BagMembers {
+        pub(crate) bag_upper: u64,
+        pub(crate) members: Vec<BagMember>,
+    }
+
+    impl Render for BagMembers {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Bag {} has {} member(s):",
+                self.bag_upper,
+                self.members.len()
+            )];
+            for (i, m) in self.members.iter().enumerate() {
+                let managed = if m.managed { " (managed)" } else { "" };
+                lines.push(format!(" {i}: {} score {}{managed}", m.who, m.score));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .members
+                .iter()
+                .enumerate()
+                .map(|(i, m)| {
+                    vec![
+                        i.to_string(),
+                        m.who.to_string(),
+                        m.score.to_string(),
+                        m.managed.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["position", "who", "score", "managed"], &rows)
+        }
+    }
+
+    /// A staker bonded below the minimum bond of its role.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BelowMinStaker {
+        pub(crate) stash: AccountId32,
+        pub(crate) role: state::Role,
+        pub(crate) active: Balance,
+    }
+
+    /// Result of `check below-min`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BelowMin {
+        pub(crate) min_validator_bond: Balance,
+        pub(crate) min_nominator_bond: Balance,
+        pub(crate) stakers: Vec<BelowMinStaker>,
+    }
+
+    impl Render for BelowMin {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} staker(s) below the minimum bonds (validators {}, nominators {}):",
+                self.stakers.len(),
+                self.min_validator_bond,
+                self.min_nominator_bond
+            )];
+            for s in self.stakers.iter() {
+                lines.push(format!(" ! {:?} {} bonded {}", s.role, s.stash, s.active));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .stakers
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.stash.to_string(),
+                        format!("{:?}", s.role),
+                        s.active.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["stash", "role", "active"], &rows)
+        }
+    }
+
+    /// Exposure of a validator against the exposure page size.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorOversubscription {
+        pub(crate) validator: AccountId32,
+        pub(crate) nominators: u32,
+        pub(crate) page_count: u32,
+        pub(crate) unclaimed_pages: Vec<u32>,
+        pub(crate) unexposed: Vec<AccountId32>,
+    }
+
+    /// Result of `validators oversubscription`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Oversubscription {
+        pub(crate) era: u32,
+        pub(crate) page_size: u32,
+        pub(crate) validators: Vec<ValidatorOversubscription>,
+    }
+
+    impl Render for Oversubscription {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Era {}, {} elected validator(s), {} nominators per exposure page:",
+                self.era,
+                self.validators.len(),
+                self.page_size
+            )];
+            for v in self.validators.iter() {
+                lines.push(format!(
+                    " {}: {} nominator(s) in {} page(s), unclaimed pages {:?}",
+                    v.validator, v.nominators, v.page_count, v.unclaimed_pages
+                ));
+                for n in v.unexposed.iter() {
+                    lines.push(format!("   ! {n} nominated it but is not exposed."));
+                }
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.nominators.to_string(),
+                        v.page_count.to_string(),
+                        format!("{:?}", v.unclaimed_pages),
+                        v.unexposed.len().to_string(),
+                    ]
+                })
+                .collect();
+            table(
+                &["validator", "nominators", "pages", "unclaimed", "unexposed"],
+                &rows,
+            )
+        }
+    }
+
+    /// Exposure metadata of a validator in an era.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ExposureOverview {
+        pub(crate) validator: AccountId32,
+        pub(crate) total: Balance,
+        pub(crate) own: Balance,
+        pub(crate) nominators: u32,
+        pub(crate) page_count: u32,
+        /// Whether the exposure is a legacy, non-paged, one.
+        pub(crate) legacy: bool,
+    }
+
+    /// Result of `exposures pages`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ExposurePages {
+        pub(crate) era: u32,
+        pub(crate) validators: Vec<ExposureOverview>,
+    }
+
+    impl Render for ExposurePages {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Era {}, {} exposed validator(s):",
+                self.era,
+                self.validators.len()
+            )];
+            for v in self.validators.iter() {
+                let legacy = if v.legacy { " (legacy)" } else { "" };
+                lines.push(format!(
+                    " {}: {} page(s), {} nominator(s), total {}, own {}{legacy}",
+                    v.validator, v.page_count, v.nominators, v.total, v.own
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.page_count.to_string(),
+                        v.nominators.to_string(),
+                        v.total.to_string(),
+                        v.own.to_string(),
+                        v.legacy.to_string(),
+                    ]
+                })
+                .collect();
+            table(
+                &["validator", "pages", "nominators", "total", "own", "legacy"],
+                &rows,
+            )
+        }
+    }
+
+    /// A nominator backing a validator.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Backing {
+        pub(crate) who: AccountId32,
+        pub(crate) value: Balance,
+    }
+
+    /// Result of `exposures page`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ExposurePage {
+        pub(crate) era: u32,
+        pub(crate) validator: AccountId32,
+        pub(crate) page: u32,
+        pub(crate) others: Vec<Backing>,
+    }
+
+    impl Render for ExposurePage {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Page {} of {} in era {}, {} nominator(s):",
+                self.page,
+                self.validator,
+                self.era,
+                self.others.len()
+            )];
+            for b in self.others.iter() {
+                lines.push(format!(" {}: {}", b.who, b.value));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .others
+                .iter()
+                .map(|b| vec![b.who.to_string(), b.value.to_string()])
+                .collect();
+            table(&["nominator", "value"], &rows)
+        }
+    }
+
+    /// Result of `session info`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SessionInfo {
+        pub(crate) index: u32,
+        pub(crate) validators: Vec<AccountId32>,
+        /// Indices in `validators` of the disabled ones.
+        pub(crate) disabled: Vec<u32>,
+        pub(crate) queued_changed: bool,
+        /// Validators of the next session and their (hex encoded) session keys.
+        pub(crate) queued: Vec<(AccountId32, String)>,
+        /// CLI-created validators with session keys which are not session validators.
+        pub(crate) pending: Vec<AccountId32>,
+    }
+
+    impl Render for SessionInfo {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Session {}, {} validator(s) ({} disabled):",
+                self.index,
+                self.validators.len(),
+                self.disabled.len()
+            )];
+            for (i, v) in self.validators.iter().enumerate() {
+                let disabled = if self.disabled.contains(&(i as u32)) {
+                    " (disabled)"
+                } else {
+                    ""
+                };
+                lines.push(format!(" {v}{disabled}"));
+            }
+            let changed = if self.queued_changed { ", changed" } else { "" };
+            lines.push(format!(
+                "> {} queued key(s) for the next session{changed}:",
+                self.queued.len()
+            ));
+            for (who, keys) in self.queued.iter() {
+                lines.push(format!(" {who}: {keys}"));
+            }
+            lines.push(format!(
+                "> {} CLI-created validator(s) with keys set, not in the session:",
+                self.pending.len()
+            ));
+            for who in self.pending.iter() {
+                lines.push(format!(" {who}"));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> =
+                self.validators
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        vec![
+                            v.to_string(),
+                            "current".to_string(),
+                            self.disabled.contains(&(i as u32)).to_string(),
+                        ]
+                    })
+                    .chain(self.queued.iter().map(|(who, _)| {
+                        vec![who.to_string(), "queued".to_string(), false.to_string()]
+                    }))
+                    .chain(
+                        self.pending.iter().map(|who| {
+                            vec![who.to_string(), "pending".to_string(), false.to_string()]
+                        }),
+                    )
+                    .collect();
+            table(&["validator", "session", "disabled"], &rows)
+        }
+    }
+
+    /// A slash of a validator, applied or still deferred.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Slash {
+        pub(crate) era: u32,
+        /// Slashed fraction in parts per billion, unknown (0) for deferred slashes.
+        pub(crate) fraction: u32,
+        pub(crate) amount: Balance,
+        pub(crate) applied: bool,
+    }
+
+    impl Slash {
+        /// The slashes of the records of a validator in the index, by era.
+        pub(crate) fn from_records(
+            records: &std::collections::BTreeMap<u32, index::SlashRecord>,
+        ) -> Vec<Slash> {
+            records
+                .iter()
+                .map(|(era, slash)| Slash {
+                    era: *era,
+                    fraction: slash.fraction,
+                    amount: slash.amount,
+                    applied: slash.applied,
+                })
+                .collect()
+        }
+    }
+
+    /// A disabled, offending or previously slashed validator.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct DisabledValidator {
+        pub(crate) validator: AccountId32,
+        /// Position in the session validators, if still part of the session.
+        pub(crate) index: Option<u32>,
+        pub(crate) disabled: bool,
+        pub(crate) offending: bool,
+        pub(crate) managed: bool,
+        pub(crate) slashes: Vec<Slash>,
+    }
+
+    /// Result of `validators disabled`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct DisabledValidators {
+        pub(crate) validators: Vec<DisabledValidator>,
+    }
+
+    impl Render for DisabledValidators {
+        fn plain(&self) -> String {
+            let n_managed = self.validators.iter().filter(|v| v.managed).count();
+            let mut lines = vec![format!(
+                "> {} disabled, offending or slashed validator(s), {n_managed} CLI-created:",
+                self.validators.len()
+            )];
+            for v in self.validators.iter() {
+                let managed = if v.managed { " (managed)" } else { "" };
+                let position = v.index.map_or("-".to_string(), |i| i.to_string());
+                lines.push(format!(
+                    " {} #{position}: disabled {}, offending {}{managed}",
+                    v.validator, v.disabled, v.offending
+                ));
+                for s in v.slashes.iter() {
+                    let status = if s.applied { "applied" } else { "deferred" };
+                    lines.push(format!(
+                        "   era {}: slashed {} ({} ppb, {status})",
+                        s.era, s.amount, s.fraction
+                    ));
+                }
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.index.map_or("-".to_string(), |i| i.to_string()),
+                        v.disabled.to_string(),
+                        v.offending.to_string(),
+                        v.managed.to_string(),
+                        v.slashes.len().to_string(),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "validator",
+                    "index",
+                    "disabled",
+                    "offending",
+                    "managed",
+                    "slashes",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// An offence reported to staking.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct OffenceReport {
+        pub(crate) block: u32,
+        pub(crate) validator: AccountId32,
+        /// Slash fraction in parts per billion.
+        pub(crate) fraction: u32,
+        pub(crate) slash_era: u32,
+        pub(crate) managed: bool,
+    }
+
+    /// A slash applied to a staker.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SlashEvent {
+        pub(crate) block: u32,
+        pub(crate) staker: AccountId32,
+        pub(crate) amount: Balance,
+        pub(crate) managed: bool,
+    }
+
+    /// Result of `offences`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Offences {
+        pub(crate) from: u32,
+        pub(crate) to: u32,
+        pub(crate) reports: Vec<OffenceReport>,
+        pub(crate) slashes: Vec<SlashEvent>,
+    }
+
+    impl Render for Offences {
+        fn plain(&self) -> String {
+            let managed = |m: bool| if m { " (managed)" } else { "" };
+            let mut lines = vec![format!(
+                "> Blocks {}..={}: {} offence report(s), {} slash(es):",
+                self.from,
+                self.to,
+                self.reports.len(),
+                self.slashes.len()
+            )];
+            for r in self.reports.iter() {
+                lines.push(format!(
+                    " #{}: {} reported, {} ppb in era {}{}",
+                    r.block,
+                    r.validator,
+                    r.fraction,
+                    r.slash_era,
+                    managed(r.managed)
+                ));
+            }
+            for s in self.slashes.iter() {
+                lines.push(format!(
+                    " #{}: {} slashed {}{}",
+                    s.block,
+                    s.staker,
+                    s.amount,
+                    managed(s.managed)
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .reports
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.block.to_string(),
+                        "report".to_string(),
+                        r.validator.to_string(),
+                        format!("{} ppb (era {})", r.fraction, r.slash_era),
+                        r.managed.to_string(),
+                    ]
+                })
+                .chain(self.slashes.iter().map(|s| {
+                    vec![
+                        s.block.to_string(),
+                        "slash".to_string(),
+                        s.staker.to_string(),
+                        s.amount.to_string(),
+                        s.managed.to_string(),
+                    ]
+                }))
+                .collect();
+            table(&["block", "event", "who", "amount", "managed"], &rows)
+        }
+    }
+
+    /// Blocks authored by a validator over a range.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorAuthorship {
+        pub(crate) validator: AccountId32,
+        pub(crate) authored: u32,
+        pub(crate) expected: f64,
+        pub(crate) managed: bool,
+    }
+
+    impl ValidatorAuthorship {
+        /// Whether the validator was expected to author at least one block but did not.
+        pub(crate) fn never_authored(&self) -> bool {
+            self.authored == 0 && self.expected >= 1.0
+        }
+    }
+
+    /// Result of `authorship`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Authorship {
+        pub(crate) from: u32,
+        pub(crate) to: u32,
+        pub(crate) validators: Vec<ValidatorAuthorship>,
+    }
+
+    impl Render for Authorship {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Blocks {}..={}, {} validator(s):",
+                self.from,
+                self.to,
+                self.validators.len()
+            )];
+            for v in self.validators.iter() {
+                let managed = if v.managed { " (managed)" } else { "" };
+                let flag = if v.managed && v.never_authored() {
+                    " !"
+                } else {
+                    ""
+                };
+                lines.push(format!(
+                    "{flag} {}: authored {}, expected {:.1}{managed}",
+                    v.validator, v.authored, v.expected
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.authored.to_string(),
+                        format!("{:.1}", v.expected),
+                        v.managed.to_string(),
+                        v.never_authored().to_string(),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "validator",
+                    "authored",
+                    "expected",
+                    "managed",
+                    "never authored",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// The staking configs checked by `check config`. Unset configs are not checked, or not set
+    /// on chain.
+    #[derive(Debug, Default, Serialize, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub(crate) struct StakingConfigs {
+        pub(crate) min_nominator_bond: Option<Balance>,
+        pub(crate) min_validator_bond: Option<Balance>,
+        pub(crate) max_nominators_count: Option<u32>,
+        pub(crate) max_validators_count: Option<u32>,
+        pub(crate) validator_count: Option<u32>,
+        pub(crate) minimum_validator_count: Option<u32>,
+        /// In parts per billion.
+        pub(crate) min_commission: Option<u32>,
+        /// In percent.
+        pub(crate) chill_threshold: Option<u8>,
+        /// In percent.
+        pub(crate) max_staked_rewards: Option<u8>,
+        /// In parts per billion.
+        pub(crate) slash_reward_fraction: Option<u32>,
+        pub(crate) slash_defer_duration: Option<u32>,
+        pub(crate) sessions_per_era: Option<u32>,
+        pub(crate) invulnerables: Option<Vec<AccountId32>>,
+    }
+
+    impl StakingConfigs {
+        /// The configs set in `self` whose value differs in `actual`.
+        pub(crate) fn drifts(&self, actual: &StakingConfigs) -> Vec<ConfigDrift> {
+            fn drift<T: PartialEq + std::fmt::Debug>(
+                drifts: &mut Vec<ConfigDrift>,
+                config: &str,
+                expected: &Option<T>,
+                actual: &Option<T>,
+            ) {
+                if expected.is_some() && expected != actual {
+                    drifts.push(ConfigDrift {
+                        config: config.to_string(),
+                        expected: format!("{expected:?}"),
+                        actual: format!("{actual:?}"),
+                    });
+                }
+            }
+
+            let mut drifts = vec![];
+            let d = &mut drifts;
+            drift(
+                d,
+                "min_nominator_bond",
+                &self.min_nominator_bond,
+                &actual.min_nominator_bond,
+            );
+            drift(
+                d,
+                "min_validator_bond",
+                &self.min_validator_bond,
+                &actual.min_validator_bond,
+            );
+            drift(
+                d,
+                "max_nominators_count",
+                &self.max_nominators_count,
+                &actual.max_nominators_count,
+            );
+            drift(
+                d,
+                "max_validators_count",
+                &self.max_validators_count,
+                &actual.max_validators_count,
+            );
+            drift(
+                d,
+                "validator_count",
+                &self.validator_count,
+                &actual.validator_count,
+            );
+            drift(
+                d,
+                "minimum_validator_count",
+                &self.minimum_validator_count,
+                &actual.minimum_validator_count,
+            );
+            drift(
+                d,
+                "min_commission",
+                &self.min_commission,
+                &actual.min_commission,
+            );
+            drift(
+                d,
+                "chill_threshold",
+                &self.chill_threshold,
+                &actual.chill_threshold,
+            );
+            drift(
+                d,
+                "max_staked_rewards",
+                &self.max_staked_rewards,
+                &actual.max_staked_rewards,
+            );
+            drift(
+                d,
+                "slash_reward_fraction",
+                &self.slash_reward_fraction,
+                &actual.slash_reward_fraction,
+            );
+            drift(
+                d,
+                "slash_defer_duration",
+                &self.slash_defer_duration,
+                &actual.slash_defer_duration,
+            );
+            drift(
+                d,
+                "sessions_per_era",
+                &self.sessions_per_era,
+                &actual.sessions_per_era,
+            );
+            // the order of the invulnerables is not relevant.
+            let sorted = |i: &Option<Vec<AccountId32>>| {
+                i.clone().map(|mut i| {
+                    i.sort();
+                    i
+                })
+            };
+            drift(
+                d,
+                "invulnerables",
+                &sorted(&self.invulnerables),
+                &sorted(&actual.invulnerables),
+            );
+
+            drifts
+        }
+    }
+
+    /// A staking config whose on-chain value differs from the expected one.
+    #[derive(Clone, Debug, Serialize)]
+    pub(crate) struct ConfigDrift {
+        pub(crate) config: String,
+        pub(crate) expected: String,
+        pub(crate) actual: String,
+    }
+
+    /// Result of `check config`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ConfigCheck {
+        pub(crate) actual: StakingConfigs,
+        pub(crate) drifts: Vec<ConfigDrift>,
+    }
+
+    impl Render for ConfigCheck {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} staking config(s) drifted:",
+                self.drifts.len()
+            )];
+            for d in self.drifts.iter() {
+                lines.push(format!(
+                    " ! {}: expected {}, got {}",
+                    d.config, d.expected, d.actual
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .drifts
+                .iter()
+                .map(|d| vec![d.config.clone(), d.expected.clone(), d.actual.clone()])
+                .collect();
+            table(&["config", "expected", "actual"], &rows)
+        }
+    }
+
+    /// Concentration metrics of a set of stakes.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Concentration {
+        pub(crate) count: usize,
+        pub(crate) total: Balance,
+        pub(crate) gini: f64,
+        pub(crate) hhi: f64,
+        pub(crate) top_k: usize,
+        pub(crate) top_k_share: f64,
+    }
+
+    /// Result of `stats concentration`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ConcentrationStats {
+        pub(crate) era: u32,
+        pub(crate) validators_backing: Concentration,
+        pub(crate) nominators_stake: Concentration,
+    }
+
+    impl Render for ConcentrationStats {
+        fn plain(&self) -> String {
+            let line = |name: &str, c: &Concentration| {
+                format!(
+                    " {name}: {} stakers, total {}, gini {:.4}, hhi {:.4}, top {} share {:.2}%",
+                    c.count,
+                    c.total,
+                    c.gini,
+                    c.hhi,
+                    c.top_k,
+                    c.top_k_share * 100.0
+                )
+            };
+            [
+                format!("> Stake concentration, era {}:", self.era),
+                line("validators backing", &self.validators_backing),
+                line("nominators stake", &self.nominators_stake),
+            ]
+            .join("\n")
+        }
+
+        fn table(&self) -> String {
+            let row = |name: &str, c: &Concentration| {
+                vec![
+                    name.to_string(),
+                    c.count.to_string(),
+                    c.total.to_string(),
+                    format!("{:.4}", c.gini),
+                    format!("{:.4}", c.hhi),
+                    format!("{:.2}%", c.top_k_share * 100.0),
+                ]
+            };
+            let rows = vec![
+                row("validators backing", &self.validators_backing),
+                row("nominators stake", &self.nominators_stake),
+            ];
+            table(
+                &["set", "count", "total", "gini", "hhi", "top-k share"],
+                &rows,
+            )
+        }
+    }
+
+    /// Commission of a registered validator.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorCommission {
+        pub(crate) validator: AccountId32,
+        /// In parts per billion.
+        pub(crate) commission: u32,
+        pub(crate) blocked: bool,
+        pub(crate) below_min: bool,
+        pub(crate) full: bool,
+    }
+
+    /// Number of validators with a commission from `from_percent` up to the next bucket.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct CommissionBucket {
+        pub(crate) from_percent: u32,
+        pub(crate) validators: usize,
+    }
+
+    /// Result of `validators commissions`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Commissions {
+        /// In parts per billion.
+        pub(crate) min_commission: u32,
+        pub(crate) buckets: Vec<CommissionBucket>,
+        pub(crate) validators: Vec<ValidatorCommission>,
+    }
+
+    impl Render for Commissions {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} validator(s), min commission {} ppb:",
+                self.validators.len(),
+                self.min_commission
+            )];
+            for b in self.buckets.iter() {
+                lines.push(format!(
+                    " {:>3}%+: {} {}",
+                    b.from_percent,
+                    "#".repeat(b.validators.min(60)),
+                    b.validators
+                ));
+            }
+            for v in self.validators.iter().filter(|v| v.full || v.below_min) {
+                let issue = if v.full {
+                    "100% commission"
+                } else {
+                    "below the min commission"
+                };
+                lines.push(format!(
+                    " ! {} has a {issue} ({} ppb).",
+                    v.validator, v.commission
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .buckets
+                .iter()
+                .map(|b| vec![format!("{}%", b.from_percent), b.validators.to_string()])
+                .collect();
+            table(&["commission from", "validators"], &rows)
+        }
+    }
+
+    /// Self-stake of a validator against its nominated backing.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ValidatorSelfStake {
+        pub(crate) validator: AccountId32,
+        pub(crate) own: Balance,
+        /// Backing from nominators, if elected.
+        pub(crate) nominated: Option<Balance>,
+        pub(crate) low: bool,
+    }
+
+    /// Result of `validators self-stake`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SelfStakes {
+        pub(crate) era: u32,
+        pub(crate) validators: Vec<ValidatorSelfStake>,
+    }
+
+    impl Render for SelfStakes {
+        fn plain(&self) -> String {
+            let n_low = self.validators.iter().filter(|v| v.low).count();
+            let mut lines = vec![format!(
+                "> Era {}, {} validator(s), {n_low} with a low self-stake:",
+                self.era,
+                self.validators.len()
+            )];
+            for v in self.validators.iter() {
+                let flag = if v.low { " !" } else { "" };
+                let nominated = match v.nominated {
+                    Some(nominated) => nominated.to_string(),
+                    None => "not elected".to_string(),
+                };
+                lines.push(format!(
+                    "{flag} {}: own {}, nominated {nominated}",
+                    v.validator, v.own
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .validators
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.validator.to_string(),
+                        v.own.to_string(),
+                        v.nominated.map(|n| n.to_string()).unwrap_or_default(),
+                        v.low.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["validator", "own", "nominated", "low"], &rows)
+        }
+    }
+
+    /// Number of nominators with a bond from `from` up to the next bucket.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BondBucket {
+        pub(crate) from: Balance,
+        pub(crate) nominators: usize,
+    }
+
+    /// Result of `nominators distribution`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BondDistribution {
+        pub(crate) nominators: usize,
+        pub(crate) min: Balance,
+        pub(crate) max: Balance,
+        pub(crate) mean: Balance,
+        pub(crate) p50: Balance,
+        pub(crate) p90: Balance,
+        pub(crate) p99: Balance,
+        pub(crate) buckets: Vec<BondBucket>,
+    }
+
+    impl Render for BondDistribution {
+        fn plain(&self) -> String {
+            let mut lines = vec![
+                format!(
+                    "> {} nominator(s), bond min {}, max {}, mean {}:",
+                    self.nominators, self.min, self.max, self.mean
+                ),
+                format!(" p50 {}, p90 {}, p99 {}", self.p50, self.p90, self.p99),
+            ];
+            for b in self.buckets.iter() {
+                lines.push(format!(
+                    " {}+: {} {}",
+                    b.from,
+                    "#".repeat(b.nominators.min(60)),
+                    b.nominators
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .buckets
+                .iter()
+                .map(|b| vec![b.from.to_string(), b.nominators.to_string()])
+                .collect();
+            table(&["bond from", "nominators"], &rows)
+        }
+    }
+
+    /// A storage key whose on-chain state differs from a dump.
+    #[derive(Clone, Debug, Serialize)]
+    pub(crate) struct DumpDivergence {
+        pub(crate) key: String,
+        /// `missing` on chain, `changed` value or `extra` key on chain.
+        pub(crate) kind: String,
+    }
+
+    /// Result of `verify-dump`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct DumpVerification {
+        pub(crate) block_number: u32,
+        pub(crate) entries: usize,
+        pub(crate) divergences: Vec<DumpDivergence>,
+    }
+
+    impl Render for DumpVerification {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} entries of the dump of block #{} verified, {} divergence(s):",
+                self.entries,
+                self.block_number,
+                self.divergences.len()
+            )];
+            for d in self.divergences.iter() {
+                lines.push(format!(" ! {} {}", d.kind, d.key));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .divergences
+                .iter()
+                .map(|d| vec![d.kind.clone(), d.key.clone()])
+                .collect();
+            table(&["divergence", "key"], &rows)
+        }
+    }
+
+    /// Result of `pool status`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolStatus {
+        pub(crate) pending: usize,
+        pub(crate) pending_bytes: usize,
+        pub(crate) ready: Option<u64>,
+        pub(crate) future: Option<u64>,
+    }
+
+    impl Render for PoolStatus {
+        fn plain(&self) -> String {
+            let count = |c: Option<u64>| c.map_or("n/a".to_string(), |c| c.to_string());
+            [
+                format!(
+                    "> {} pending extrinsic(s), {} byte(s).",
+                    self.pending, self.pending_bytes
+                ),
+                format!(" ready: {}", count(self.ready)),
+                format!(" future: {}", count(self.future)),
+            ]
+            .join("\n")
+        }
+    }
+
+    /// A pending extrinsic with staking calls.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PendingExtrinsic {
+        pub(crate) signer: AccountId32,
+        pub(crate) nonce: u64,
+        /// Whether the signer is a CLI-created stash or controller.
+        pub(crate) managed: bool,
+        pub(crate) call: String,
+        pub(crate) staking_calls: Vec<String>,
+    }
+
+    /// Result of `pool pending`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PendingExtrinsics {
+        pub(crate) total: usize,
+        pub(crate) staking: Vec<PendingExtrinsic>,
+    }
+
+    impl Render for PendingExtrinsics {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} of {} pending extrinsic(s) with staking calls:",
+                self.staking.len(),
+                self.total
+            )];
+            for xt in self.staking.iter() {
+                let managed = if xt.managed { " (CLI)" } else { "" };
+                lines.push(format!(
+                    " {}{managed} #{}: {} [{}]",
+                    xt.signer,
+                    xt.nonce,
+                    xt.call,
+                    xt.staking_calls.join(", ")
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .staking
+                .iter()
+                .map(|xt| {
+                    vec![
+                        xt.signer.to_string(),
+                        xt.nonce.to_string(),
+                        xt.managed.to_string(),
+                        xt.call.clone(),
+                        xt.staking_calls.join(", "),
+                    ]
+                })
+                .collect();
+            table(&["signer", "nonce", "cli", "call", "staking calls"], &rows)
+        }
+    }
+
+    /// Outcome of a pre-signed extrinsic.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SubmittedExtrinsic {
+        /// Line of the extrinsic in the file.
+        pub(crate) line: usize,
+        pub(crate) hash: H256,
+        /// Block the extrinsic was finalized in.
+        pub(crate) block: Option<H256>,
+        pub(crate) error: Option<String>,
+    }
+
+    /// Result of `submit-file`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SubmittedFile {
+        pub(crate) extrinsics: Vec<SubmittedExtrinsic>,
+    }
+
+    impl Render for SubmittedFile {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {} extrinsic(s):", self.extrinsics.len())];
+            for xt in self.extrinsics.iter() {
+                let outcome = match (&xt.block, &xt.error) {
+                    (_, Some(error)) => format!("failed: {error}"),
+                    (Some(block), None) => format!("finalized in {block:?}"),
+                    (None, None) => "unknown".to_string(),
+                };
+                lines.push(format!(" line {} {:?}: {outcome}", xt.line, xt.hash));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .extrinsics
+                .iter()
+                .map(|xt| {
+                    vec![
+                        xt.line.to_string(),
+                        format!("{:?}", xt.hash),
+                        xt.block.map(|b| format!("{b:?}")).unwrap_or_default(),
+                        xt.error.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            table(&["line", "hash", "block", "error"], &rows)
+        }
+    }
+
+    /// Result of `encode`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct EncodedCall {
+        pub(crate) call: String,
+        pub(crate) call_data: String,
+        /// Blake2-256 hash of the call data.
+        pub(crate) hash: H256,
+    }
+
+    impl Render for EncodedCall {
+        fn plain(&self) -> String {
+            [
+                format!("> {}", self.call),
+                format!(" call data: {}", self.call_data),
+                format!(" hash: {:?}", self.hash),
+            ]
+            .join("\n")
+        }
+    }
+
+    /// A decoded call, with the calls it wraps. Result of `decode`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct DecodedCall {
+        /// `Pallet.call` name.
+        pub(crate) call: String,
+        pub(crate) args: Option<String>,
+        pub(crate) calls: Vec<DecodedCall>,
+    }
+
+    impl DecodedCall {
+        fn lines(&self, depth: usize, lines: &mut Vec<String>) {
+            let args = self.args.as_deref().unwrap_or_default();
+            lines.push(format!("{}{} {args}", "  ".repeat(depth), self.call));
+            for call in self.calls.iter() {
+                call.lines(depth + 1, lines);
+            }
+        }
+    }
+
+    impl Render for DecodedCall {
+        fn plain(&self) -> String {
+            let mut lines = vec![];
+            self.lines(0, &mut lines);
+            lines
+                .iter()
+                .map(|l| l.trim_end())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    /// Result of `propose`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Proposal {
+        /// The proposed call.
+        pub(crate) call: String,
+        pub(crate) call_data: String,
+        pub(crate) preimage_hash: H256,
+        pub(crate) preimage_len: u32,
+        /// `Preimage.note_preimage` call data, if the chain has the governance pallets.
+        pub(crate) note_preimage: Option<String>,
+        /// `Referenda.submit` call data, if the chain has the governance pallets.
+        pub(crate) referendum_submit: Option<String>,
+        /// Index of the submitted referendum.
+        pub(crate) referendum: Option<u32>,
+    }
+
+    impl Render for Proposal {
+        fn plain(&self) -> String {
+            let mut lines = vec![
+                format!("> {}", self.call),
+                format!(" call data: {}", self.call_data),
+                format!(
+                    " preimage: {:?} ({} bytes)",
+                    self.preimage_hash, self.preimage_len
+                ),
+            ];
+            match (&self.note_preimage, &self.referendum_submit) {
+                (Some(note), Some(submit)) => {
+                    lines.push(format!(" Preimage.note_preimage: {note}"));
+                    lines.push(format!(" Referenda.submit: {submit}"));
+                }
+                _ => lines.push(" ! the chain has no Preimage and Referenda pallets.".to_string()),
+            }
+            if let Some(referendum) = self.referendum {
+                lines.push(format!(" submitted as referendum #{referendum}"));
+            }
+            lines.join("\n")
+        }
+    }
+
+    /// An SS58 address of an account.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Ss58Address {
+        pub(crate) prefix: u16,
+        /// Name of the network registered with the prefix, or the prefix if none is.
+        pub(crate) network: String,
+        pub(crate) address: String,
+    }
+
+    /// Result of `address`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Address {
+        pub(crate) public_key: String,
+        /// Prefix and network of the given SS58 address.
+        pub(crate) network: Option<(u16, String)>,
+        pub(crate) addresses: Vec<Ss58Address>,
+    }
+
+    impl Render for Address {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {}", self.public_key)];
+            if let Some((prefix, network)) = &self.network {
+                lines.push(format!(" network: {network} (prefix {prefix})"));
+            }
+            for address in self.addresses.iter() {
+                lines.push(format!(
+                    " {} ({}): {}",
+                    address.network, address.prefix, address.address
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .addresses
+                .iter()
+                .map(|a| vec![a.prefix.to_string(), a.network.clone(), a.address.clone()])
+                .collect();
+            table(&["prefix", "network", "address"], &rows)
+        }
+    }
+
+    /// A preflight check.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Check {
+        pub(crate) name: String,
+        pub(crate) passed: bool,
+        pub(crate) detail: String,
+    }
+
+    impl Check {
+        pub(crate) fn new(name: &str, passed: bool, detail: String) -> Self {
+            Self {
+                name: name.to_string(),
+                passed,
+                detail,
+            }
+        }
+    }
+
+    /// Result of `doctor`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Doctor {
+        pub(crate) checks: Vec<Check>,
+    }
+
+    impl Render for Doctor {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {} check(s):", self.checks.len())];
+            for check in self.checks.iter() {
+                let status = if check.passed { "ok" } else { "FAIL" };
+                lines.push(format!(" [{status}] {}: {}", check.name, check.detail));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .checks
+                .iter()
+                .map(|c| vec![c.name.clone(), c.passed.to_string(), c.detail.clone()])
+                .collect();
+            table(&["check", "passed", "detail"], &rows)
+        }
+    }
+
+    /// Result of `runtime`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct RuntimeInfo {
+        pub(crate) spec_name: String,
+        pub(crate) impl_name: String,
+        pub(crate) spec_version: u32,
+        pub(crate) impl_version: u32,
+        pub(crate) transaction_version: u32,
+        pub(crate) metadata_version: u8,
+    }
+
+    impl Render for RuntimeInfo {
+        fn plain(&self) -> String {
+            [
+                format!("> {} ({})", self.spec_name, self.impl_name),
+                format!(" spec version: {}", self.spec_version),
+                format!(" impl version: {}", self.impl_version),
+                format!(" transaction version: {}", self.transaction_version),
+                format!(" metadata version: V{}", self.metadata_version),
+            ]
+            .join("\n")
+        }
+    }
+
+    /// Fees paid by the extrinsics of a run, by phase.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct FeeReport(pub(crate) std::collections::BTreeMap<String, status::PhaseFees>);
+
+    impl Render for FeeReport {
+        fn plain(&self) -> String {
+            let total: u128 = self.0.values().map(|p| p.fees).sum();
+            let mut lines = vec![format!("> Fees paid: {total}")];
+            for (phase, fees) in self.0.iter() {
+                lines.push(format!(
+                    " {phase}: {} over {} extrinsic(s), tips {}",
+                    fees.fees, fees.extrinsics, fees.tips
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .0
+                .iter()
+                .map(|(phase, fees)| {
+                    vec![
+                        phase.clone(),
+                        fees.extrinsics.to_string(),
+                        fees.fees.to_string(),
+                        fees.tips.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["phase", "extrinsics", "fees", "tips"], &rows)
+        }
+    }
+
+    /// The state of a CLI-created pool in an era.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolSample {
+        pub(crate) pool_id: u32,
+        pub(crate) members: u32,
+        pub(crate) points: u128,
+        /// Active balance of the pool's bonded account.
+        pub(crate) balance: u128,
+    }
+
+    impl PoolSample {
+        /// Balance per point, 1 until the pool is slashed or compounds rewards.
+        pub(crate) fn ratio(&self) -> f64 {
+            if self.points == 0 {
+                return 0.0;
+            }
+            self.balance as f64 / self.points as f64
+        }
+    }
+
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolsEra {
+        pub(crate) era: u32,
+        pub(crate) pools: Vec<PoolSample>,
+    }
+
+    /// Points against bonded balances of the pools over the eras of a `pools loadtest`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolsLoadtest {
+        pub(crate) eras: Vec<PoolsEra>,
+    }
+
+    impl Render for PoolsLoadtest {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> Pools over {} era(s):", self.eras.len())];
+            for era in self.eras.iter() {
+                for pool in era.pools.iter() {
+                    lines.push(format!(
+                        " era {} pool {}: {} member(s), {} points, {} bonded, {:.6} per point",
+                        era.era,
+                        pool.pool_id,
+                        pool.members,
+                        pool.points,
+                        pool.balance,
+                        pool.ratio()
+                    ));
+                }
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .eras
+                .iter()
+                .flat_map(|era| {
+                    era.pools.iter().map(|pool| {
+                        vec![
+                            era.era.to_string(),
+                            pool.pool_id.to_string(),
+                            pool.members.to_string(),
+                            pool.points.to_string(),
+                            pool.balance.to_string(),
+                            format!("{:.6}", pool.ratio()),
+                        ]
+                    })
+                })
+                .collect();
+            table(
+                &["era", "pool", "members", "points", "balance", "per point"],
+                &rows,
+            )
+        }
+    }
+
+    /// A reward claim of a CLI-created pool, with the balance of the credited account around
+    /// it.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolClaim {
+        pub(crate) pool_id: u32,
+        /// `payout` of a member or `commission` of the pool.
+        pub(crate) kind: String,
+        pub(crate) who: AccountId32,
+        pub(crate) claimed: Balance,
+        /// Fees paid by `who` for the claim.
+        pub(crate) fees: Balance,
+        pub(crate) before: Balance,
+        pub(crate) after: Balance,
+        /// Whether the balance grew by `claimed` net of `fees`.
+        pub(crate) verified: bool,
+    }
+
+    /// Result of `pools claim`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolClaims {
+        pub(crate) claims: Vec<PoolClaim>,
+    }
+
+    impl PoolClaims {
+        fn summary(&self) -> String {
+            let claimed: Balance = self.claims.iter().map(|c| c.claimed).sum();
+            let unverified = self.claims.iter().filter(|c| !c.verified).count();
+            format!(
+                "{} claim(s), {claimed} claimed, {unverified} balance(s) not matching the claim",
+                self.claims.len()
+            )
+        }
+    }
+
+    impl Render for PoolClaims {
+        fn plain(&self) -> String {
+            let mut lines = vec!["> Pool claims:".to_string()];
+            for c in self.claims.iter() {
+                let mark = if c.verified { "" } else { " [MISMATCH]" };
+                lines.push(format!(
+                    " pool {} {} of {}: {} claimed, {} fees, balance {} -> {}{mark}",
+                    c.pool_id, c.kind, c.who, c.claimed, c.fees, c.before, c.after
+                ));
+            }
+            lines.push(format!(" {}", self.summary()));
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .claims
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.pool_id.to_string(),
+                        c.kind.clone(),
+                        c.who.to_string(),
+                        c.claimed.to_string(),
+                        c.fees.to_string(),
+                        c.before.to_string(),
+                        c.after.to_string(),
+                        c.verified.to_string(),
+                    ]
+                })
+                .collect();
+            format!(
+                "{}\n{}",
+                table(
+                    &[
+                        "pool", "claim", "account", "claimed", "fees", "before", "after",
+                        "verified"
+                    ],
+                    &rows
+                ),
+                self.summary()
+            )
+        }
+    }
+
+    /// A member of a CLI-created pool which has not left it.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct StuckMember {
+        pub(crate) pool_id: u32,
+        pub(crate) who: AccountId32,
+        pub(crate) reason: String,
+    }
+
+    /// Result of `pools exit`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolsExit {
+        pub(crate) destroyed: Vec<u32>,
+        pub(crate) stuck: Vec<StuckMember>,
+    }
+
+    impl Render for PoolsExit {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> Pools destroyed: {:?}", self.destroyed)];
+            if self.stuck.is_empty() {
+                lines.push(" all the members left their pools.".to_string());
+            }
+            for s in self.stuck.iter() {
+                lines.push(format!(
+                    " ! pool {} member {}: {}",
+                    s.pool_id, s.who, s.reason
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .stuck
+                .iter()
+                .map(|s| vec![s.pool_id.to_string(), s.who.to_string(), s.reason.clone()])
+                .collect();
+            format!(
+                "pools destroyed: {:?}\n{}",
+                self.destroyed,
+                table(&["pool", "stuck member", "reason"], &rows)
+            )
+        }
+    }
+
+    /// The state, commission and roles of a pool.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolInfo {
+        pub(crate) pool_id: u32,
+        pub(crate) state: String,
+        /// Commission, in percent.
+        pub(crate) commission: Option<f64>,
+        pub(crate) commission_payee: Option<AccountId32>,
+        pub(crate) max_commission: Option<f64>,
+        /// Max increase, in percent, and min delay in blocks between increases.
+        pub(crate) change_rate: Option<(f64, u32)>,
+        pub(crate) root: Option<AccountId32>,
+        pub(crate) nominator: Option<AccountId32>,
+        pub(crate) bouncer: Option<AccountId32>,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PoolsInfo {
+        pub(crate) pools: Vec<PoolInfo>,
+    }
+
+    impl Render for PoolsInfo {
+        fn plain(&self) -> String {
+            let show =
+                |who: &Option<AccountId32>| who.as_ref().map_or("-".to_string(), |w| w.to_string());
+            let mut lines = vec!["> Pools:".to_string()];
+            for p in self.pools.iter() {
+                lines.push(format!(" pool {} ({}):", p.pool_id, p.state));
+                lines.push(format!(
+                    "  commission {} to {}, max {}, change rate {}",
+                    p.commission.map_or("-".to_string(), |c| format!("{c}%")),
+                    show(&p.commission_payee),
+                    p.max_commission
+                        .map_or("-".to_string(), |c| format!("{c}%")),
+                    p.change_rate.map_or("-".to_string(), |(increase, delay)| {
+                        format!("{increase}% per {delay} blocks")
+                    })
+                ));
+                lines.push(format!(
+                    "  root {}, nominator {}, bouncer {}",
+                    show(&p.root),
+                    show(&p.nominator),
+                    show(&p.bouncer)
+                ));
+            }
+            lines.join("\n")
+        }
+    }
+
+    /// An agent of `DelegatedStaking`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Agent {
+        pub(crate) agent: AccountId32,
+        pub(crate) payee: AccountId32,
+        pub(crate) total_delegated: Balance,
+        pub(crate) unclaimed_withdrawals: Balance,
+        pub(crate) pending_slash: Balance,
+        /// Active stake of its staking ledger.
+        pub(crate) active: Balance,
+        pub(crate) delegators: usize,
+        /// Pool the agent is the bonded account of.
+        pub(crate) pool: Option<u32>,
+    }
+
+    /// Result of `delegated agents`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Agents {
+        pub(crate) agents: Vec<Agent>,
+    }
+
+    impl Render for Agents {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {} agent(s):", self.agents.len())];
+            for a in self.agents.iter() {
+                let pool = a
+                    .pool
+                    .map_or(String::new(), |pool| format!(" [pool {pool}]"));
+                lines.push(format!(
+                    " {}{pool}: {} delegated by {} delegator(s), {} active, {} unclaimed, {} pending slash",
+                    a.agent, a.total_delegated, a.delegators, a.active, a.unclaimed_withdrawals, a.pending_slash
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .agents
+                .iter()
+                .map(|a| {
+                    vec![
+                        a.agent.to_string(),
+                        a.pool.map_or("-".to_string(), |p| p.to_string()),
+                        a.delegators.to_string(),
+                        a.total_delegated.to_string(),
+                        a.active.to_string(),
+                        a.unclaimed_withdrawals.to_string(),
+                        a.pending_slash.to_string(),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "agent",
+                    "pool",
+                    "delegators",
+                    "delegated",
+                    "active",
+                    "unclaimed",
+                    "pending slash",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// A delegator of `DelegatedStaking`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Delegator {
+        pub(crate) who: AccountId32,
+        pub(crate) agent: AccountId32,
+        pub(crate) amount: Balance,
+        /// Balance held by `DelegatedStaking`.
+        pub(crate) held: Balance,
+        /// Pool it is a member of, with its points.
+        pub(crate) pool: Option<u32>,
+        pub(crate) points: Option<u128>,
+        pub(crate) issues: Vec<String>,
+    }
+
+    /// Result of `delegated delegators`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Delegators {
+        pub(crate) delegators: Vec<Delegator>,
+    }
+
+    impl Render for Delegators {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {} delegator(s):", self.delegators.len())];
+            for d in self.delegators.iter() {
+                let member = match (d.pool, d.points) {
+                    (Some(pool), Some(points)) => format!(", pool {pool} with {points} points"),
+                    _ => String::new(),
+                };
+                lines.push(format!(
+                    " {} -> {}: {} delegated, {} held{member}",
+                    d.who, d.agent, d.amount, d.held
+                ));
+                for issue in d.issues.iter() {
+                    lines.push(format!("  ! {issue}"));
+                }
+            }
+            let flagged = self
+                .delegators
+                .iter()
+                .filter(|d| !d.issues.is_empty())
+                .count();
+            lines.push(format!(" {flagged} delegator(s) flagged."));
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .delegators
+                .iter()
+                .map(|d| {
+                    vec![
+                        d.who.to_string(),
+                        d.agent.to_string(),
+                        d.amount.to_string(),
+                        d.held.to_string(),
+                        d.pool.map_or("-".to_string(), |p| p.to_string()),
+                        d.points.map_or("-".to_string(), |p| p.to_string()),
+                        d.issues.join("; "),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "delegator",
+                    "agent",
+                    "delegated",
+                    "held",
+                    "pool",
+                    "points",
+                    "issues",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// A virtual staker with the violations of its invariants.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct VirtualStaker {
+        pub(crate) who: AccountId32,
+        /// Pool it is the bonded account of.
+        pub(crate) pool: Option<u32>,
+        /// Total delegated to it, if it is a `DelegatedStaking` agent.
+        pub(crate) delegated: Option<Balance>,
+        pub(crate) total: Balance,
+        pub(crate) active: Balance,
+        pub(crate) pool_points: Option<u128>,
+        pub(crate) violations: Vec<String>,
+    }
 
-        let mut bond_calls: Vec<(_, _)> = vec![];
-        let mut nominate_calls: Vec<(_, _)> = vec![];
+    /// Result of `check virtual-stakers`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct VirtualStakers {
+        pub(crate) stakers: Vec<VirtualStaker>,
+    }
 
-        // prepare both bond and nominate calls for generated & funded keypairs.
-        for pair in keypairs.into_iter() {
-            let bond_tx = staking_parachain::tx()
-                .staking()
-                .bond(bond_amount, RewardDestination::Staked);
-            let nominate_tx = staking_parachain::tx().staking().nominate(targets.clone());
+    impl Render for VirtualStakers {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> {} virtual staker(s):", self.stakers.len())];
+            for s in self.stakers.iter() {
+                let pool = s
+                    .pool
+                    .map_or(String::new(), |pool| format!(" [pool {pool}]"));
+                let delegated = s
+                    .delegated
+                    .map_or(String::new(), |d| format!(", {d} delegated"));
+                lines.push(format!(
+                    " {}{pool}: {} total, {} active{delegated}",
+                    s.who, s.total, s.active
+                ));
+                for violation in s.violations.iter() {
+                    lines.push(format!("  ! {violation}"));
+                }
+            }
+            lines.join("\n")
+        }
 
-            bond_calls.push((pair.clone(), bond_tx));
-            nominate_calls.push((pair, nominate_tx));
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .stakers
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.who.to_string(),
+                        s.pool.map_or("-".to_string(), |p| p.to_string()),
+                        s.total.to_string(),
+                        s.active.to_string(),
+                        s.delegated.map_or("-".to_string(), |d| d.to_string()),
+                        s.pool_points.map_or("-".to_string(), |p| p.to_string()),
+                        s.violations.join("; "),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "virtual staker",
+                    "pool",
+                    "total",
+                    "active",
+                    "delegated",
+                    "pool points",
+                    "violations",
+                ],
+                &rows,
+            )
         }
+    }
 
-        let mut it = bond_calls.into_iter().peekable();
-        while let Some(next) = it.next() {
-            let (pair, bond_tx) = next;
-            let mut progress = api
-                .tx()
-                .sign_and_submit_then_watch_default(&bond_tx, &pair)
-                .await?;
-            // make sure all bonds went through before progressing.
-            if it.peek().is_none() {
-                while let Some(_) = progress.next().await {}
+    /// A session report received from the relay chain in pages, not complete yet.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct IncompleteSessionReport {
+        pub(crate) end_index: Option<u128>,
+        /// Validators with points received so far.
+        pub(crate) validator_points: usize,
+    }
+
+    /// A validator set waiting to be sent to the relay chain.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct OutgoingValidatorSet {
+        pub(crate) id: Option<u128>,
+        pub(crate) validators: usize,
+        pub(crate) retries_left: Option<u128>,
+    }
+
+    /// Result of `staking-messages`. `None` for the entries the runtime does not have.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct StakingMessages {
+        pub(crate) rc_client: String,
+        pub(crate) rc_client_found: bool,
+        /// End index of the last session report processed.
+        pub(crate) last_session_report: Option<u128>,
+        pub(crate) incomplete_session_report: Option<IncompleteSessionReport>,
+        pub(crate) outgoing_validator_set: Option<OutgoingValidatorSet>,
+        pub(crate) offence_queue_eras: Option<usize>,
+        pub(crate) queued_offences: Option<usize>,
+        pub(crate) processing_offence: bool,
+        /// Messages waiting to be sent to the relay chain.
+        pub(crate) upward_queue: Option<usize>,
+        /// Messages from the relay chain waiting to be processed.
+        pub(crate) downward_queue: Option<u128>,
+    }
+
+    impl Render for StakingMessages {
+        fn plain(&self) -> String {
+            let show = |value: Option<String>| value.unwrap_or_else(|| "n/a".to_string());
+            let mut lines = vec![format!(
+                "> Staking messages ({}{}):",
+                self.rc_client,
+                if self.rc_client_found {
+                    ""
+                } else {
+                    " not found in the runtime"
+                }
+            )];
+            lines.push(format!(
+                " last session report processed: {}",
+                show(self.last_session_report.map(|i| i.to_string()))
+            ));
+            lines.push(format!(
+                " incomplete session report: {}",
+                show(self.incomplete_session_report.as_ref().map(|r| format!(
+                    "ending session {}, {} validator(s) with points so far",
+                    show(r.end_index.map(|i| i.to_string())),
+                    r.validator_points
+                )))
+            ));
+            lines.push(format!(
+                " outgoing validator set: {}",
+                show(self.outgoing_validator_set.as_ref().map(|s| format!(
+                    "id {} of {} validator(s), {} retries left",
+                    show(s.id.map(|i| i.to_string())),
+                    s.validators,
+                    show(s.retries_left.map(|r| r.to_string()))
+                )))
+            ));
+            lines.push(format!(
+                " offences: {} queued over {} era(s){}",
+                show(self.queued_offences.map(|n| n.to_string())),
+                show(self.offence_queue_eras.map(|n| n.to_string())),
+                if self.processing_offence {
+                    ", one being processed"
+                } else {
+                    ""
+                }
+            ));
+            lines.push(format!(
+                " upward queue: {} message(s), downward queue: {} message(s)",
+                show(self.upward_queue.map(|n| n.to_string())),
+                show(self.downward_queue.map(|n| n.to_string()))
+            ));
+            lines.join("\n")
+        }
+    }
+
+    /// The staking limits applying to a bond, of an account if given.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct BondLimits {
+        pub(crate) account: Option<AccountId32>,
+        pub(crate) bond: Balance,
+        /// `None` if the runtime has no nominations quota API.
+        pub(crate) nominations_quota: Option<u32>,
+        pub(crate) can_nominate: bool,
+        pub(crate) can_validate: bool,
+        pub(crate) nominations: Option<usize>,
+        pub(crate) unlocking_chunks: Option<usize>,
+    }
+
+    /// Result of `quota`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Quota {
+        pub(crate) min_nominator_bond: Balance,
+        pub(crate) min_validator_bond: Balance,
+        pub(crate) max_unlocking_chunks: u32,
+        pub(crate) max_exposure_page_size: u32,
+        pub(crate) bonding_duration: u32,
+        pub(crate) limits: Vec<BondLimits>,
+    }
+
+    impl Render for Quota {
+        fn plain(&self) -> String {
+            let mut lines = vec![
+                format!(
+                    "> Min bonds: {} to nominate, {} to validate",
+                    self.min_nominator_bond, self.min_validator_bond
+                ),
+                format!(
+                    "> Max {} unlocking chunks, {} nominators per exposure page, {} eras of bonding",
+                    self.max_unlocking_chunks, self.max_exposure_page_size, self.bonding_duration
+                ),
+            ];
+            for l in self.limits.iter() {
+                let who = l.account.as_ref().map_or(format!("bond {}", l.bond), |a| {
+                    format!("{a} (bond {})", l.bond)
+                });
+                let quota = l
+                    .nominations_quota
+                    .map_or("n/a".to_string(), |q| q.to_string());
+                let mut line = format!(
+                    " {who}: {quota} nominations allowed, can nominate: {}, can validate: {}",
+                    l.can_nominate, l.can_validate
+                );
+                if let (Some(nominations), Some(chunks)) = (l.nominations, l.unlocking_chunks) {
+                    line.push_str(&format!(
+                        ", {nominations} nominations, {chunks}/{} unlocking chunks",
+                        self.max_unlocking_chunks
+                    ));
+                }
+                lines.push(line);
             }
+            lines.join("\n")
         }
-        println!("Bonding done for {n_nominators} staker(s).");
 
-        let mut it = nominate_calls.into_iter().peekable();
-        while let Some(next) = it.next() {
-            let (pair, nominate_tx) = next;
-            let mut progress = api
-                .tx()
-                .sign_and_submit_then_watch_default(&nominate_tx, &pair)
-                .await?;
-            // make sure all bonds went through before progressing.
-            if it.peek().is_none() {
-                while let Some(_) = progress.next().await {}
+        fn table(&self) -> String {
+            let show = |value: Option<usize>| value.map_or("-".to_string(), |v| v.to_string());
+            let rows: Vec<Vec<String>> = self
+                .limits
+                .iter()
+                .map(|l| {
+                    vec![
+                        l.account
+                            .as_ref()
+                            .map_or("-".to_string(), |a| a.to_string()),
+                        l.bond.to_string(),
+                        l.nominations_quota
+                            .map_or("n/a".to_string(), |q| q.to_string()),
+                        l.can_nominate.to_string(),
+                        l.can_validate.to_string(),
+                        show(l.nominations),
+                        show(l.unlocking_chunks),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "account",
+                    "bond",
+                    "quota",
+                    "nominate",
+                    "validate",
+                    "nominations",
+                    "unlocking",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// A task scheduled by `schedule`, cancellable with `scheduler.cancel_named(id)`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ScheduledTask {
+        pub(crate) id: H256,
+        /// `Root` or the account the call is dispatched as.
+        pub(crate) origin: String,
+    }
+
+    /// Result of `schedule`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Scheduled {
+        pub(crate) when: u32,
+        pub(crate) call: String,
+        pub(crate) tasks: Vec<ScheduledTask>,
+    }
+
+    impl Render for Scheduled {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> {} scheduled at block {} for {} origin(s):",
+                self.call,
+                self.when,
+                self.tasks.len()
+            )];
+            for t in self.tasks.iter() {
+                lines.push(format!(" {:?} as {}", t.id, t.origin));
             }
+            lines.join("\n")
         }
-        println!("Nominations done for {n_nominators} staker(s).");
-        let configs = stakers_info(configs).await?;
 
-        Ok(configs)
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .tasks
+                .iter()
+                .map(|t| vec![format!("{:?}", t.id), t.origin.clone()])
+                .collect();
+            format!(
+                "{} at block {}\n{}",
+                self.call,
+                self.when,
+                table(&["task id", "origin"], &rows)
+            )
+        }
     }
 
-    /// Fetches the current stakers info.
-    pub(crate) async fn stakers_info(configs: Configs) -> color_eyre::Result<Configs> {
-        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
+    /// An aye vote cast by `open-gov`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct GovVote {
+        pub(crate) voter: AccountId32,
+        pub(crate) balance: Balance,
+    }
+
+    /// Result of `open-gov`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct OpenGov {
+        /// The proposed call.
+        pub(crate) call: String,
+        pub(crate) preimage_hash: H256,
+        pub(crate) referendum: u32,
+        pub(crate) origin: String,
+        pub(crate) track: Option<u16>,
+        pub(crate) votes: Vec<GovVote>,
+        /// Last `Referenda` event of the referendum, `None` if it was still ongoing.
+        pub(crate) outcome: Option<String>,
+        /// Block the new configs were found on chain at.
+        pub(crate) enacted: Option<u32>,
+    }
+
+    impl Render for OpenGov {
+        fn plain(&self) -> String {
+            let mut lines = vec![
+                format!("> {}", self.call),
+                format!(" preimage: {:?}", self.preimage_hash),
+                format!(
+                    " referendum #{} from {} on track {}",
+                    self.referendum,
+                    self.origin,
+                    self.track.map_or("?".to_string(), |t| t.to_string())
+                ),
+            ];
+            for vote in self.votes.iter() {
+                lines.push(format!(" aye with {} from {}", vote.balance, vote.voter));
+            }
+            lines.push(match &self.outcome {
+                Some(outcome) => format!(" {outcome}"),
+                None => " ! still ongoing".to_string(),
+            });
+            if let Some(enacted) = self.enacted {
+                lines.push(format!(" configs enacted at #{enacted}"));
+            }
+            lines.join("\n")
+        }
+    }
 
-        let validators = helpers::get_validators(&api).await?;
-        let nominators = helpers::get_nominators(&api).await?;
+    /// Run of a population command against one of several chains.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ChainRun {
+        pub(crate) url: String,
+        /// Spec name and genesis hash prefix of the chain, suffixing its files.
+        pub(crate) chain: String,
+        pub(crate) state: PathBuf,
+        pub(crate) error: Option<String>,
+    }
 
-        println!("> Stakers info:");
-        println!(" {:?} validators registered.", validators.len());
-        println!(" {:?} nominators registered.", nominators.len());
+    /// Result of a population command run against several chains.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ChainRuns(pub(crate) Vec<ChainRun>);
 
-        Ok(configs)
+    impl Render for ChainRuns {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> Ran against {} chains:", self.0.len())];
+            for run in self.0.iter() {
+                lines.push(match &run.error {
+                    None => format!(
+                        " {} ({}): ok, state in {}",
+                        run.url,
+                        run.chain,
+                        run.state.display()
+                    ),
+                    Some(e) => format!(" {} ({}): failed: {e}", run.url, run.chain),
+                });
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .0
+                .iter()
+                .map(|run| {
+                    vec![
+                        run.url.clone(),
+                        run.chain.clone(),
+                        run.state.display().to_string(),
+                        run.error.clone().unwrap_or_else(|| "ok".to_string()),
+                    ]
+                })
+                .collect();
+            table(&["url", "chain", "state", "result"], &rows)
+        }
     }
 
-    pub(crate) async fn playground(configs: Configs) -> color_eyre::Result<Configs> {
-        let api = OnlineClient::<SubstrateConfig>::from_url(&configs.url).await?;
-        let _current_validators = helpers::get_validators(&api).await?;
-        Ok(configs)
+    /// Result of `tag`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Tags {
+        /// Number of accounts tagged.
+        pub(crate) tagged: usize,
+        /// Number of accounts in scope with each tag.
+        pub(crate) tags: std::collections::BTreeMap<String, usize>,
+    }
+
+    impl Render for Tags {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!("> Tagged {} accounts.", self.tagged)];
+            for (tag, count) in self.tags.iter() {
+                lines.push(format!(" {tag}: {count} accounts"));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .tags
+                .iter()
+                .map(|(tag, count)| vec![tag.clone(), count.to_string()])
+                .collect();
+            table(&["tag", "accounts"], &rows)
+        }
+    }
+
+    /// Outcome of an assertion of `assert`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct AssertionResult {
+        pub(crate) assertion: String,
+        /// Value of the metric, if it could be read.
+        pub(crate) actual: Option<u128>,
+        pub(crate) passed: bool,
+        pub(crate) error: Option<String>,
+    }
+
+    /// Result of `assert`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Assertions(pub(crate) Vec<AssertionResult>);
+
+    impl Render for Assertions {
+        fn plain(&self) -> String {
+            let passed = self.0.iter().filter(|r| r.passed).count();
+            let mut lines = vec![format!("> {passed}/{} assertions hold:", self.0.len())];
+            for r in self.0.iter() {
+                let mark = if r.passed { "ok" } else { "!" };
+                lines.push(match (r.actual, r.error.as_ref()) {
+                    (Some(actual), _) => format!(" {mark} {} (actual {actual})", r.assertion),
+                    (None, Some(e)) => format!(" {mark} {}: {e}", r.assertion),
+                    (None, None) => format!(" {mark} {}", r.assertion),
+                });
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .0
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.assertion.clone(),
+                        r.actual.map_or("-".to_string(), |a| a.to_string()),
+                        if r.passed { "ok" } else { "failed" }.to_string(),
+                        r.error.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            table(&["assertion", "actual", "result", "error"], &rows)
+        }
+    }
+
+    /// Result of `election estimate`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionEstimate {
+        pub(crate) voters: u64,
+        /// Voters the snapshot holds at most, over all its pages.
+        pub(crate) max_voters: u64,
+        pub(crate) targets: u32,
+        pub(crate) max_targets: u32,
+        pub(crate) desired_targets: u32,
+        pub(crate) pages: u32,
+        pub(crate) votes_per_voter: f64,
+        /// Estimated encoded size of a full voter snapshot page.
+        pub(crate) voter_page_bytes: u64,
+        /// Estimated encoded size of the whole snapshot, targets included.
+        pub(crate) snapshot_bytes: u64,
+        /// Estimated encoded size of a full solution page.
+        pub(crate) solution_page_bytes: u64,
+        pub(crate) max_proof_size: u64,
+        /// Max length of a block for normal extrinsics.
+        pub(crate) max_length: u32,
+        /// Most voters nominating a single target.
+        pub(crate) most_backers: u32,
+        pub(crate) max_backers: Option<u32>,
+        pub(crate) max_winners_per_page: Option<u32>,
+        pub(crate) warnings: Vec<String>,
+    }
+
+    /// Result of `election plan`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionPlan {
+        /// Estimate of the election after the planned population.
+        pub(crate) estimate: ElectionEstimate,
+        /// Planned nominators bonding too little to make it into the snapshot.
+        pub(crate) trimmed: usize,
+        /// Lowest stake making it into the snapshot, if the voters exceed its bound.
+        pub(crate) cutoff: Option<Balance>,
+        pub(crate) advice: Vec<String>,
+    }
+
+    impl Render for ElectionPlan {
+        fn plain(&self) -> String {
+            let mut lines = vec![self.estimate.plain()];
+            if let Some(cutoff) = self.cutoff {
+                lines.push(format!(
+                    "> Snapshot cutoff stake {cutoff}, {} planned nominators below it.",
+                    self.trimmed
+                ));
+            }
+            if self.advice.is_empty() {
+                lines.push("The planned population fits the election.".to_string());
+            } else {
+                lines.push("> Advice:".to_string());
+                lines.extend(self.advice.iter().map(|a| format!(" - {a}")));
+            }
+            lines.join("\n")
+        }
+    }
+
+    impl Render for ElectionEstimate {
+        fn plain(&self) -> String {
+            let bound = |b: Option<u32>| b.map_or("unknown".to_string(), |b| b.to_string());
+            let mut lines = vec![
+                format!(
+                    "> Snapshot: {}/{} voters ({} pages), {}/{} targets, {:.2} votes per voter",
+                    self.voters,
+                    self.max_voters,
+                    self.pages,
+                    self.targets,
+                    self.max_targets,
+                    self.votes_per_voter
+                ),
+                format!(
+                    " ~{} bytes, ~{} per voter page (max proof size {})",
+                    self.snapshot_bytes, self.voter_page_bytes, self.max_proof_size
+                ),
+                format!(
+                    "> Solution: {} winners, ~{} bytes per page (max block length {})",
+                    self.desired_targets, self.solution_page_bytes, self.max_length
+                ),
+                format!(
+                    " most backers of a target {} (max {}), max winners per page {}",
+                    self.most_backers,
+                    bound(self.max_backers),
+                    bound(self.max_winners_per_page)
+                ),
+            ];
+            if self.warnings.is_empty() {
+                lines.push("No trimming expected.".to_string());
+            }
+            lines.extend(self.warnings.iter().map(|w| format!(" ! {w}")));
+            lines.join("\n")
+        }
+    }
+
+    /// Result of `election mine`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct MinedSolution {
+        pub(crate) round: u32,
+        pub(crate) params: String,
+        pub(crate) voters: usize,
+        pub(crate) trimmed_voters: usize,
+        pub(crate) trimmed_backers: usize,
+        pub(crate) winners: usize,
+        pub(crate) edges: usize,
+        pub(crate) reduced_edges: usize,
+        /// Estimated encoded size of the solution.
+        pub(crate) size_bytes: usize,
+        pub(crate) score: Score,
+        /// Solutions mined before in the round.
+        pub(crate) others: Vec<index::MinedRecord>,
+    }
+
+    impl Render for MinedSolution {
+        fn plain(&self) -> String {
+            let mut lines = vec![
+                format!("> Solution mined in round {} with {}:", self.round, self.params),
+                format!(
+                    " {} voters ({} trimmed), {} winners, {} edges ({} reduced, {} backers trimmed), ~{} bytes",
+                    self.voters,
+                    self.trimmed_voters,
+                    self.winners,
+                    self.edges,
+                    self.reduced_edges,
+                    self.trimmed_backers,
+                    self.size_bytes
+                ),
+                format!(" score {}", self.score),
+            ];
+            if !self.others.is_empty() {
+                lines.push(format!("> Mined before in round {}:", self.round));
+                for other in self.others.iter() {
+                    lines.push(format!(
+                        " {}: {} edges, score ({}, {}, {})",
+                        other.params,
+                        other.edges,
+                        other.score.minimal_stake,
+                        other.score.sum_stake,
+                        other.score.sum_stake_squared
+                    ));
+                }
+            }
+            lines.join("\n")
+        }
+    }
+
+    /// A voter or target left out of the snapshot by `simulate-trimming`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct TrimmedStaker {
+        pub(crate) who: AccountId32,
+        /// Vote weight of a voter.
+        pub(crate) weight: Option<u64>,
+        pub(crate) managed: bool,
+    }
+
+    /// Result of `simulate-trimming`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct TrimmingSimulation {
+        pub(crate) max_voters: usize,
+        /// Voters taken into the snapshot.
+        pub(crate) voters: usize,
+        /// Vote weight of the last voter taken.
+        pub(crate) lowest_taken: Option<u64>,
+        pub(crate) max_targets: usize,
+        pub(crate) excluded_voters: Vec<TrimmedStaker>,
+        pub(crate) excluded_targets: Vec<TrimmedStaker>,
+    }
+
+    impl Render for TrimmingSimulation {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Snapshot takes {}/{} voters, the last one with weight {}.",
+                self.voters,
+                self.max_voters,
+                self.lowest_taken.unwrap_or_default()
+            )];
+            if self.excluded_voters.is_empty() && self.excluded_targets.is_empty() {
+                lines.push("Nobody is left out.".to_string());
+            }
+            for s in self.excluded_voters.iter() {
+                lines.push(format!(
+                    " ! voter {} (weight {}){} left out",
+                    s.who,
+                    s.weight.unwrap_or_default(),
+                    if s.managed { ", CLI-created," } else { "" }
+                ));
+            }
+            for s in self.excluded_targets.iter() {
+                lines.push(format!(
+                    " ! target {}{} left out, over {} targets",
+                    s.who,
+                    if s.managed { " (CLI-created)" } else { "" },
+                    self.max_targets
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .excluded_voters
+                .iter()
+                .map(|s| ("voter", s))
+                .chain(self.excluded_targets.iter().map(|s| ("target", s)))
+                .map(|(kind, s)| {
+                    vec![
+                        kind.to_string(),
+                        s.who.to_string(),
+                        s.weight.map_or("-".to_string(), |w| w.to_string()),
+                        s.managed.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["kind", "account", "weight", "managed"], &rows)
+        }
+    }
+
+    /// Duration, in blocks, of the phases of an election round.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct RoundTiming {
+        pub(crate) round: u32,
+        pub(crate) durations: std::collections::BTreeMap<String, u32>,
+    }
+
+    /// Duration, in blocks, of an election phase across rounds.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct PhaseStats {
+        pub(crate) phase: String,
+        pub(crate) rounds: usize,
+        pub(crate) mean: f64,
+        pub(crate) stddev: f64,
+        pub(crate) min: u32,
+        pub(crate) max: u32,
+        /// Configured duration, for the phases with a bounded one.
+        pub(crate) configured: Option<u32>,
+    }
+
+    /// Result of `election timing`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionTiming {
+        pub(crate) rounds: Vec<RoundTiming>,
+        pub(crate) phases: Vec<PhaseStats>,
+    }
+
+    impl Render for ElectionTiming {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Phase durations over {} round(s):",
+                self.rounds.len()
+            )];
+            for timing in self.rounds.iter() {
+                let durations: Vec<String> = timing
+                    .durations
+                    .iter()
+                    .map(|(phase, blocks)| format!("{phase} {blocks}"))
+                    .collect();
+                lines.push(format!(" round {}: {}", timing.round, durations.join(", ")));
+            }
+            lines.push("> Across rounds:".to_string());
+            for p in self.phases.iter() {
+                lines.push(format!(
+                    " {}: mean {:.1} blocks, stddev {:.1}, range {}..={} over {} round(s){}",
+                    p.phase,
+                    p.mean,
+                    p.stddev,
+                    p.min,
+                    p.max,
+                    p.rounds,
+                    p.configured
+                        .map_or(String::new(), |c| format!(", {c} configured"))
+                ));
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .phases
+                .iter()
+                .map(|p| {
+                    vec![
+                        p.phase.clone(),
+                        p.rounds.to_string(),
+                        format!("{:.1}", p.mean),
+                        format!("{:.1}", p.stddev),
+                        p.min.to_string(),
+                        p.max.to_string(),
+                        p.configured.map_or("-".to_string(), |c| c.to_string()),
+                    ]
+                })
+                .collect();
+            table(
+                &[
+                    "phase",
+                    "rounds",
+                    "mean",
+                    "stddev",
+                    "min",
+                    "max",
+                    "configured",
+                ],
+                &rows,
+            )
+        }
+    }
+
+    /// An elected validator that authored no block of the checked window.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct SilentValidator {
+        pub(crate) validator: AccountId32,
+        pub(crate) managed: bool,
+        /// Whether it was in the session validators at any block of the window.
+        pub(crate) in_session: bool,
+    }
+
+    /// Result of `check authorship`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct AuthorshipCheck {
+        pub(crate) era: u32,
+        pub(crate) from: u32,
+        pub(crate) to: u32,
+        /// Whether every session validator had a slot in the window, otherwise the validators
+        /// that never authored are not flagged.
+        pub(crate) complete: bool,
+        pub(crate) elected: usize,
+        pub(crate) authors: usize,
+        /// Elected validators disabled in the window, not expected to author.
+        pub(crate) disabled: Vec<AccountId32>,
+        pub(crate) never_authored: Vec<SilentValidator>,
+        /// Authors not elected for the era.
+        pub(crate) unexpected: Vec<AccountId32>,
+    }
+
+    impl Render for AuthorshipCheck {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Era {}: {} author(s) in #{}..=#{} for {} elected validator(s).",
+                self.era, self.authors, self.from, self.to, self.elected
+            )];
+            if !self.complete {
+                lines.push(
+                    " not every validator had a slot yet, the silent ones are not flagged."
+                        .to_string(),
+                );
+            }
+            for validator in self.disabled.iter() {
+                lines.push(format!(" {validator} disabled"));
+            }
+            for silent in self.never_authored.iter() {
+                lines.push(format!(
+                    "  ! {}{} elected but never authored{}",
+                    silent.validator,
+                    if silent.managed { " [managed]" } else { "" },
+                    if silent.in_session {
+                        ""
+                    } else {
+                        ", not in the session validators"
+                    }
+                ));
+            }
+            for author in self.unexpected.iter() {
+                lines.push(format!("  ! {author} authored but was not elected"));
+            }
+            if self.never_authored.is_empty() && self.unexpected.is_empty() {
+                lines.push("> The authors match the elected validators.".to_string());
+            }
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let mut rows: Vec<Vec<String>> = self
+                .disabled
+                .iter()
+                .map(|v| vec![v.to_string(), "-".to_string(), "disabled".to_string()])
+                .collect();
+            rows.extend(self.never_authored.iter().map(|s| {
+                vec![
+                    s.validator.to_string(),
+                    s.managed.to_string(),
+                    if s.in_session {
+                        "never authored".to_string()
+                    } else {
+                        "not in session".to_string()
+                    },
+                ]
+            }));
+            rows.extend(
+                self.unexpected
+                    .iter()
+                    .map(|a| vec![a.to_string(), "-".to_string(), "not elected".to_string()]),
+            );
+            table(&["validator", "managed", "status"], &rows)
+        }
+    }
+
+    /// Result of the compliance sweep of `set-min-commission`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct MinCommissionSweep {
+        /// The new minimum commission, in ppb.
+        pub(crate) min_commission: u32,
+        /// Validators with a lower commission.
+        pub(crate) below: Vec<ValidatorCommission>,
+    }
+
+    impl Render for MinCommissionSweep {
+        fn plain(&self) -> String {
+            let mut lines: Vec<String> = self
+                .below
+                .iter()
+                .map(|v| format!(" ! {} has commission {} ppb.", v.validator, v.commission))
+                .collect();
+            lines.push(format!(
+                "{} validator(s) below the minimum commission of {} ppb.",
+                self.below.len(),
+                self.min_commission
+            ));
+            lines.join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .below
+                .iter()
+                .map(|v| vec![v.validator.to_string(), v.commission.to_string()])
+                .collect();
+            table(&["validator", "commission_ppb"], &rows)
+        }
+    }
+
+    /// Result of `verify-election`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct ElectionVerification {
+        pub(crate) era: u32,
+        /// Number of winners on-chain.
+        pub(crate) onchain: usize,
+        /// Number of winners offline.
+        pub(crate) offline: usize,
+        pub(crate) divergences: Vec<String>,
+    }
+
+    impl Render for ElectionVerification {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "> Comparing with era {} ({} winners on-chain, {} offline):",
+                self.era, self.onchain, self.offline
+            )];
+            lines.extend(self.divergences.iter().map(|d| format!(" ! {d}")));
+            if self.divergences.is_empty() {
+                lines.push(format!(
+                    "On-chain election of era {} matches the offline one.",
+                    self.era
+                ));
+            }
+            lines.join("\n")
+        }
+    }
+
+    /// Result of `submit-raw`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct RawSubmission {
+        pub(crate) extrinsic: H256,
+        pub(crate) block: H256,
+        /// Events of the extrinsic, as `Pallet::Event`.
+        pub(crate) events: Vec<String>,
+    }
+
+    impl Render for RawSubmission {
+        fn plain(&self) -> String {
+            let mut lines = vec![format!(
+                "Extrinsic {:?} finalized in block {:?}.",
+                self.extrinsic, self.block
+            )];
+            lines.extend(self.events.iter().map(|e| format!(" {e}")));
+            lines.join("\n")
+        }
+    }
+
+    /// Pages still unpaid after `rewards payout`.
+    #[derive(Debug, Serialize)]
+    pub(crate) struct Unpaid(pub(crate) Vec<OutstandingPayout>);
+
+    impl Render for Unpaid {
+        fn plain(&self) -> String {
+            if self.0.is_empty() {
+                return "All the outstanding pages are paid.".to_string();
+            }
+            self.0
+                .iter()
+                .map(|p| {
+                    format!(
+                        " ! era {} page {} of {} is still unpaid.",
+                        p.era, p.page, p.validator
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        fn table(&self) -> String {
+            let rows: Vec<Vec<String>> = self
+                .0
+                .iter()
+                .map(|p| {
+                    vec![
+                        p.era.to_string(),
+                        p.validator.to_string(),
+                        p.page.to_string(),
+                    ]
+                })
+                .collect();
+            table(&["era", "validator", "page"], &rows)
+        }
     }
 }
 
-mod helpers {
+#[cfg(test)]
+mod tests {
     use super::*;
-    use std::io::Write;
-    use subxt_signer::sr25519::Keypair;
 
-    /// Randomly generates and funds `n` accounts. The vec of key paurs of the generated accounts
-    /// are returned.
-    pub(crate) async fn fund_accounts(
-        api: &OnlineClient<SubstrateConfig>,
-        n: usize,
-        amount: Option<Balance>,
-    ) -> color_eyre::Result<Vec<Keypair>> {
-        let ed = staking_parachain::constants()
-            .balances()
-            .existential_deposit();
-        let fund_with = amount.unwrap_or(api.constants().at(&ed)? * 1000);
+    /// A fresh directory for the files written by a test.
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("staking-cli-{test}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
 
-        let mut pairs: Vec<Keypair> = vec![];
-        let mut mint_calls: Vec<RuntimeCall> = vec![];
+    fn assertion(metric: &str, op: CmpOp, value: u128) -> Assertion {
+        Assertion {
+            metric: metric.to_string(),
+            op,
+            value,
+        }
+    }
 
-        // generate and fund new accounts:
-        // - generate random keypair
-        // - funds account
-        for _n in 0..n {
-            let mut rng = rand::thread_rng();
-            let seed: usize = rng.gen();
-            let pair = helpers::signer_from_seed(&seed.to_string());
+    #[test]
+    fn assertion_parses_two_char_ops_before_one_char_ones() {
+        assert_eq!(
+            "validators >= 10".parse(),
+            Ok(assertion("validators", CmpOp::Ge, 10))
+        );
+        assert_eq!(
+            "validators > 10".parse(),
+            Ok(assertion("validators", CmpOp::Gt, 10))
+        );
+        assert_eq!(
+            "active_era<=3".parse(),
+            Ok(assertion("active_era", CmpOp::Le, 3))
+        );
+        assert_eq!(
+            "nominators != 0".parse(),
+            Ok(assertion("nominators", CmpOp::Ne, 0))
+        );
+        assert_eq!(
+            "nominators == 0".parse(),
+            Ok(assertion("nominators", CmpOp::Eq, 0))
+        );
+    }
 
-            pairs.push(pair.clone());
+    #[test]
+    fn assertion_values_accept_separators() {
+        assert_eq!(
+            "queued_score.minimal_stake > 1_000_000".parse(),
+            Ok(assertion(
+                "queued_score.minimal_stake",
+                CmpOp::Gt,
+                1_000_000
+            ))
+        );
+        assert!("validators > ten".parse::<Assertion>().is_err());
+        assert!("validators".parse::<Assertion>().is_err());
+    }
 
-            let mint_call = BalancesCall::transfer_allow_death {
-                dest: pair.public_key().into(),
-                value: fund_with,
+    #[test]
+    fn no_failed_extrinsics_is_zero_failed_extrinsics() {
+        assert_eq!(
+            " no_failed_extrinsics ".parse(),
+            Ok(assertion("failed_extrinsics", CmpOp::Eq, 0))
+        );
+    }
+
+    #[test]
+    fn scenario_lines_turn_expectations_into_asserts() {
+        let scenario = "\
+# setup
+ramp --validators 10
+
+expect: validators >= 10
+expect:
+  nominators > 0
+  no_failed_extrinsics
+wait-until-active
+";
+        assert_eq!(
+            helpers::scenario_lines(scenario),
+            vec![
+                "ramp --validators 10",
+                "assert validators >= 10",
+                "assert nominators > 0",
+                "assert no_failed_extrinsics",
+                "wait-until-active",
+            ]
+        );
+    }
+
+    #[test]
+    fn concentration_of_known_distributions() {
+        let equal = helpers::concentration(vec![100; 4], 1);
+        assert_eq!((equal.count, equal.total), (4, 400));
+        assert!(equal.gini.abs() < 1e-9);
+        assert!((equal.hhi - 0.25).abs() < 1e-9);
+        assert!((equal.top_k_share - 0.25).abs() < 1e-9);
+
+        // a single holder of the whole stake.
+        let single = helpers::concentration(vec![0, 0, 0, 100], 1);
+        assert!((single.gini - 0.75).abs() < 1e-9);
+        assert!((single.hhi - 1.0).abs() < 1e-9);
+        assert!((single.top_k_share - 1.0).abs() < 1e-9);
+
+        let unsorted = helpers::concentration(vec![30, 10, 20], 2);
+        assert!((unsorted.top_k_share - 50.0 / 60.0).abs() < 1e-9);
+
+        let empty = helpers::concentration(vec![], 3);
+        assert_eq!((empty.count, empty.gini, empty.hhi), (0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn bond_distribution_buckets_and_percentiles() {
+        let stakes: Vec<Balance> = (1..=100).rev().collect();
+        let d = helpers::bond_distribution(stakes, 4).unwrap();
+        assert_eq!((d.nominators, d.min, d.max, d.mean), (100, 1, 100, 50));
+        assert_eq!((d.p50, d.p90, d.p99), (50, 90, 99));
+        // 99 / 4 wide buckets, the highest stakes fall in the last one.
+        let buckets: Vec<(Balance, usize)> =
+            d.buckets.iter().map(|b| (b.from, b.nominators)).collect();
+        assert_eq!(buckets, vec![(1, 24), (25, 24), (49, 24), (73, 28)]);
+
+        let same = helpers::bond_distribution(vec![7; 3], 2).unwrap();
+        assert_eq!((same.p50, same.p99), (7, 7));
+        assert_eq!(same.buckets[0].nominators, 3);
+
+        assert!(helpers::bond_distribution(vec![], 4).is_none());
+    }
+
+    #[test]
+    fn schedule_target_predicts_the_next_phase_start() {
+        let dir = scratch_dir("schedule-target");
+        let path = dir.join("index.json");
+        let mut index = index::Index::default();
+        for (round, block) in [(1, 100), (2, 150)] {
+            let record = index::PhaseRecord {
+                phase: "Signed".to_string(),
+                block,
             };
-            mint_calls.push(RuntimeCall::Balances(mint_call));
+            index.election_phases.insert(round, vec![record]);
         }
+        index.save(&path).unwrap();
+        let configs = Configs::from_iter([
+            "staking-cli",
+            "--url",
+            "ws://127.0.0.1:9944",
+            "--index",
+            path.to_str().unwrap(),
+        ]);
 
-        let tx = staking_parachain::tx().utility().batch(mint_calls);
-        let mut progress = api
-            .tx()
-            .sign_and_submit_then_watch_default(&tx, &dev::alice())
-            .await?;
-        // make sure all mints went through before progressing.
-        while let Some(_) = progress.next().await {}
+        for (schedule, now, target) in [
+            ("7", 100, 7),
+            ("signed", 120, 150),
+            ("signed-5", 120, 145),
+            ("signed-5", 145, 195),
+            ("signed+10", 300, 310),
+        ] {
+            let schedule: Schedule = schedule.parse().unwrap();
+            assert_eq!(
+                helpers::schedule_target(&configs, &schedule, now).unwrap(),
+                target,
+                "{schedule:?} at {now}"
+            );
+        }
 
-        Ok(pairs)
+        let unrecorded: Schedule = "snapshot".parse().unwrap();
+        assert!(helpers::schedule_target(&configs, &unrecorded, 120).is_err());
     }
 
-    /// Fetches all validators registered in the system.
-    pub(crate) async fn get_validators(
-        api: &OnlineClient<SubstrateConfig>,
-    ) -> color_eyre::Result<Targets> {
-        let mut validators = vec![];
-        let storage_query = staking_parachain::storage().staking().validators_iter();
+    #[test]
+    fn rehearsal_lines_point_at_copies() {
+        let dir = scratch_dir("rehearsal-lines");
+        let state = dir.join("state.json");
+        std::fs::write(&state, "{}").unwrap();
+        let state = state.display().to_string();
+        let lines = vec![
+            format!("validate --state {state} --index idx.json"),
+            format!("nominate --state={state}"),
+            "wait-until-active".to_string(),
+        ];
 
-        let mut results = api.storage().at_latest().await?.iter(storage_query).await?;
-        while let Some(Ok(kv)) = results.next().await {
-            let (k, _) = kv;
-            let account: Vec<u8> = k.into_iter().rev().take(32).rev().collect();
-            let account: [u8; 32] = account.try_into().expect("32 bytes should fit");
-            let maddress = Id(AccountId32(account));
+        let rehearsed = helpers::rehearsal_lines(&lines, &dir).unwrap();
+        let (state_copy, index_copy) = (dir.join("0-state"), dir.join("1-index"));
+        assert_eq!(
+            rehearsed,
+            vec![
+                format!(
+                    "validate --state {} --index {}",
+                    state_copy.display(),
+                    index_copy.display()
+                ),
+                format!("nominate --state={}", state_copy.display()),
+                "wait-until-active".to_string(),
+            ]
+        );
+        assert_eq!(std::fs::read_to_string(state_copy).unwrap(), "{}");
+        // a file that does not exist yet is not created.
+        assert!(!index_copy.exists());
+    }
 
-            validators.push(maddress);
+    #[test]
+    fn failed_items_of_batches() {
+        use helpers::BatchItem::{Completed, Failed, Interrupted};
+
+        for (items, n, failed) in [
+            (vec![Completed, Completed], 2, vec![]),
+            (vec![Completed, Failed, Completed], 3, vec![1]),
+            // `force_batch` keeps going after a failure.
+            (vec![Failed, Failed, Completed, Failed], 4, vec![0, 1, 3]),
+            // `batch` stops at the failed item, the ones after it are never dispatched.
+            (vec![Completed, Interrupted(1)], 4, vec![1, 2, 3]),
+            (vec![Interrupted(0)], 3, vec![0, 1, 2]),
+            // with an unknown number of calls only the interrupting item is known.
+            (vec![Completed, Interrupted(1)], 0, vec![1]),
+            (vec![], 0, vec![]),
+        ] {
+            assert_eq!(helpers::failed_items(&items, n), failed, "{items:?} of {n}");
         }
+    }
 
-        Ok(validators)
+    #[test]
+    fn notional_bag_is_the_first_threshold_not_below_the_score() {
+        let thresholds = [10, 100, 1_000];
+        for (score, bag) in [
+            (0, 10),
+            (10, 10),
+            (11, 100),
+            (1_000, 1_000),
+            (1_001, u64::MAX),
+        ] {
+            assert_eq!(helpers::notional_bag(&thresholds, score), bag, "{score}");
+        }
+        assert_eq!(helpers::notional_bag(&[], 5), u64::MAX);
     }
 
-    /// Fetches all the nominators registered in the systen.
-    pub(crate) async fn get_nominators(
-        api: &OnlineClient<SubstrateConfig>,
-    ) -> color_eyre::Result<Targets> {
-        let mut nominators = vec![];
-        let storage_query = staking_parachain::storage().staking().nominators_iter();
+    #[test]
+    fn to_vote_scales_down_with_the_issuance() {
+        let max = u64::MAX as Balance;
+        for (balance, issuance, vote) in [
+            (1_000, 1_000_000, 1_000),
+            (max, max, u64::MAX),
+            // an issuance over `u64::MAX` scales the votes down so that it fits.
+            (1_000, 2 * max, 500),
+            (1_001, 3 * max + 1, 333),
+            (max + 1, max, u64::MAX),
+        ] {
+            assert_eq!(
+                helpers::to_vote(balance, issuance),
+                vote,
+                "{balance} of {issuance}"
+            );
+        }
+    }
 
-        let mut results = api.storage().at_latest().await?.iter(storage_query).await?;
-        while let Some(Ok(kv)) = results.next().await {
-            let (k, _) = kv;
-            let account: Vec<u8> = k.into_iter().rev().take(32).collect();
-            let account: [u8; 32] = account.try_into().expect("32 bytes should fit");
-            let maddress: MultiAddress<AccountId32, ()> = MultiAddress::Address32(account);
-            nominators.push(maddress);
+    #[test]
+    fn prometheus_gauge_sums_the_labels_of_a_gauge() {
+        let body = "\
+# HELP substrate_sub_txpool_validations_scheduled Validations scheduled
+# TYPE substrate_sub_txpool_validations_scheduled gauge
+substrate_sub_txpool_validations_scheduled 12
+substrate_ready_transactions_number{chain=\"a\"} 3
+substrate_ready_transactions_number{chain=\"b\"} 4.0
+substrate_ready_transactions_number_total 100
+";
+        for (name, value) in [
+            ("substrate_sub_txpool_validations_scheduled", Some(12)),
+            ("substrate_ready_transactions_number", Some(7)),
+            ("substrate_ready_transactions", None),
+            ("substrate_block_height", None),
+        ] {
+            assert_eq!(helpers::prometheus_gauge(body, name), value, "{name}");
         }
+    }
 
-        Ok(nominators)
+    #[test]
+    fn chain_path_appends_the_chain_to_the_stem() {
+        use std::path::Path;
+
+        for (path, chain, expected) in [
+            (
+                "staking-cli-state.json",
+                "westend",
+                "staking-cli-state-westend.json",
+            ),
+            ("data/index.json", "kusama", "data/index-kusama.json"),
+            ("/tmp/state", "local", "/tmp/state-local"),
+            ("archive.tar.gz", "a", "archive.tar-a.gz"),
+        ] {
+            assert_eq!(
+                helpers::chain_path(Path::new(path), chain),
+                Path::new(expected)
+            );
+        }
     }
 
-    /// Selects a random `n` number of targets from a vec of validators.
-    pub(crate) fn select_targets(n: usize, validators: Targets) -> Targets {
-        validators
-            .choose_multiple(&mut rand::thread_rng(), n)
-            .cloned()
-            .collect::<Vec<_>>()
+    #[test]
+    fn needs_root_looks_into_batches() {
+        let transfer = || {
+            RuntimeCall::Balances(BalancesCall::transfer_allow_death {
+                dest: Id(AccountId32([1; 32])),
+                value: 1,
+            })
+        };
+        let force_transfer = || {
+            RuntimeCall::Balances(BalancesCall::force_transfer {
+                source: Id(AccountId32([1; 32])),
+                dest: Id(AccountId32([2; 32])),
+                value: 1,
+            })
+        };
+        let batch = |calls| RuntimeCall::Utility(UtilityCall::batch_all { calls });
+
+        for (call, root) in [
+            (RuntimeCall::Staking(StakingCall::chill), false),
+            (RuntimeCall::Staking(StakingCall::force_new_era), true),
+            (transfer(), false),
+            (force_transfer(), true),
+            (batch(vec![transfer(), transfer()]), false),
+            (batch(vec![transfer(), force_transfer()]), true),
+            (batch(vec![batch(vec![force_transfer()])]), true),
+        ] {
+            assert_eq!(helpers::needs_root(&call), root, "{call:?}");
+        }
     }
 
-    /// Generates a key pair from an init seed.
-    pub(crate) fn signer_from_seed(init_seed: &str) -> Keypair {
-        let mut seed = [0; 32];
-        let mut buffer = &mut seed[..];
-        buffer.write(init_seed.as_bytes()).unwrap();
+    #[test]
+    fn perbill_of_percentages() {
+        for (percent, parts) in [
+            (0.0, 0),
+            (5.0, 50_000_000),
+            (12.5, 125_000_000),
+            (0.0000001, 1),
+            (100.0, 1_000_000_000),
+        ] {
+            assert_eq!(helpers::perbill(percent).unwrap().0, parts, "{percent}");
+        }
+        assert!(helpers::perbill(-1.0).is_err());
+        assert!(helpers::perbill(100.1).is_err());
+    }
 
-        Keypair::from_seed(seed).expect("generate keypair should be ok")
+    #[test]
+    fn table_pads_the_columns_to_their_widest_cell() {
+        let rows = vec![
+            vec!["alice".to_string(), "1".to_string()],
+            vec!["bob".to_string(), "1000".to_string()],
+        ];
+        assert_eq!(
+            output::table(&["who", "bond"], &rows),
+            "\
+who   | bond
+------+-----
+alice | 1
+bob   | 1000"
+        );
+        assert_eq!(output::table(&["who"], &[]), "who\n---");
     }
 }